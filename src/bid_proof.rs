@@ -0,0 +1,242 @@
+use p3_field::{Field, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::prove;
+use silent_bid_verifier::bid_proof::{
+    BidProofAir, BidProofCols, BidPublicValues, AMOUNT_BITS, BID_LIMBS, EXPONENT_BITS, LIMB_BITS, NONCE_MODULUS, NUM_BID_PROOF_COLS,
+};
+use silent_bid_verifier::config::{make_evm_challenger, make_evm_config, EvmConfig, Val, EVM_CONFIG_ID};
+use silent_bid_verifier::proof_file::ProofFile;
+use silent_bid_verifier::public_input::Address;
+use crate::reference::{encrypt_bid, AuctionRules};
+
+/// Encrypts `amount` and `nonce` under the owner's public key and proves that the result is a valid bid within
+/// `deposit`, see `silent_bid_verifier::bid_proof`. The proof only holds for `bidder` submitting it to the auction
+/// `auction_id`. Returns the encrypted amount to submit with the proof.
+pub fn prove_bid(
+    amount: u64,
+    nonce: u64,
+    public_exponent: u32,
+    modulus: u32,
+    deposit: u64,
+    bidder: Address,
+    auction_id: u64,
+) -> Result<(Vec<u8>, ProofFile<EvmConfig>), String> {
+    if nonce >= NONCE_MODULUS {
+        return Err(format!("nonce {} is not below {}", nonce, NONCE_MODULUS));
+    }
+    if amount >= 1 << AMOUNT_BITS {
+        return Err(format!("amount {} is not below 2^{}", amount, AMOUNT_BITS));
+    }
+    if amount > deposit {
+        return Err(format!("amount {} is more than the deposit of {}", amount, deposit));
+    }
+    // the auction skips zero limbs, so they can only come after the last non-zero one
+    let value = amount * NONCE_MODULUS + nonce;
+    let limbs: Vec<u64> = (0..BID_LIMBS).map(|i| value >> (i * LIMB_BITS) & 0xffff).collect();
+    if limbs.windows(2).any(|pair| pair[0] == 0 && pair[1] != 0) {
+        return Err(format!("value {} has a zero limb the auction would skip, pick another nonce", value));
+    }
+    let encrypted_amount = encrypt_bid(amount, nonce, public_exponent, modulus, BID_LIMBS, &AuctionRules::default());
+    let public_values = BidPublicValues {
+        modulus: modulus as u64,
+        public_exponent: public_exponent as u64,
+        deposit,
+        encrypted_amount,
+        bidder,
+        auction_id,
+    };
+    public_values.check()?;
+
+    let trace = generate_bid_trace(amount, nonce, public_exponent, modulus, deposit);
+    let elements: Vec<Val> = public_values.to_field_elements()?;
+    let proof = prove(&make_evm_config(), &BidProofAir, &mut make_evm_challenger(), trace, &elements);
+    Ok((public_values.encrypted_amount, ProofFile::new(EVM_CONFIG_ID, &elements, proof)))
+}
+
+/// One run of square-and-multiply rows per limb of `amount * NONCE_MODULUS + nonce`, then at least one padding row.
+pub fn generate_bid_trace<F: PrimeField64>(amount: u64, nonce: u64, public_exponent: u32, modulus: u32, deposit: u64) -> RowMajorMatrix<F> {
+    bid_trace(amount, nonce, public_exponent as u128, modulus, deposit)
+}
+
+/// The trace of `generate_bid_trace` raising the limbs to `exponent`, which may be beyond the field order.
+fn bid_trace<F: PrimeField64>(amount: u64, nonce: u64, exponent: u128, modulus: u32, deposit: u64) -> RowMajorMatrix<F> {
+    let n = modulus as u64;
+    let field_element = |x: u128| F::from_canonical_u64((x % F::ORDER_U64 as u128) as u64);
+    let mut values: Vec<BidProofCols<F>> = Vec::new();
+    let mut registers = BidProofCols::<F>::default();
+    registers.set_amount(F::from_canonical_u64(amount))
+        .set_nonce(F::from_canonical_u64(nonce))
+        .set_amount_bits(bits(amount))
+        .set_nonce_bits(bits(nonce))
+        .set_nonce_slack_bits(bits(NONCE_MODULUS - 1 - nonce))
+        .set_deposit_slack_bits(bits(deposit - amount));
+
+    let mut value = amount * NONCE_MODULUS + nonce;
+    let mut gap: u64 = 1;
+    let mut total: u64 = 0;
+    for i in 0..BID_LIMBS {
+        let limb = value & 0xffff;
+        value >>= LIMB_BITS;
+        total += limb * gap;
+        let mut chunk = [F::zero(); BID_LIMBS];
+        chunk[i] = F::one();
+        let limb_field = F::from_canonical_u64(limb);
+        registers.set_new_limb(F::one())
+            .set_is_step(F::zero())
+            .set_is_padding(F::zero())
+            .set_chunk(chunk)
+            .set_limb(limb_field)
+            .set_limb_bits(bits(limb))
+            .set_is_zero(F::from_bool(limb == 0))
+            .set_limb_inverse(limb_field.try_inverse().unwrap_or(F::zero()))
+            .set_exponent(field_element(exponent))
+            .set_odd(F::zero())
+            .set_steps(F::zero())
+            .set_steps_slack_bits(bits(EXPONENT_BITS as u64))
+            .set_gap(F::from_canonical_u64(gap))
+            .set_value(F::from_canonical_u64(total));
+        set_power(&mut registers, limb, 1, 0, 0);
+        values.push(registers.clone());
+
+        let mut left = exponent;
+        let (mut power, mut r) = (limb, 1);
+        let mut steps: u64 = 0;
+        while left > 0 {
+            let odd = (left & 1) as u64;
+            left >>= 1;
+            steps += 1;
+            let factor = if odd == 1 { power } else { 1 };
+            let q_r = r * factor / n;
+            r = r * factor % n;
+            let q_power = power * power / n;
+            power = power * power % n;
+            registers.set_new_limb(F::zero())
+                .set_is_step(F::one())
+                .set_exponent(field_element(left))
+                .set_odd(F::from_canonical_u64(odd))
+                .set_steps(F::from_canonical_u64(steps))
+                .set_steps_slack_bits(bits((EXPONENT_BITS as u64).wrapping_sub(steps)));
+            set_power(&mut registers, power, r, q_power, q_r);
+            values.push(registers.clone());
+        }
+        if limb != 0 {
+            gap <<= LIMB_BITS;
+        }
+    }
+
+    let height = (values.len() + 1).next_power_of_two();
+    registers.set_new_limb(F::zero()).set_is_step(F::zero()).set_is_padding(F::one());
+    while values.len() < height {
+        values.push(registers.clone());
+    }
+    RowMajorMatrix::new(values.iter().flat_map(|row| row.to_vec()).collect(), NUM_BID_PROOF_COLS)
+}
+
+fn set_power<F: Field>(registers: &mut BidProofCols<F>, power: u64, r: u64, q_power: u64, q_r: u64) {
+    registers.set_power(F::from_canonical_u64(power))
+        .set_r(F::from_canonical_u64(r))
+        .set_q_power(F::from_canonical_u64(q_power))
+        .set_q_r(F::from_canonical_u64(q_r))
+        .set_power_bits(bits(power))
+        .set_r_bits(bits(r))
+        .set_q_power_bits(bits(q_power))
+        .set_q_r_bits(bits(q_r));
+}
+
+/// The `N` little-endian bits of `value`.
+fn bits<F: Field, const N: usize>(value: u64) -> [F; N] {
+    core::array::from_fn(|i| F::from_canonical_u64(value >> i & 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::{AbstractField, PrimeField64};
+    use silent_bid_verifier::bid_proof::{BidProofAir, BidPublicValues, BID_LIMBS, LIMB_BITS, NONCE_MODULUS};
+    use silent_bid_verifier::config::Val;
+    use silent_bid_verifier::debug_builder::failed_sections;
+    use silent_bid_verifier::public_input::{Address, PublicBid};
+    use silent_bid_verifier::{verify_bid, VerifyError};
+    use crate::fixtures::{MODULUS, PRIVATE_EXPONENT, PUBLIC_EXPONENT};
+    use crate::reference::{decrypt_bid, encrypt_bid, AuctionRules, RsaKey};
+    use super::{bid_trace, generate_bid_trace, prove_bid};
+
+    const DEPOSIT: u64 = 5000;
+    const BIDDER: Address = [0x22; 20];
+    const AUCTION_ID: u64 = 7;
+
+    fn public_values(encrypted_amount: Vec<u8>) -> BidPublicValues {
+        BidPublicValues {
+            modulus: MODULUS as u64,
+            public_exponent: PUBLIC_EXPONENT as u64,
+            deposit: DEPOSIT,
+            encrypted_amount,
+            bidder: BIDDER,
+            auction_id: AUCTION_ID,
+        }
+    }
+
+    #[test]
+    fn proves_bids_within_the_deposit() {
+        let (encrypted_amount, proof) = prove_bid(3035, 123, PUBLIC_EXPONENT, MODULUS, DEPOSIT, BIDDER, AUCTION_ID).unwrap();
+        let key = RsaKey { modulus: MODULUS, private_exponent: PRIVATE_EXPONENT };
+        let bid = PublicBid { bidder: BIDDER, encrypted_amount: encrypted_amount.clone() };
+        let decrypted = decrypt_bid(&bid, &key, &AuctionRules::default());
+        assert!(decrypted.valid);
+        assert_eq!((decrypted.amount, decrypted.nonce), (3035, 123));
+
+        let bytes = proof.to_bytes();
+        verify_bid(&bytes, &public_values(encrypted_amount.clone())).unwrap();
+
+        // the proof is bound to the bid, the deposit, the bidder and the auction
+        let mut other = encrypted_amount.clone();
+        other[0] ^= 1;
+        assert!(matches!(verify_bid(&bytes, &public_values(other)), Err(VerifyError::InvalidPublicValues(_))));
+        let larger_deposit = BidPublicValues { deposit: DEPOSIT + 1, ..public_values(encrypted_amount.clone()) };
+        assert!(matches!(verify_bid(&bytes, &larger_deposit), Err(VerifyError::InvalidPublicValues(_))));
+        let other_bidder = BidPublicValues { bidder: [0x33; 20], ..public_values(encrypted_amount.clone()) };
+        assert!(matches!(verify_bid(&bytes, &other_bidder), Err(VerifyError::InvalidPublicValues(_))));
+        let other_auction = BidPublicValues { auction_id: AUCTION_ID + 1, ..public_values(encrypted_amount) };
+        assert!(matches!(verify_bid(&bytes, &other_auction), Err(VerifyError::InvalidPublicValues(_))));
+
+        assert!(prove_bid(DEPOSIT + 1, 0, PUBLIC_EXPONENT, MODULUS, DEPOSIT, BIDDER, AUCTION_ID).is_err());
+        assert!(prove_bid(10, 1000, PUBLIC_EXPONENT, MODULUS, DEPOSIT, BIDDER, AUCTION_ID).is_err());
+    }
+
+    #[test]
+    fn rejects_runs_beyond_the_exponent_bits() {
+        let (amount, nonce) = (3035, 123);
+        let mut public_values = public_values(encrypt_bid(amount, nonce, PUBLIC_EXPONENT, MODULUS, BID_LIMBS, &AuctionRules::default()));
+        let honest: Vec<Val> = public_values.to_field_elements().unwrap();
+        let trace = generate_bid_trace::<Val>(amount, nonce, PUBLIC_EXPONENT, MODULUS, DEPOSIT);
+        assert!(failed_sections(&BidProofAir, &trace, &honest).is_empty());
+
+        // e + p is e in the field, but encrypts the limbs to other 4 bytes
+        let exponent = PUBLIC_EXPONENT as u128 + Val::ORDER_U64 as u128;
+        let value = amount * NONCE_MODULUS + nonce;
+        let mut forged = honest.clone();
+        for i in 0..BID_LIMBS {
+            let chunk = pow_mod(value >> (i * LIMB_BITS) & 0xffff, exponent, MODULUS as u64);
+            forged[3 + i] = Val::from_canonical_u64(chunk);
+        }
+        assert_ne!(forged, honest);
+        let trace = bid_trace::<Val>(amount, nonce, exponent, MODULUS, DEPOSIT);
+        let failed = failed_sections(&BidProofAir, &trace, &forged);
+        assert!(!failed.is_empty());
+        assert!(failed.iter().all(|(_, section)| *section == "end"), "{:?}", failed);
+
+        public_values.public_exponent = 1 << 32;
+        assert!(public_values.check().is_err());
+    }
+
+    fn pow_mod(base: u64, mut exponent: u128, modulus: u64) -> u64 {
+        let (mut power, mut r) = (base as u128 % modulus as u128, 1u128);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                r = r * power % modulus as u128;
+            }
+            power = power * power % modulus as u128;
+            exponent >>= 1;
+        }
+        r as u64
+    }
+}
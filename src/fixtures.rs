@@ -0,0 +1,61 @@
+//! The demo auction of `main`, shared by the tests.
+
+use std::fs;
+use std::path::PathBuf;
+use p3_field::{AbstractField, PrimeField64};
+use p3_goldilocks::Goldilocks;
+use p3_matrix::dense::RowMajorMatrix;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use silent_bid_verifier::public_input::PublicBid;
+use silent_bid_verifier::AuctionPublicValues;
+use crate::generate_execution_trace::generate_execution_trace;
+use crate::keystore::{AuctionKey, KdfParams, Keystore};
+use crate::private_input::PrivateInput;
+use crate::threshold::{partial_decryptions, split_exponent};
+
+pub const MODULUS: u32 = 1875143437;
+pub const PRIVATE_EXPONENT: u32 = 561461413;
+pub const PUBLIC_EXPONENT: u32 = 5153;
+/// Cheap Argon2 costs, so that the tests do not spend their time deriving keystore keys.
+pub const TEST_KDF: KdfParams = KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+pub fn demo_bids() -> Vec<PublicBid> {
+    vec![
+        PublicBid::from_hex("0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfe5", "211be84e0b6176170000000000000000").unwrap(),
+        PublicBid::from_hex("0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfdd", "d1188229623701140000000000000000").unwrap(),
+        PublicBid::from_hex("0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfdd", "8f8eb7400b6176170000000000000000").unwrap(),
+    ]
+}
+
+pub fn private_input() -> PrivateInput<Goldilocks> {
+    PrivateInput::new(Goldilocks::from_canonical_u32(MODULUS), PRIVATE_EXPONENT)
+}
+
+/// Trace and public values of an auction over `bids` with the demo key.
+pub fn auction_trace(bids: &[PublicBid]) -> (RowMajorMatrix<Goldilocks>, Vec<Goldilocks>) {
+    let (trace, winner, winner_amount, hash_value) = generate_execution_trace(bids, &private_input());
+    let public_values = AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner);
+    (trace, public_values.to_field_elements())
+}
+
+/// Every operator's partial decryptions of the demo bids, with the demo key split among 3 operators.
+pub fn threshold_partials() -> Vec<Vec<u32>> {
+    let shares = split_exponent(PRIVATE_EXPONENT, MODULUS, 3, &mut StdRng::seed_from_u64(7));
+    shares.iter().map(|share| partial_decryptions(*share, MODULUS, &demo_bids())).collect()
+}
+
+/// A fresh directory for a test, unique per process and `name`.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("silent-bid-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A keystore in a fresh directory holding the demo key as `demo`.
+pub fn demo_keystore(name: &str) -> Keystore {
+    let keystore = Keystore::open(temp_dir(name), "passphrase").unwrap().with_kdf(TEST_KDF);
+    keystore.import("demo", &AuctionKey { modulus: MODULUS, private_exponent: PRIVATE_EXPONENT }).unwrap();
+    keystore
+}
@@ -1,39 +1,60 @@
-use p3_field::{Field};
+use core::borrow::Borrow;
+use p3_field::{Field, PrimeField64};
 use p3_matrix::dense::RowMajorMatrix;
-use crate::columns::{BidCols, BASE, DECODED_BYTES, NUM_BID_COLS, READ_BYTES};
+use silent_bid_verifier::bid_proof::VALUE_BITS;
+use silent_bid_verifier::columns::{
+    BidCols, MembershipCols, Phase, ReceiptCols, RevealCols, ShareCols, StatisticsCols, TimeLockCols, ADDRESS_LIMBS,
+    ADDRESS_LIMB_BITS, BASE, DECODED_BYTES, MAX_OPERATORS, NUM_BID_COLS, NUM_MEMBERSHIP_COLS, NUM_RECEIPT_COLS,
+    NUM_REVEAL_COLS, NUM_SHARE_COLS, NUM_STATISTICS_COLS, NUM_TIME_LOCK_COLS, PERMUTATION_COLS, PUBLIC_EXPONENT_BITS,
+    READ_BYTES, RECEIPT_DEPTH, WHITELIST_DEPTH,
+};
+use silent_bid_verifier::poseidon2::{
+    address_limbs, compress_input, permute_with_rounds, range_input, share_input, DIGEST, WIDTH,
+};
+use silent_bid_verifier::receipt::salted;
+use silent_bid_verifier::reveal::RevealedBid;
+use silent_bid_verifier::whitelist::Whitelist;
 use crate::private_input::PrivateInput;
-use crate::public_input::PublicBid;
-use crate::utils::{address_to_bytes, bytes_to_address, hex_to_bytes};
+use crate::time_lock::inverse_mod;
+use silent_bid_verifier::public_input::{Address, PublicBid, NUM_STATISTICS};
 
-pub fn generate_execution_trace<F: Field>(
+/// The trace of the auction of `bidders`, decrypted with `private_input`, with the winner, its amount and the bid hash.
+/// The private exponent is only read from `private_input`, which zeroizes it when dropped.
+pub fn generate_execution_trace<F: PrimeField64>(
     bidders: &[PublicBid],
     private_input: &PrivateInput<F>,
-    d: u32,
-    n: u32,
-) -> (RowMajorMatrix<F>, String, F, F) {
+) -> (RowMajorMatrix<F>, Address, F, F) {
 
     let mut values: Vec<BidCols<F>> = Vec::new();
-    let mut registers = BidCols::<F>::default();
-    registers.hash_lim = F::one();
+    let mut registers = BidCols::<F> { hash_lim: F::one(), ..BidCols::default() };
 
     let one = F::from_canonical_u32(1);
     let zero = F::zero();
-    let exponent = private_input.private_exponent;
+    let exponent = F::from_canonical_u32(private_input.private_exponent);
+    let n = private_input.private_modulus.as_canonical_u64() as u32;
     let u16_gap: u64 = u16::MAX as u64 + 1;
-    let u16_max: u64 = u16::MAX as u64;
     let base = F::from_canonical_u64(311);
     let mut winner_amount = 0;
     let mut change_winner = 0;
     let mut winner_add= [0; 20];
+    let partials = &private_input.partial_decryptions;
+    let threshold = !partials.is_empty();
+    // index of the next non-zero 4 bytes in the operators' partial decryptions
+    let mut chunk = 0;
+    // the solved puzzle of the current bid, in time-lock mode
+    let mut mask = 0;
+    let mut revealed = Vec::with_capacity(bidders.len());
 
     for public_bid in bidders {
         change_winner = 0;
-        let mut encrypted_amount = hex_to_bytes(&public_bid.encrypted_amount).unwrap();
+        let mut encrypted_amount = public_bid.encrypted_amount.clone();
         while encrypted_amount.len() % 4 != 0 {
             encrypted_amount.push(0);
         }
 
-        let address_bytes: [u8; 20] = address_to_bytes(&public_bid.bidder)[0..20].try_into().expect("wrong address");
+        let address_bytes = public_bid.bidder;
+        // with a whitelist, the bids of other addresses are decrypted but invalid
+        let is_member = private_input.whitelist.as_ref().is_none_or(|whitelist| whitelist.contains(&address_bytes));
 
         new_bidder(&mut registers, exponent, &address_bytes);
         let address: [F; 20] = address_bytes.iter().map(|e| F::from_canonical_u8(*e)).collect::<Vec<F>>().try_into().expect("slice with incorrect length");
@@ -57,34 +78,100 @@ pub fn generate_execution_trace<F: Field>(
             let mut current_value = u32::from_le_bytes(_vec) as u64;
 
             // init
-            if is_error == 1 {
-                registers.change(zero, zero, one, zero, zero, encoded_vec, read_value, zero, exponent, zero, one, zero, decoded_vec,
-                                 one, F::from_canonical_u64(gap), registers.final_value, registers.read_address, registers.hash_lim, registers.hash_value,
-                                 registers.bid_amount, registers.nonce, registers.winner_amount, registers.change_winner, registers.winner_address);
-                values.push(registers.clone());
-                start += 4;
-                registers.hash_lim *= base;
-                continue;
+            let (exponent_value, error) = if is_error == 1 {
+                (exponent, one)
             } else if current_value == 0 {
-                registers.change(zero, zero, one, zero, zero, encoded_vec, read_value, zero, zero, zero, one, zero, decoded_vec, zero,
-                                 F::from_canonical_u64(gap), registers.final_value, registers.read_address, registers.hash_lim, registers.hash_value,
-                                 registers.bid_amount, registers.nonce, registers.winner_amount,  registers.change_winner, registers.winner_address);
+                (zero, zero)
+            } else {
+                (exponent, zero)
+            };
+            // in time-lock mode, only the puzzle, the first 4 bytes, is squared
+            let puzzle = private_input.squarings.is_some() && start == 0;
+            let exponent_value = match private_input.squarings {
+                Some(squarings) if puzzle => F::from_canonical_u32(squarings),
+                Some(_) => zero,
+                None => exponent_value,
+            };
+            registers.set_phase(Phase::Reading)
+                .set_read_bytes(encoded_vec)
+                .set_current_value(read_value)
+                .set_quotient_value(zero)
+                .set_exponent_value(exponent_value)
+                .set_odd_exponent(zero)
+                .set_r(one)
+                .set_q_r(zero)
+                .set_decoded_bytes(decoded_vec)
+                .set_is_error(error)
+                .set_gap(F::from_canonical_u64(gap));
+            values.push(registers.clone());
+            start += 4;
+            registers.hash_lim *= base;
+            if let Some(squarings) = private_input.squarings {
+                if puzzle {
+                    for remaining in (0..squarings).rev() {
+                        let q = (current_value * current_value) / (n as u64);
+                        current_value = (current_value * current_value) % (n as u64);
+                        registers.set_phase(Phase::Exponent)
+                            .set_current_value(F::from_canonical_u64(current_value))
+                            .set_quotient_value(F::from_canonical_u64(q))
+                            .set_exponent_value(F::from_canonical_u32(remaining));
+                        values.push(registers.clone());
+                    }
+                    // the next row takes the solved puzzle as its mask
+                    mask = current_value;
+                    continue;
+                }
+                if current_value == 0 || is_error == 1 {
+                    continue;
+                }
+                let Some(inverse) = inverse_mod(mask, n as u64) else {
+                    // a mask sharing a factor with the modulus unmasks nothing: the bid is invalid
+                    is_error = 1;
+                    registers.set_phase(Phase::Write)
+                        .set_current_value(zero)
+                        .set_quotient_value(zero)
+                        .set_r(zero)
+                        .set_is_error(one);
+                    values.push(registers.clone());
+                    gap *= u16_gap;
+                    continue;
+                };
+                let limb = current_value * inverse % (n as u64);
+                let q = (mask * limb - current_value) / (n as u64);
+                write_value(&mut registers, limb, q, &mut is_error, &mut final_value, gap);
                 values.push(registers.clone());
-                start += 4;
-                registers.hash_lim *= base;
+                gap *= u16_gap;
                 continue;
-            } else {
-                registers.change(zero, zero, one, zero, zero, encoded_vec, read_value, zero, exponent, zero, one, zero, decoded_vec, zero,
-                                 F::from_canonical_u64(gap), registers.final_value, registers.read_address, registers.hash_lim, registers.hash_value,
-                                 registers.bid_amount, registers.nonce, registers.winner_amount,
-                                 registers.change_winner, registers.winner_address);
+            }
+            if current_value == 0 || (is_error == 1 && !threshold) {
+                continue;
+            }
+
+            if threshold {
+                // multiply in each operator's partial decryption, in turn
+                let mut r: u64 = 1;
+                for operator_partials in partials {
+                    let partial = operator_partials[chunk] as u64;
+                    let q_r = (r * partial) / (n as u64);
+                    r = (r * partial) % (n as u64);
+                    registers.set_phase(Phase::Share)
+                        .set_r(F::from_canonical_u64(r))
+                        .set_q_r(F::from_canonical_u64(q_r));
+                    values.push(registers.clone());
+                }
+                chunk += 1;
+                if is_error == 1 {
+                    gap *= u16_gap;
+                    continue;
+                }
+                write_value(&mut registers, r, 0, &mut is_error, &mut final_value, gap);
                 values.push(registers.clone());
-                start += 4;
-                registers.hash_lim *= base;
+                gap *= u16_gap;
+                continue;
             }
 
             // exponent to decrypt
-            let mut exp = d;
+            let mut exp = private_input.private_exponent;
             let mut r: u64 = 1;
             let mut q: u64 = 0;
             let mut q_r: u64 = 0;
@@ -99,10 +186,13 @@ pub fn generate_execution_trace<F: Field>(
                 q = (current_value * current_value) / (n as u64);
                 current_value = (current_value  * current_value) % (n as u64);
 
-                registers.change(zero, zero, zero, one, zero, registers.read_bytes, F::from_canonical_u64(current_value), F::from_canonical_u64(q), F::from_canonical_u32(new_exp),
-                                 F::from_canonical_u32(odd), F::from_canonical_u64(r), F::from_canonical_u64(q_r), registers.decoded_bytes,
-                                 registers.is_error, registers.gap, registers.final_value, registers.read_address, registers.hash_lim, registers.hash_value,
-                                 registers.bid_amount, registers.nonce,  registers.winner_amount, registers.change_winner, registers.winner_address);
+                registers.set_phase(Phase::Exponent)
+                    .set_current_value(F::from_canonical_u64(current_value))
+                    .set_quotient_value(F::from_canonical_u64(q))
+                    .set_exponent_value(F::from_canonical_u32(new_exp))
+                    .set_odd_exponent(F::from_canonical_u32(odd))
+                    .set_r(F::from_canonical_u64(r))
+                    .set_q_r(F::from_canonical_u64(q_r));
 
                 exp = new_exp;
                 values.push(registers.clone());
@@ -111,36 +201,20 @@ pub fn generate_execution_trace<F: Field>(
             // write the decrypted value
             q = (current_value * r) / (n as u64);
             let current_value = (current_value * r) % (n as u64);
-            if current_value > u16_max {
-                is_error = 1;
-                let decoded_byte = (current_value as u32).to_le_bytes();
-                let decoded_vec: [F; 4] = decoded_byte.iter().map(|e| F::from_canonical_u8(*e)).collect::<Vec<F>>().try_into().expect("slice with incorrect length");
-                registers.change(zero, zero, zero, zero, zero, registers.read_bytes, F::from_canonical_u64(current_value), F::from_canonical_u64(q), zero, zero,
-                                 zero, zero, decoded_vec, one, registers.gap, F::from_canonical_u64(final_value), registers.read_address, registers.hash_lim, registers.hash_value,
-                                 registers.bid_amount, registers.nonce, registers.winner_amount, registers.change_winner, registers.winner_address);
-
-            } else {
-                let decoded_byte = (current_value as u32).to_le_bytes();
-                let decoded_vec: [F; 4] = decoded_byte.iter().map(|e| F::from_canonical_u8(*e)).collect::<Vec<F>>().try_into().expect("slice with incorrect length");
-
-                final_value += current_value * gap;
-                registers.change(zero, zero, zero, zero, zero, registers.read_bytes, F::from_canonical_u64(current_value), F::from_canonical_u64(q), zero, zero, one, zero,
-                                 decoded_vec, zero, registers.gap, F::from_canonical_u64(final_value), registers.read_address, registers.hash_lim, registers.hash_value,
-                                 registers.bid_amount, registers.nonce, registers.winner_amount, registers.change_winner, registers.winner_address);
-            }
-
+            write_value(&mut registers, current_value, q, &mut is_error, &mut final_value, gap);
             values.push(registers.clone());
             gap *= u16_gap;
         }
 
 
         // compute answer
-        if is_error == 1 {
-            registers.change_winner = zero;
-            registers.change(zero, zero, zero, zero, one, registers.read_bytes, registers.current_value, registers.quotient_value, registers.exponent_value,
-                             registers.odd_exponent, registers.r, registers.q_r, registers.decoded_bytes, registers.is_error, registers.gap, registers.final_value,
-                             registers.read_address, registers.hash_lim, registers.hash_value, registers.bid_amount, registers.nonce,
-                             registers.winner_amount, zero, registers.winner_address);
+        if is_error == 1 || !is_member {
+            registers.set_phase(Phase::ComputingWinner)
+                .set_is_error(one)
+                .set_bid_amount(zero)
+                .set_nonce(zero)
+                .set_change_winner(zero);
+            revealed.push(RevealedBid { bidder: address_bytes, bid_amount: 0, nonce: 0, is_valid: false });
         } else {
             let nonce = final_value % 1000;
             let bid_amount = final_value / 1000;
@@ -154,10 +228,12 @@ pub fn generate_execution_trace<F: Field>(
             } else {
                 change_winner = 0;
             }
-            registers.change(zero, zero, zero, zero, one, registers.read_bytes, registers.current_value, registers.quotient_value, registers.exponent_value,
-                             registers.odd_exponent, registers.r, registers.q_r, registers.decoded_bytes, registers.is_error, registers.gap, registers.final_value,
-                             registers.read_address, registers.hash_lim, registers.hash_value, F::from_canonical_u64(bid_amount), F::from_canonical_u64(nonce),
-                             F::from_canonical_u64(winner_amount), F::from_canonical_u64(change_winner), registers.winner_address);
+            revealed.push(RevealedBid { bidder: address_bytes, bid_amount, nonce, is_valid: true });
+            registers.set_phase(Phase::ComputingWinner)
+                .set_bid_amount(F::from_canonical_u64(bid_amount))
+                .set_nonce(F::from_canonical_u64(nonce))
+                .set_winner_amount(F::from_canonical_u64(winner_amount))
+                .set_change_winner(F::from_canonical_u64(change_winner));
         }
         values.push(registers.clone());
 
@@ -165,18 +241,319 @@ pub fn generate_execution_trace<F: Field>(
     }
 
     let height = values.len().next_power_of_two();
-    registers.is_dummy = one;
+    registers.set_phase(Phase::Dummy);
     while values.len() < height {
         values.push(registers.clone());
     }
 
-    let trace = RowMajorMatrix::new(values.iter().flat_map(|r| r.to_vec()).collect(), NUM_BID_COLS);
+    // the `MembershipCols` of a whitelist, then the `RevealCols` in full reveal mode, the `ReceiptCols`, the
+    // `StatisticsCols`, and the `TimeLockCols` or the `ShareCols` of the decryption follow the `BidCols`
+    let membership = private_input.whitelist.as_ref().map(|whitelist| membership_rows(&values, bidders, whitelist));
+    let reveal = private_input.reveal.then(|| reveal_rows(&values, &revealed));
+    let receipts = private_input.receipt_salts.as_ref().map(|salts| {
+        assert_eq!(salts.len(), bidders.len(), "a receipt salt per bid");
+        receipt_rows(&values, &salted(&revealed, salts))
+    });
+    let statistics = private_input.statistics.then(|| statistics_rows(&values));
+    let time_lock = private_input.squarings.is_some().then(|| time_lock_rows(&values));
+    let shares = threshold.then(|| share_rows(&values, partials, n as u64, private_input.public_exponent));
+    let width = NUM_BID_COLS
+        + membership.as_ref().map_or(0, |_| NUM_MEMBERSHIP_COLS)
+        + reveal.as_ref().map_or(0, |_| NUM_REVEAL_COLS)
+        + receipts.as_ref().map_or(0, |_| NUM_RECEIPT_COLS)
+        + statistics.as_ref().map_or(0, |_| NUM_STATISTICS_COLS)
+        + time_lock.as_ref().map_or(0, |_| NUM_TIME_LOCK_COLS)
+        + shares.as_ref().map_or(0, |_| NUM_SHARE_COLS);
+    let rows = values.iter().enumerate().flat_map(|(i, row)| {
+        row.to_vec()
+            .into_iter()
+            .chain(membership.iter().flat_map(move |membership| membership[i].to_vec()))
+            .chain(reveal.iter().flat_map(move |reveal| reveal[i].to_vec()))
+            .chain(receipts.iter().flat_map(move |receipts| receipts[i].to_vec()))
+            .chain(statistics.iter().flat_map(move |statistics| statistics[i].to_vec()))
+            .chain(time_lock.iter().flat_map(move |time_lock| time_lock[i].to_vec()))
+            .chain(shares.iter().flat_map(move |shares| shares[i].to_vec()))
+    });
+    let trace = RowMajorMatrix::new(rows.collect(), width);
 
-    #[cfg(debug_assertions)]
-    let rows: &[BidCols<F>] = &values;
+    (trace, winner_add, registers.winner_amount, registers.hash_value)
+}
 
-    let winner_add_str = bytes_to_address(&winner_add);
-    (trace, winner_add_str, registers.winner_amount, registers.hash_value)
+/// The value of every statistic over all the bids of `trace`, see `AuctionStatistics`.
+pub fn trace_statistics<F: PrimeField64>(trace: &RowMajorMatrix<F>) -> [u64; NUM_STATISTICS] {
+    let revealed = revealed_bids(trace);
+    let valid_bids = revealed.iter().filter(|bid| bid.is_valid).count() as u64;
+    let bid_sum = revealed.iter().filter(|bid| bid.is_valid).map(|bid| bid.bid_amount).sum();
+    [valid_bids, revealed.len() as u64 - valid_bids, bid_sum]
+}
+
+/// Every bid as the computing winner rows of `trace` decided it, in submission order, see `reveal::RevealedBid`.
+pub fn revealed_bids<F: PrimeField64>(trace: &RowMajorMatrix<F>) -> Vec<RevealedBid> {
+    trace.values.chunks(trace.width).filter_map(|row| {
+        let row: &BidCols<F> = row[..NUM_BID_COLS].borrow();
+        if row.computing_winner != F::one() || row.is_dummy == F::one() {
+            return None;
+        }
+        Some(RevealedBid {
+            bidder: row.read_address.map(|byte| byte.as_canonical_u64() as u8),
+            bid_amount: row.bid_amount.as_canonical_u64(),
+            nonce: row.nonce.as_canonical_u64(),
+            is_valid: row.is_error == F::zero(),
+        })
+    }).collect()
+}
+
+/// The `RevealCols` of every row: each computing winner row hashes its bid, the next of `revealed`, into the chain,
+/// the other rows carry it.
+fn reveal_rows<F: Field>(values: &[BidCols<F>], revealed: &[RevealedBid]) -> Vec<RevealCols<F>> {
+    let mut revealed = revealed.iter();
+    let mut chain = [F::zero(); DIGEST];
+    values.iter().map(|row| {
+        let mut reveal = RevealCols::<F>::default();
+        if row.computing_winner == F::one() && row.is_dummy == F::zero() {
+            let bid = revealed.next().expect("a computing winner row per bid");
+            let leaf = permute_with_rounds(bid.leaf_input());
+            let rounds = permute_with_rounds(compress_input(chain, leaf.digest()));
+            reveal.leaf_rounds = leaf.columns();
+            reveal.chain_rounds = rounds.columns();
+            chain = rounds.digest();
+        }
+        reveal.reveal_hash = chain;
+        reveal
+    }).collect()
+}
+
+/// The `ReceiptCols` of every row: each computing winner row inserts its bid, the next of `salted` whose nonces are
+/// the salts, into the tree of `receipt::commitment`, the other rows carry it.
+fn receipt_rows<F: Field>(values: &[BidCols<F>], salted: &[RevealedBid]) -> Vec<ReceiptCols<F>> {
+    assert!(salted.len() <= 1 << RECEIPT_DEPTH, "at most 2^{} bids get receipts", RECEIPT_DEPTH);
+    let mut salted = salted.iter().enumerate();
+    let mut tree = ReceiptCols::<F>::default();
+    values.iter().map(|row| {
+        let mut receipt = ReceiptCols::<F>::default();
+        if row.computing_winner == F::one() && row.is_dummy == F::zero() {
+            let (index, bid) = salted.next().expect("a computing winner row per bid");
+            let leaf = permute_with_rounds(bid.leaf_input());
+            receipt.salt = F::from_canonical_u64(bid.nonce);
+            receipt.leaf_rounds = leaf.columns();
+            let mut node = leaf.digest();
+            for level in 0..RECEIPT_DEPTH {
+                let is_right = index >> level & 1 == 1;
+                let frontier = &mut tree.frontier[level * DIGEST..(level + 1) * DIGEST];
+                let input = if is_right {
+                    compress_input(<[F; DIGEST]>::try_from(&*frontier).unwrap(), node)
+                } else {
+                    frontier.copy_from_slice(&node);
+                    compress_input(node, [F::zero(); DIGEST])
+                };
+                let rounds = permute_with_rounds(input);
+                receipt.index_bits[level] = F::from_bool(is_right);
+                receipt.level_inputs[level * WIDTH..(level + 1) * WIDTH].copy_from_slice(&input);
+                receipt.level_rounds[level * PERMUTATION_COLS..(level + 1) * PERMUTATION_COLS].copy_from_slice(&rounds.columns());
+                node = rounds.digest();
+            }
+            tree.root = node;
+            tree.count += F::one();
+        }
+        receipt.root = tree.root;
+        receipt.count = tree.count;
+        receipt.frontier = tree.frontier;
+        receipt
+    }).collect()
+}
+
+/// The `StatisticsCols` of every row: each computing winner row counts its bid, the other rows carry the counts.
+fn statistics_rows<F: Field>(values: &[BidCols<F>]) -> Vec<StatisticsCols<F>> {
+    let mut statistics = StatisticsCols::<F>::default();
+    values.iter().map(|row| {
+        if row.computing_winner == F::one() && row.is_dummy == F::zero() {
+            if row.is_error == F::one() {
+                statistics.invalid_bids += F::one();
+            } else {
+                statistics.valid_bids += F::one();
+                statistics.bid_sum += row.bid_amount;
+            }
+        }
+        statistics.clone()
+    }).collect()
+}
+
+/// The `TimeLockCols` of every row: the row after the last squaring of a puzzle takes it as its mask, the other rows
+/// carry it.
+fn time_lock_rows<F: Field>(values: &[BidCols<F>]) -> Vec<TimeLockCols<F>> {
+    let mut mask = F::zero();
+    let mut rows = Vec::with_capacity(values.len());
+    for (i, row) in values.iter().enumerate() {
+        if i > 0 && values[i - 1].is_exponent == F::one() && row.is_reading == F::one() {
+            mask = values[i - 1].current_value;
+        }
+        rows.push(TimeLockCols { mask });
+    }
+    rows
+}
+
+/// The `ShareCols` of every row: each share row multiplies in the partial of its operator, from `partials`, and hashes
+/// it into that operator's chain, the other rows carry the chains, and each write row after share rows re-encrypts
+/// its decryption with `public_exponent`.
+fn share_rows<F: PrimeField64>(
+    values: &[BidCols<F>],
+    partials: &[Vec<u32>],
+    modulus: u64,
+    public_exponent: u32,
+) -> Vec<ShareCols<F>> {
+    assert!(public_exponent >> PUBLIC_EXPONENT_BITS == 0, "the public exponent has at most {} bits", PUBLIC_EXPONENT_BITS);
+    let bits: [u32; PUBLIC_EXPONENT_BITS] = core::array::from_fn(|i| public_exponent >> (PUBLIC_EXPONENT_BITS - 1 - i) & 1);
+    let mut chains = [[F::zero(); DIGEST]; MAX_OPERATORS];
+    let mut last_share: Option<&BidCols<F>> = None;
+    // the operator of the next share row, and the index of the 4 bytes it decrypts
+    let (mut operator, mut chunk) = (0, 0);
+    let mut rows = Vec::with_capacity(values.len());
+    for row in values {
+        let mut shares = ShareCols::<F> { exponent_bits: bits.map(F::from_canonical_u32), ..ShareCols::default() };
+        set_value_bits(&mut shares.modulus_bits, modulus);
+        if row.is_exponent == F::one() {
+            let j = operator;
+            shares.partial = F::from_canonical_u32(partials[j][chunk]);
+            shares.operator[j] = F::one();
+            (operator, chunk) = if j + 1 == partials.len() { (0, chunk + 1) } else { (j + 1, chunk) };
+            let rounds = permute_with_rounds(share_input(chains[j], shares.partial));
+            shares.share_input = chains[j];
+            shares.share_rounds = rounds.columns();
+            chains[j] = rounds.digest();
+        } else if let Some(last_share) = last_share.filter(|last_share| last_share.is_error == F::zero()) {
+            // square-and-multiply the decryption back into the read 4 bytes
+            let decryption = row.current_value.as_canonical_u64();
+            let mut power: u64 = 1;
+            for (i, bit) in bits.into_iter().enumerate() {
+                let square = power * power;
+                let product = (square % modulus) * if bit == 1 { decryption } else { 1 };
+                let values = [square % modulus, square / modulus, product % modulus, product / modulus];
+                shares.squares[i] = F::from_canonical_u64(values[0]);
+                shares.square_quotients[i] = F::from_canonical_u64(values[1]);
+                shares.powers[i] = F::from_canonical_u64(values[2]);
+                shares.power_quotients[i] = F::from_canonical_u64(values[3]);
+                let range = i * VALUE_BITS..(i + 1) * VALUE_BITS;
+                set_value_bits(&mut shares.squares_bits[range.clone()], values[0]);
+                set_value_bits(&mut shares.square_quotients_bits[range.clone()], values[1]);
+                set_value_bits(&mut shares.powers_bits[range.clone()], values[2]);
+                set_value_bits(&mut shares.power_quotients_bits[range], values[3]);
+                power = product % modulus;
+            }
+            let encrypted_quotient = last_share.current_value.as_canonical_u64() / modulus;
+            shares.encrypted_quotient = F::from_canonical_u64(encrypted_quotient);
+            set_value_bits(&mut shares.encrypted_quotient_bits, encrypted_quotient);
+            set_value_bits(&mut shares.decryption_bits, decryption);
+            set_value_bits(&mut shares.decryption_slack_bits, modulus - 1 - decryption);
+        }
+        shares.share_hash = core::array::from_fn(|i| chains[i / DIGEST][i % DIGEST]);
+        last_share = (row.is_exponent == F::one()).then_some(row);
+        rows.push(shares);
+    }
+    rows
+}
+
+/// Writes the little-endian bits of `value` into `bits`.
+fn set_value_bits<F: Field>(bits: &mut [F], value: u64) {
+    for (i, bit) in bits.iter_mut().enumerate() {
+        *bit = F::from_canonical_u64(value >> i & 1);
+    }
+}
+
+/// The `MembershipCols` of every row: each new bidder row hashes the whitelist range of its address, the other rows
+/// of the bidder only keep whether it is listed.
+fn membership_rows<F: Field>(values: &[BidCols<F>], bidders: &[PublicBid], whitelist: &Whitelist) -> Vec<MembershipCols<F>> {
+    let mut bidders = bidders.iter();
+    let mut is_member = F::zero();
+    values.iter().map(|row| {
+        if row.new_bidder == F::one() {
+            let address = bidders.next().expect("a new bidder row per bid").bidder;
+            let membership = membership(&address, whitelist);
+            is_member = membership.is_member;
+            return membership;
+        }
+        let mut membership = MembershipCols::default();
+        if row.is_dummy == F::zero() {
+            membership.is_member = is_member;
+        }
+        membership
+    }).collect()
+}
+
+/// Hashes the whitelist range holding `address` up to the root, and for an address that is not listed shows that it
+/// lies strictly inside the range.
+fn membership<F: Field>(address: &Address, whitelist: &Whitelist) -> MembershipCols<F> {
+    let mut membership = MembershipCols::<F>::default();
+    let range = whitelist.range(address);
+    membership.is_member = F::from_bool(range.low == *address);
+    if range.low != *address {
+        (membership.above_bits, membership.above_borrows) = less_than(&range.low, address);
+        (membership.below_bits, membership.below_borrows) = less_than(address, &range.next);
+    }
+    let input = range_input(&range.low, &range.next);
+    membership.low.copy_from_slice(&input[..ADDRESS_LIMBS]);
+    membership.next.copy_from_slice(&input[ADDRESS_LIMBS..]);
+    let rounds = permute_with_rounds(input);
+    membership.leaf_rounds = rounds.columns();
+    let mut node = rounds.digest();
+    let path = range.path;
+    for level in 0..WHITELIST_DEPTH {
+        let sibling = path.siblings[level].map(F::from_canonical_u64);
+        let input = if path.is_right[level] { compress_input(sibling, node) } else { compress_input(node, sibling) };
+        let rounds = permute_with_rounds(input);
+        membership.path_bits[level] = F::from_bool(path.is_right[level]);
+        membership.siblings[level * DIGEST..(level + 1) * DIGEST].copy_from_slice(&sibling);
+        membership.level_inputs[level * WIDTH..(level + 1) * WIDTH].copy_from_slice(&input);
+        membership.level_rounds[level * PERMUTATION_COLS..(level + 1) * PERMUTATION_COLS].copy_from_slice(&rounds.columns());
+        node = rounds.digest();
+    }
+    membership
+}
+
+/// The bits of every limb of `upper - lower - 1` and whether each limb below the first borrows from the one above,
+/// for `lower < upper`.
+fn less_than<F: Field>(lower: &Address, upper: &Address) -> ([F; ADDRESS_LIMBS * ADDRESS_LIMB_BITS], [F; ADDRESS_LIMBS - 1]) {
+    let (lower, upper) = (address_limbs(lower), address_limbs(upper));
+    let mut bits = [F::zero(); ADDRESS_LIMBS * ADDRESS_LIMB_BITS];
+    let mut borrows = [F::zero(); ADDRESS_LIMBS - 1];
+    let mut lent = 1;
+    for i in (0..ADDRESS_LIMBS).rev() {
+        let mut limb = upper[i] as i64 - lower[i] as i64 - lent;
+        lent = (limb < 0) as i64;
+        if i > 0 {
+            borrows[i - 1] = F::from_bool(limb < 0);
+        }
+        limb += lent << ADDRESS_LIMB_BITS;
+        for (k, bit) in bits[i * ADDRESS_LIMB_BITS..(i + 1) * ADDRESS_LIMB_BITS].iter_mut().enumerate() {
+            *bit = F::from_bool(limb >> k & 1 == 1);
+        }
+    }
+    assert_eq!(lent, 0, "the lower address is below the upper one");
+    (bits, borrows)
+}
+
+/// Sets the write row of a decrypted `current_value`, entering the error state when it does not fit in 16 bits.
+fn write_value<F: Field>(registers: &mut BidCols<F>, current_value: u64, q: u64, is_error: &mut u64, final_value: &mut u64, gap: u64) {
+    let zero = F::zero();
+    let one = F::one();
+    let decoded_byte = (current_value as u32).to_le_bytes();
+    let decoded_vec: [F; 4] = decoded_byte.iter().map(|e| F::from_canonical_u8(*e)).collect::<Vec<F>>().try_into().expect("slice with incorrect length");
+    let (write_r, error) = if current_value > u16::MAX as u64 {
+        *is_error = 1;
+        (zero, one)
+    } else {
+        *final_value += current_value * gap;
+        (one, zero)
+    };
+    registers.set_phase(Phase::Write)
+        .set_current_value(F::from_canonical_u64(current_value))
+        .set_quotient_value(F::from_canonical_u64(q))
+        .set_exponent_value(zero)
+        .set_odd_exponent(zero)
+        .set_r(write_r)
+        .set_q_r(zero)
+        .set_decoded_bytes(decoded_vec)
+        .set_is_error(error)
+        .set_final_value(F::from_canonical_u64(*final_value));
 }
 
 pub fn new_bidder<F: Field> (registers: &mut BidCols<F>, exponent: F, address_bytes: &[u8]) {
@@ -184,9 +561,23 @@ pub fn new_bidder<F: Field> (registers: &mut BidCols<F>, exponent: F, address_by
     let zero = F::zero();
     let address: [F; 20] = address_bytes.iter().map(|e| F::from_canonical_u8(*e)).collect::<Vec<F>>().try_into().expect("slice with incorrect length");
     let new_hash_value = hash_address(registers.hash_value, address_bytes, registers.hash_lim);
-    registers.change(zero, one, zero, zero, zero, [zero; READ_BYTES], zero, zero, exponent, zero, one,
-                     zero, [zero; DECODED_BYTES], zero, one, zero, address, registers.hash_lim, new_hash_value,
-                     zero, zero, registers.winner_amount, zero, registers.winner_address);
+    registers.set_phase(Phase::NewBidder)
+        .set_read_bytes([zero; READ_BYTES])
+        .set_current_value(zero)
+        .set_quotient_value(zero)
+        .set_exponent_value(exponent)
+        .set_odd_exponent(zero)
+        .set_r(one)
+        .set_q_r(zero)
+        .set_decoded_bytes([zero; DECODED_BYTES])
+        .set_is_error(zero)
+        .set_gap(one)
+        .set_final_value(zero)
+        .set_read_address(address)
+        .set_hash_value(new_hash_value)
+        .set_bid_amount(zero)
+        .set_nonce(zero)
+        .set_change_winner(zero);
 }
 
 pub fn hash_address<F: Field> (hash_value: F, address: &[u8], hash_lim: F) -> F {
@@ -201,4 +592,149 @@ pub fn hash_address<F: Field> (hash_value: F, address: &[u8], hash_lim: F) -> F
         new_hash_lim *= base;
     }
     new_hash_value
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use core::borrow::Borrow;
+    use p3_field::{AbstractField, PrimeField64};
+    use p3_goldilocks::Goldilocks;
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use silent_bid_verifier::air::ProverAir;
+    use silent_bid_verifier::columns::{membership_col, BidCols, NUM_BID_COLS};
+    use silent_bid_verifier::debug_builder::check_constraints;
+    use silent_bid_verifier::receipt::{self, BidReceipt};
+    use silent_bid_verifier::reveal::{commitment, RevealedBid};
+    use silent_bid_verifier::whitelist::Whitelist;
+    use silent_bid_verifier::{verify_auction, AuctionPublicValues, AuctionStatistics, VerifyError};
+    use crate::fixtures::{demo_bids, private_input, MODULUS};
+    use crate::prove::{prove_auction, prove_auction_evm};
+    use super::{generate_execution_trace, revealed_bids, trace_statistics};
+
+    /// Sets the `MembershipCols::is_member` of every row of bid `bid` of `trace` to `is_member`.
+    fn set_membership(trace: &mut RowMajorMatrix<Goldilocks>, bid: usize, is_member: Goldilocks) {
+        let width = trace.width();
+        let mut bidder = 0;
+        for row in trace.values.chunks_mut(width) {
+            let cols: &BidCols<Goldilocks> = row[..NUM_BID_COLS].borrow();
+            bidder += (cols.new_bidder == Goldilocks::one()) as usize;
+            if bidder == bid + 1 && cols.is_dummy == Goldilocks::zero() {
+                row[NUM_BID_COLS + membership_col::IS_MEMBER] = is_member;
+            }
+        }
+    }
+
+    #[test]
+    fn whitelists_invalidate_outsiders() {
+        // only the first bidder is listed, the highest bid comes from the other address
+        let listed = demo_bids()[0].bidder;
+        let whitelist = Whitelist::new(vec![[0x11; 20], listed]).unwrap();
+        let private_input = private_input().with_whitelist(whitelist.clone()).with_statistics();
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&demo_bids(), &private_input);
+        assert_eq!(winner, listed);
+        assert_eq!(trace_statistics(&trace), [1, 2, 2023]);
+        let statistics = AuctionStatistics::select(trace_statistics(&trace), [true, true, true]);
+        let public_values = AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner)
+            .with_statistics(statistics)
+            .with_whitelist(whitelist.root());
+        assert_eq!(AuctionPublicValues::from_field_elements::<Goldilocks>(&public_values.to_field_elements()).unwrap(), public_values);
+
+        let file = prove_auction(demo_bids(), trace.clone(), &public_values).unwrap();
+        assert_eq!(verify_auction(&file.to_bytes(), &public_values).unwrap().winner, listed);
+        let unrestricted = AuctionPublicValues { whitelist: None, ..public_values.clone() };
+        assert!(matches!(verify_auction(&file.to_bytes(), &unrestricted), Err(VerifyError::InvalidPublicValues(_))));
+        assert!(prove_auction_evm(demo_bids(), trace.clone(), &public_values).is_err());
+
+        // claiming the outsider is listed does not start its range
+        let mut forged = trace.clone();
+        set_membership(&mut forged, 1, Goldilocks::one());
+        assert!(prove_auction(demo_bids(), forged, &public_values).is_err());
+
+        // nor does claiming a member is not listed put it strictly inside its range
+        let mut forged = trace;
+        set_membership(&mut forged, 0, Goldilocks::zero());
+        let air = ProverAir::for_auction(demo_bids(), &public_values);
+        let report = check_constraints(&air, &forged, &public_values.to_field_elements()).unwrap_err();
+        assert!(report.failures.iter().any(|failure| (failure.gadget, failure.section) == ("whitelist", "range")));
+        assert!(prove_auction(demo_bids(), forged, &public_values).is_err());
+    }
+
+    #[test]
+    fn publishes_the_statistics_switched_on() {
+        let bids = demo_bids();
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&bids, &private_input().with_statistics());
+        assert_eq!(trace_statistics(&trace), [3, 0, 7082]);
+
+        let statistics = AuctionStatistics::select(trace_statistics(&trace), [true, false, true]);
+        assert_eq!(statistics.average_bid(), Some(2360));
+        let public_values = AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner)
+            .with_statistics(statistics);
+        assert_eq!(AuctionPublicValues::from_field_elements::<Goldilocks>(&public_values.to_field_elements()).unwrap(), public_values);
+        let file = prove_auction(bids.clone(), trace.clone(), &public_values).unwrap();
+        verify_auction(&file.to_bytes(), &public_values).unwrap();
+        assert!(prove_auction_evm(bids.clone(), trace.clone(), &public_values).is_err());
+
+        let larger_sum = AuctionStatistics { bid_sum: Some(7083), ..statistics };
+        assert!(prove_auction(bids, trace, &public_values.with_statistics(larger_sum)).is_err());
+    }
+
+    #[test]
+    fn full_reveal_commits_to_every_bid() {
+        let bids = demo_bids();
+        let listed = bids[0].bidder;
+        let whitelist = Whitelist::new(vec![listed]).unwrap();
+        let private_input = private_input().with_whitelist(whitelist.clone()).with_reveal();
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&bids, &private_input);
+        let revealed = revealed_bids(&trace);
+        // the bids of the other address are revealed as invalid
+        assert_eq!(revealed.iter().map(|bid| (bid.bidder, bid.bid_amount, bid.is_valid)).collect::<Vec<_>>(), [
+            (listed, 2023, true),
+            (bids[1].bidder, 0, false),
+            (bids[2].bidder, 0, false),
+        ]);
+
+        let public_values = AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner)
+            .with_whitelist(whitelist.root())
+            .with_reveal(commitment(&revealed));
+        assert_eq!(AuctionPublicValues::from_field_elements::<Goldilocks>(&public_values.to_field_elements()).unwrap(), public_values);
+        let file = prove_auction(bids.clone(), trace.clone(), &public_values).unwrap();
+        verify_auction(&file.to_bytes(), &public_values).unwrap();
+        assert!(prove_auction_evm(bids.clone(), trace.clone(), &public_values).is_err());
+
+        // the owner cannot misreport a losing bid
+        let mut misreported = revealed.clone();
+        misreported[0] = RevealedBid { bid_amount: 1000, ..misreported[0] };
+        assert!(prove_auction(bids, trace, &public_values.with_reveal(commitment(&misreported))).is_err());
+    }
+
+    #[test]
+    fn receipts_lead_to_the_public_commitment() {
+        let bids = demo_bids();
+        let salts = vec![5, 6, 7];
+        let private_input = private_input().with_reveal().with_receipts(salts.clone());
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&bids, &private_input);
+        let revealed = revealed_bids(&trace);
+        let receipts = receipt::issue(&revealed, &salts);
+        assert_eq!((receipts[0].bidder, receipts[0].bid_amount, receipts[0].is_valid), (bids[0].bidder, 2023, true));
+
+        let public_values = AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner)
+            .with_reveal(commitment(&revealed))
+            .with_receipts(receipt::commitment(&revealed, &salts));
+        assert_eq!(AuctionPublicValues::from_field_elements::<Goldilocks>(&public_values.to_field_elements()).unwrap(), public_values);
+        let file = prove_auction(bids.clone(), trace.clone(), &public_values).unwrap();
+        verify_auction(&file.to_bytes(), &public_values).unwrap();
+        for receipt in &receipts {
+            receipt.check(&public_values).unwrap();
+        }
+        assert!(prove_auction_evm(bids.clone(), trace.clone(), &public_values).is_err());
+
+        // a receipt with another amount, or a commitment to one, does not check
+        let higher = BidReceipt { bid_amount: 2100, ..receipts[0].clone() };
+        assert!(higher.check(&public_values).is_err());
+        let mut misreported = revealed.clone();
+        misreported[0] = RevealedBid { bid_amount: 2100, ..misreported[0] };
+        let forged = public_values.with_receipts(receipt::commitment(&misreported, &salts));
+        assert!(prove_auction(bids, trace, &forged).is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! Just enough HTTP/1.1 for the proving service and for talking to a local node, one request per connection.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use serde::Serialize;
+
+/// Requests larger than this are refused, to bound the memory of a connection.
+pub const MAX_BODY: usize = 64 << 20;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, value: &impl Serialize) -> Self {
+        Self { status, content_type: "application/json", body: serde_json::to_vec(value).unwrap() }
+    }
+
+    pub fn bytes(body: Vec<u8>) -> Self {
+        Self { status: 200, content_type: "application/octet-stream", body }
+    }
+
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message.into() }))
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        500 => "Internal Server Error",
+        _ => "Error",
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Reads the request line, the headers and a `Content-Length` body.
+pub fn read_request(stream: &mut impl Read) -> io::Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(invalid(format!("invalid request line {:?}", line))),
+    };
+    let mut len = 0;
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                len = value.trim().parse().map_err(|_| invalid("invalid Content-Length"))?;
+            }
+        }
+    }
+    if len > MAX_BODY {
+        return Err(invalid("body too large"));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok(Request { method, path, body })
+}
+
+pub fn write_response(stream: &mut impl Write, response: &Response) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status, reason(response.status), response.content_type, response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+/// Sends one request to a plain `http://host:port/path` url and returns the status and the body.
+pub fn send(url: &str, method: &str, body: &[u8]) -> Result<(u16, Vec<u8>), String> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| format!("{} is not an http:// url", url))?;
+    let (host, path) = rest.split_once('/').map_or((rest, "/".to_string()), |(host, path)| (host, format!("/{}", path)));
+    let io = |e: io::Error| format!("{}: {}", url, e);
+
+    let mut stream = TcpStream::connect(host).map_err(io)?;
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        method, path, host, body.len()
+    ).map_err(io)?;
+    stream.write_all(body).map_err(io)?;
+    let mut response = vec![];
+    stream.read_to_end(&mut response).map_err(io)?;
+
+    let end = response.windows(4).position(|w| w == b"\r\n\r\n").ok_or("no HTTP headers")?;
+    let head = String::from_utf8_lossy(&response[..end]).to_ascii_lowercase();
+    let body = &response[end + 4..];
+    let status = head.split_whitespace().nth(1).and_then(|s| s.parse().ok()).ok_or("invalid status line")?;
+    if head.contains("transfer-encoding: chunked") {
+        return dechunk(body).map(|body| (status, body)).ok_or_else(|| "invalid chunked body".to_string());
+    }
+    Ok((status, body.to_vec()))
+}
+
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = vec![];
+    loop {
+        let line = body.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        if size == 0 {
+            return Some(out);
+        }
+        let rest = &body[line + 2..];
+        out.extend_from_slice(rest.get(..size)?);
+        body = rest.get(size + 2..)?;
+    }
+}
@@ -0,0 +1,297 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use p3_goldilocks::Goldilocks;
+use p3_keccak::Keccak256Hash;
+use p3_symmetric::CryptographicHasher;
+use serde_json::{json, Value};
+use silent_bid_verifier::abi::{read_address, read_u64};
+use silent_bid_verifier::public_input::{Address, BidHash, PublicBid};
+use silent_bid_verifier::utils::{bytes_to_address, bytes_to_hex, hex_to_bytes};
+use crate::http;
+
+/// The event the auction contract logs for every bid. The bidder may be indexed (then it is the second topic and
+/// the data is `abi.encode(encryptedAmount)`) or not (the data is `abi.encode(bidder, encryptedAmount)`).
+pub const BID_SUBMITTED: &str = "BidSubmitted(address,bytes)";
+
+#[derive(Debug)]
+pub enum IngestError {
+    Io(String),
+    /// The response is not JSON-RPC, or the node returned an error.
+    Rpc(String),
+    /// A `BidSubmitted` log cannot be decoded; `index` is its position in the response.
+    InvalidLog { index: usize, reason: String },
+    /// The bids do not hash to what the contract accumulated, so a log is missing, extra or reordered.
+    HashMismatch { expected: u64, actual: u64 },
+}
+
+impl fmt::Display for IngestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngestError::Io(e) => write!(f, "{}", e),
+            IngestError::Rpc(e) => write!(f, "invalid JSON-RPC response: {}", e),
+            IngestError::InvalidLog { index, reason } => write!(f, "invalid BidSubmitted log #{}: {}", index, reason),
+            IngestError::HashMismatch { expected, actual } => {
+                write!(f, "bids hash to {}, but the contract accumulated {}", actual, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IngestError {}
+
+pub fn bid_submitted_topic() -> [u8; 32] {
+    Keccak256Hash.hash_iter(BID_SUBMITTED.bytes())
+}
+
+/// Reads the bids of a saved `eth_getLogs` response, either the full JSON-RPC response or its `result` array.
+pub fn read_bids_file(path: impl AsRef<Path>, contract: Option<Address>) -> Result<Vec<PublicBid>, IngestError> {
+    let path = path.as_ref();
+    let json = fs::read_to_string(path).map_err(|e| IngestError::Io(format!("cannot read {}: {}", path.display(), e)))?;
+    let response = serde_json::from_str(&json).map_err(|e| IngestError::Rpc(e.to_string()))?;
+    bids_from_logs(&response, contract)
+}
+
+/// Asks a node at `url` (plain `http://host:port`, such as a local development node) for every `BidSubmitted` log
+/// of `contract`.
+pub fn fetch_bids(url: &str, contract: Address) -> Result<Vec<PublicBid>, IngestError> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getLogs",
+        "params": [{
+            "address": bytes_to_address(&contract),
+            "topics": [format!("0x{}", bytes_to_hex(&bid_submitted_topic()))],
+            "fromBlock": "earliest",
+            "toBlock": "latest",
+        }],
+    });
+    let (status, body) = http::send(url, "POST", request.to_string().as_bytes()).map_err(IngestError::Io)?;
+    if status != 200 {
+        return Err(IngestError::Rpc(format!("HTTP status {}", status)));
+    }
+    let response = serde_json::from_slice(&body).map_err(|e| IngestError::Rpc(e.to_string()))?;
+    bids_from_logs(&response, Some(contract))
+}
+
+/// Decodes the `BidSubmitted` logs of an `eth_getLogs` response in on-chain order (block number, then log index).
+/// Logs of other events or other contracts than `contract`, and logs removed by a reorg, are skipped.
+pub fn bids_from_logs(response: &Value, contract: Option<Address>) -> Result<Vec<PublicBid>, IngestError> {
+    if let Some(error) = response.get("error") {
+        return Err(IngestError::Rpc(error.to_string()));
+    }
+    let logs = response.get("result").unwrap_or(response);
+    let logs = logs.as_array().ok_or_else(|| IngestError::Rpc("expected an array of logs".to_string()))?;
+    let topic = bid_submitted_topic();
+
+    let mut bids = vec![];
+    for (index, log) in logs.iter().enumerate() {
+        let invalid = |reason: String| IngestError::InvalidLog { index, reason };
+        if log.get("removed").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        let topics = log.get("topics").and_then(Value::as_array).ok_or_else(|| invalid("no topics".to_string()))?;
+        let topics = topics.iter().map(|t| hex_field(t).map_err(&invalid)).collect::<Result<Vec<_>, _>>()?;
+        if topics.first().map(Vec::as_slice) != Some(&topic[..]) {
+            continue;
+        }
+        if let Some(contract) = contract {
+            let address = log.get("address").ok_or_else(|| invalid("no address".to_string()))?;
+            if hex_field(address).map_err(&invalid)? != contract {
+                continue;
+            }
+        }
+        let data = hex_field(log.get("data").unwrap_or(&Value::Null)).map_err(&invalid)?;
+        let bid = match topics.len() {
+            1 => decode_bid(&data).map_err(&invalid)?,
+            2 => PublicBid {
+                bidder: read_address(&topics[1]).map_err(|e| invalid(e.to_string()))?,
+                encrypted_amount: read_bytes(&data, 0).map_err(&invalid)?,
+            },
+            n => return Err(invalid(format!("{} topics", n))),
+        };
+        let block = quantity(log, "blockNumber").map_err(&invalid)?;
+        let log_index = quantity(log, "logIndex").map_err(&invalid)?;
+        bids.push(((block, log_index), bid));
+    }
+    bids.sort_by_key(|(position, _)| *position);
+    if let Some(pair) = bids.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(IngestError::Rpc(format!("two logs at block {} index {}", pair[0].0 .0, pair[0].0 .1)));
+    }
+    Ok(bids.into_iter().map(|(_, bid)| bid).collect())
+}
+
+/// Checks that `bids` are exactly the bids the contract hashed, before spending time on a proof over them.
+pub fn check_bid_hash(bids: &[PublicBid], expected: u64) -> Result<(), IngestError> {
+    let actual = BidHash::<Goldilocks>::of(bids);
+    if actual != expected {
+        return Err(IngestError::HashMismatch { expected, actual });
+    }
+    Ok(())
+}
+
+fn hex_field(value: &Value) -> Result<Vec<u8>, String> {
+    let hex = value.as_str().ok_or_else(|| format!("{} is not a hex string", value))?;
+    hex_to_bytes(hex.trim_start_matches("0x"))
+}
+
+fn quantity(log: &Value, field: &str) -> Result<u64, String> {
+    let value = log.get(field).and_then(Value::as_str).ok_or_else(|| format!("no {}, the log is pending", field))?;
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).map_err(|_| format!("invalid {} {}", field, value))
+}
+
+/// `abi.encode(address bidder, bytes encryptedAmount)`.
+fn decode_bid(data: &[u8]) -> Result<PublicBid, String> {
+    let bidder = data.get(..32).ok_or("data is too short")?;
+    Ok(PublicBid {
+        bidder: read_address(bidder).map_err(|e| e.to_string())?,
+        encrypted_amount: read_bytes(data, 1)?,
+    })
+}
+
+/// The `bytes` whose offset is in head word `slot` of `data`.
+fn read_bytes(data: &[u8], slot: usize) -> Result<Vec<u8>, String> {
+    // the offset and the length come from the log, so every sum is checked
+    let overrun = || "bytes overrun the data".to_string();
+    let word = |at: usize| -> Result<usize, String> {
+        let word = data.get(at..at.checked_add(32).ok_or_else(overrun)?).ok_or("data is too short")?;
+        usize::try_from(read_u64(word).map_err(|e| e.to_string())?).map_err(|_| overrun())
+    };
+    let offset = word(slot * 32)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32).ok_or_else(overrun)?;
+    let end = start.checked_add(len).ok_or_else(overrun)?;
+    data.get(start..end).map(<[u8]>::to_vec).ok_or_else(overrun)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use serde_json::{json, Value};
+    use silent_bid_verifier::abi::word;
+    use silent_bid_verifier::public_input::PublicBid;
+    use silent_bid_verifier::utils::bytes_to_hex;
+    use crate::fixtures::{auction_trace, demo_bids};
+    use super::{bid_submitted_topic, bids_from_logs, check_bid_hash, fetch_bids, IngestError};
+
+    const CONTRACT: [u8; 20] = [0xcc; 20];
+
+    fn hex(bytes: &[u8]) -> String {
+        format!("0x{}", bytes_to_hex(bytes))
+    }
+
+    fn padded(bytes: &[u8]) -> Vec<u8> {
+        let mut bytes = bytes.to_vec();
+        bytes.resize(bytes.len().next_multiple_of(32), 0);
+        bytes
+    }
+
+    fn log(bid: &PublicBid, block: u64, log_index: u64) -> Value {
+        let mut bidder = [0; 32];
+        bidder[12..].copy_from_slice(&bid.bidder);
+        let data = [&bidder[..], &word(64), &word(bid.encrypted_amount.len() as u128), &padded(&bid.encrypted_amount)].concat();
+        json!({
+            "address": hex(&CONTRACT),
+            "topics": [hex(&bid_submitted_topic())],
+            "data": hex(&data),
+            "blockNumber": format!("{:#x}", block),
+            "logIndex": format!("{:#x}", log_index),
+            "removed": false,
+        })
+    }
+
+    /// The demo bids, logged out of order, with the bidder indexed in the last one.
+    fn response() -> Value {
+        let bids = demo_bids();
+        let mut indexed = log(&bids[2], 9, 0);
+        let mut bidder = [0; 32];
+        bidder[12..].copy_from_slice(&bids[2].bidder);
+        indexed["topics"] = json!([hex(&bid_submitted_topic()), hex(&bidder)]);
+        let amount = &bids[2].encrypted_amount;
+        indexed["data"] = json!(hex(&[&word(32)[..], &word(amount.len() as u128), &padded(amount)].concat()));
+
+        let mut other_event = log(&bids[0], 7, 1);
+        other_event["topics"] = json!([hex(&[1; 32])]);
+        let mut removed = log(&bids[0], 7, 2);
+        removed["removed"] = json!(true);
+        let mut other_contract = log(&bids[0], 7, 3);
+        other_contract["address"] = json!(hex(&[0xdd; 20]));
+
+        json!({ "jsonrpc": "2.0", "id": 1, "result": [indexed, log(&bids[1], 8, 5), other_event, removed, other_contract, log(&bids[0], 7, 4)] })
+    }
+
+    #[test]
+    fn decodes_logs_in_chain_order() {
+        let bids = bids_from_logs(&response(), Some(CONTRACT)).unwrap();
+        assert_eq!(bids, demo_bids());
+        // the hash the contract accumulated, as the trace computes it
+        let (_, public_values) = auction_trace(&demo_bids());
+        let hash_value = silent_bid_verifier::AuctionPublicValues::from_field_elements(&public_values).unwrap().hash_value;
+        check_bid_hash(&bids, hash_value).unwrap();
+
+        let mut missing = bids.clone();
+        missing.pop();
+        assert!(matches!(check_bid_hash(&missing, hash_value), Err(IngestError::HashMismatch { .. })));
+        let mut reordered = bids;
+        reordered.swap(0, 2);
+        assert!(check_bid_hash(&reordered, hash_value).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_logs() {
+        let mut response = response();
+        response["result"][1]["data"] = json!("0x1234");
+        assert!(matches!(bids_from_logs(&response, None), Err(IngestError::InvalidLog { index: 1, .. })));
+
+        let mut pending = self::response();
+        pending["result"][0]["blockNumber"] = Value::Null;
+        assert!(matches!(bids_from_logs(&pending, None), Err(IngestError::InvalidLog { index: 0, .. })));
+
+        // offsets and lengths near `u64::MAX` must not overflow
+        for (offset, len) in [(u64::MAX as u128, 0), (64, u64::MAX as u128)] {
+            let data = [&[0; 32][..], &word(offset), &word(len)].concat();
+            let mut huge = self::response();
+            huge["result"][1]["data"] = json!(hex(&data));
+            assert!(matches!(bids_from_logs(&huge, None), Err(IngestError::InvalidLog { index: 1, .. })));
+        }
+
+        let error = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": "boom" } });
+        assert!(matches!(bids_from_logs(&error, None), Err(IngestError::Rpc(_))));
+    }
+
+    /// The whole request, headers and body: closing the connection with unread bytes would reset it.
+    fn read_request(stream: &mut TcpStream) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut request = String::new();
+        while !request.ends_with("\r\n\r\n") {
+            if reader.read_line(&mut request).unwrap() == 0 {
+                break;
+            }
+        }
+        let length = request.lines()
+            .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|len| len.trim().parse().unwrap()))
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        request + &String::from_utf8_lossy(&body)
+    }
+
+    #[test]
+    fn fetches_logs_from_a_node() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let node = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            let body = response().to_string();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n", body.len(), body).unwrap();
+            request
+        });
+        assert_eq!(fetch_bids(&url, CONTRACT).unwrap(), demo_bids());
+        let request = node.join().unwrap();
+        assert!(request.starts_with("POST / HTTP/1.1"));
+        assert!(request.contains("eth_getLogs"));
+    }
+}
@@ -0,0 +1,142 @@
+//! On-disk state of the proving service, so that a restart loses no work.
+//!
+//! Each job is a directory `<dir>/<id>/` holding `job.json` (a `StoredJob`), then `trace.json` once its trace is
+//! generated and `proof.bin` once it is proved. Every file is written to a temporary file, synced and renamed over
+//! the old one, so after a crash a file is either the old or the new version, never a torn one.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use p3_matrix::dense::RowMajorMatrix;
+use serde::{Deserialize, Serialize};
+use silent_bid_verifier::config::Val;
+use crate::service::{JobId, JobRequest, JobStatus};
+use crate::trace_dump::{trace_from_json, trace_to_json};
+
+const JOB_FILE: &str = "job.json";
+const TRACE_FILE: &str = "trace.json";
+const PROOF_FILE: &str = "proof.bin";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoredJob {
+    pub id: JobId,
+    pub request: JobRequest,
+    pub status: JobStatus,
+    /// The canonical public values of the generated trace, set together with `trace.json`.
+    #[serde(default)]
+    pub public_values: Option<Vec<u64>>,
+}
+
+pub struct JobStore {
+    dir: PathBuf,
+}
+
+impl JobStore {
+    /// Opens (creating it if needed) the store in `dir` and loads its jobs, by id.
+    pub fn open(dir: impl AsRef<Path>) -> Result<(Self, Vec<StoredJob>), String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+        let store = Self { dir };
+
+        let mut jobs = vec![];
+        let entries = fs::read_dir(&store.dir).map_err(|e| format!("cannot read {}: {}", store.dir.display(), e))?;
+        for entry in entries.flatten() {
+            // directories of jobs whose record was never written, and temporary files, are skipped
+            let path = entry.path().join(JOB_FILE);
+            if entry.file_name().to_str().and_then(|name| name.parse::<JobId>().ok()).is_none() || !path.exists() {
+                continue;
+            }
+            let json = fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+            jobs.push(serde_json::from_str(&json).map_err(|e| format!("invalid {}: {}", path.display(), e))?);
+        }
+        jobs.sort_by_key(|job: &StoredJob| job.id);
+        Ok((store, jobs))
+    }
+
+    fn job_dir(&self, id: JobId) -> PathBuf {
+        self.dir.join(id.to_string())
+    }
+
+    pub fn save(&self, job: &StoredJob) -> Result<(), String> {
+        self.write(job.id, JOB_FILE, &serde_json::to_vec_pretty(job).unwrap())
+    }
+
+    pub fn save_trace(&self, id: JobId, trace: &RowMajorMatrix<Val>) -> Result<(), String> {
+        self.write(id, TRACE_FILE, trace_to_json(trace).as_bytes())
+    }
+
+    pub fn load_trace(&self, id: JobId) -> Result<RowMajorMatrix<Val>, String> {
+        let path = self.job_dir(id).join(TRACE_FILE);
+        trace_from_json(&fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?)
+    }
+
+    /// Stores the proof and drops the trace, which is no longer needed.
+    pub fn save_proof(&self, id: JobId, proof: &[u8]) -> Result<(), String> {
+        self.write(id, PROOF_FILE, proof)?;
+        let _ = fs::remove_file(self.job_dir(id).join(TRACE_FILE));
+        Ok(())
+    }
+
+    pub fn load_proof(&self, id: JobId) -> Option<Vec<u8>> {
+        fs::read(self.job_dir(id).join(PROOF_FILE)).ok()
+    }
+
+    fn write(&self, id: JobId, name: &str, contents: &[u8]) -> Result<(), String> {
+        let dir = self.job_dir(id);
+        let path = dir.join(name);
+        let write = || -> io::Result<()> {
+            fs::create_dir_all(&dir)?;
+            let tmp = dir.join(format!(".{}.tmp", name));
+            let mut file = File::create(&tmp)?;
+            file.write_all(contents)?;
+            file.sync_all()?;
+            fs::rename(&tmp, &path)?;
+            // make the rename itself durable
+            File::open(&dir)?.sync_all()
+        };
+        write().map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::fixtures::{auction_trace, demo_bids, temp_dir};
+    use crate::service::{JobRequest, JobStatus};
+    use super::{JobStore, StoredJob};
+
+    #[test]
+    fn reloads_jobs_traces_and_proofs() {
+        let dir = temp_dir("job-store");
+        let (store, jobs) = JobStore::open(&dir).unwrap();
+        assert!(jobs.is_empty());
+
+        let request = JobRequest {
+            auction_id: "a".to_string(),
+            key_id: "demo".to_string(),
+            bids: vec![],
+            bid_hash: None,
+            evm: false,
+        };
+        let jobs: Vec<StoredJob> = (1..=2)
+            .map(|id| StoredJob { id, request: request.clone(), status: JobStatus::Queued, public_values: None })
+            .collect();
+        jobs.iter().for_each(|job| store.save(job).unwrap());
+        let (trace, _) = auction_trace(&demo_bids());
+        store.save_trace(2, &trace).unwrap();
+        // leftovers of an interrupted write are ignored
+        fs::write(dir.join("2").join(".job.json.tmp"), "{").unwrap();
+        fs::create_dir_all(dir.join("3")).unwrap();
+
+        let (store, reloaded) = JobStore::open(&dir).unwrap();
+        assert_eq!(reloaded, jobs);
+        assert_eq!(store.load_trace(2).unwrap().values, trace.values);
+        assert!(store.load_trace(1).is_err());
+
+        store.save_proof(2, b"proof").unwrap();
+        assert_eq!(store.load_proof(2).unwrap(), b"proof");
+        assert!(store.load_trace(2).is_err());
+        assert_eq!(store.load_proof(1), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
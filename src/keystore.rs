@@ -0,0 +1,256 @@
+//! Owner keys encrypted at rest, referred to by id.
+//!
+//! Each key is a file `<dir>/<id>.json` holding the public modulus and the private exponent encrypted with
+//! ChaCha20-Poly1305, under a key derived from the keystore's passphrase with Argon2id and a per-file salt. The id and
+//! the modulus are authenticated with the exponent, so a file cannot be renamed or pointed at another modulus.
+//! Decrypted keys are `AuctionKey`s, which are zeroized when dropped; load one just before generating a trace and
+//! let it go right after.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use p3_field::AbstractField;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use silent_bid_verifier::config::Val;
+use silent_bid_verifier::utils::{bytes_to_hex, hex_to_bytes};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use crate::private_input::PrivateInput;
+
+pub const KEYSTORE_VERSION: u32 = 1;
+const AAD_PREFIX: &[u8] = b"silent-bid/keystore/v1";
+const SALT_BYTES: usize = 16;
+const NONCE_BYTES: usize = 12;
+
+/// The demo auction's `(modulus, private_exponent)`. It is public, so it needs no keystore.
+pub const DEMO_KEY: (u32, u32) = (1875143437, 561461413);
+/// The public exponent bidders of the demo auction encrypt with.
+pub const DEMO_PUBLIC_EXPONENT: u32 = 5153;
+
+/// An auction's RSA key, zeroized when dropped.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct AuctionKey {
+    pub modulus: u32,
+    pub private_exponent: u32,
+}
+
+impl AuctionKey {
+    pub fn demo() -> Self {
+        Self { modulus: DEMO_KEY.0, private_exponent: DEMO_KEY.1 }
+    }
+
+    pub fn private_input(&self) -> PrivateInput<Val> {
+        PrivateInput::new(Val::from_canonical_u32(self.modulus), self.private_exponent)
+    }
+}
+
+impl fmt::Debug for AuctionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuctionKey").field("modulus", &self.modulus).finish_non_exhaustive()
+    }
+}
+
+/// Argon2id costs, stored with every key so that they can be raised for new keys without breaking old ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory in KiB.
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// The OWASP recommendation for Argon2id.
+    fn default() -> Self {
+        Self { m_cost: 19 * 1024, t_cost: 2, p_cost: 1 }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    version: u32,
+    id: String,
+    modulus: u32,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug)]
+pub enum KeystoreError {
+    Io(String),
+    InvalidId(String),
+    UnknownKey(String),
+    KeyExists(String),
+    InvalidFile(String),
+    /// The passphrase is wrong or the file was tampered with.
+    Decryption(String),
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::Io(e) => write!(f, "{}", e),
+            KeystoreError::InvalidId(id) => write!(f, "invalid key id {:?}, use letters, digits, '-' and '_'", id),
+            KeystoreError::UnknownKey(id) => write!(f, "unknown key {}", id),
+            KeystoreError::KeyExists(id) => write!(f, "key {} already exists", id),
+            KeystoreError::InvalidFile(e) => write!(f, "invalid key file: {}", e),
+            KeystoreError::Decryption(id) => write!(f, "cannot decrypt key {}: wrong passphrase or corrupted file", id),
+        }
+    }
+}
+
+impl std::error::Error for KeystoreError {}
+
+/// A directory of encrypted keys and the passphrase that opens them.
+pub struct Keystore {
+    dir: PathBuf,
+    passphrase: Zeroizing<String>,
+    kdf: KdfParams,
+}
+
+impl Keystore {
+    pub fn open(dir: impl AsRef<Path>, passphrase: impl Into<String>) -> Result<Self, KeystoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|e| KeystoreError::Io(format!("cannot create {}: {}", dir.display(), e)))?;
+        Ok(Self { dir, passphrase: Zeroizing::new(passphrase.into()), kdf: KdfParams::default() })
+    }
+
+    /// Sets the costs used for keys imported from now on; tests lower them.
+    #[cfg(test)]
+    pub fn with_kdf(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, KeystoreError> {
+        let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(KeystoreError::InvalidId(id.to_string()));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.path(id).is_ok_and(|path| path.exists())
+    }
+
+    /// Encrypts and stores `key` as `id`; existing keys are never overwritten.
+    pub fn import(&self, id: &str, key: &AuctionKey) -> Result<(), KeystoreError> {
+        let path = self.path(id)?;
+        if path.exists() {
+            return Err(KeystoreError::KeyExists(id.to_string()));
+        }
+        let mut salt = [0; SALT_BYTES];
+        let mut nonce = [0; NONCE_BYTES];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let cipher = self.cipher(&self.kdf, &salt)?;
+        let plaintext = Zeroizing::new(key.private_exponent.to_le_bytes());
+        let payload = Payload { msg: &plaintext[..], aad: &aad(id, key.modulus) };
+        let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), payload).expect("a 4-byte message always encrypts");
+
+        let file = KeyFile {
+            version: KEYSTORE_VERSION,
+            id: id.to_string(),
+            modulus: key.modulus,
+            kdf: self.kdf,
+            salt: bytes_to_hex(&salt),
+            nonce: bytes_to_hex(&nonce),
+            ciphertext: bytes_to_hex(&ciphertext),
+        };
+        fs::write(&path, serde_json::to_string_pretty(&file).unwrap())
+            .map_err(|e| KeystoreError::Io(format!("cannot write {}: {}", path.display(), e)))
+    }
+
+    /// Decrypts the key `id`.
+    pub fn load(&self, id: &str) -> Result<AuctionKey, KeystoreError> {
+        let path = self.path(id)?;
+        let json = fs::read_to_string(&path).map_err(|_| KeystoreError::UnknownKey(id.to_string()))?;
+        let file: KeyFile = serde_json::from_str(&json).map_err(|e| KeystoreError::InvalidFile(e.to_string()))?;
+        if file.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::InvalidFile(format!("unsupported version {}", file.version)));
+        }
+        let hex = |hex: &str| hex_to_bytes(hex).map_err(KeystoreError::InvalidFile);
+        let (salt, nonce, ciphertext) = (hex(&file.salt)?, hex(&file.nonce)?, hex(&file.ciphertext)?);
+        if nonce.len() != NONCE_BYTES {
+            return Err(KeystoreError::InvalidFile("invalid nonce".to_string()));
+        }
+
+        let cipher = self.cipher(&file.kdf, &salt)?;
+        let payload = Payload { msg: &ciphertext[..], aad: &aad(id, file.modulus) };
+        let plaintext = Zeroizing::new(
+            cipher.decrypt(Nonce::from_slice(&nonce), payload).map_err(|_| KeystoreError::Decryption(id.to_string()))?,
+        );
+        let exponent = Zeroizing::new(
+            <[u8; 4]>::try_from(&plaintext[..]).map_err(|_| KeystoreError::InvalidFile("invalid exponent".to_string()))?,
+        );
+        Ok(AuctionKey { modulus: file.modulus, private_exponent: u32::from_le_bytes(*exponent) })
+    }
+
+    fn cipher(&self, kdf: &KdfParams, salt: &[u8]) -> Result<ChaCha20Poly1305, KeystoreError> {
+        let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32))
+            .map_err(|e| KeystoreError::InvalidFile(format!("invalid KDF parameters: {}", e)))?;
+        let mut key = Zeroizing::new([0; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(self.passphrase.as_bytes(), salt, &mut key[..])
+            .map_err(|e| KeystoreError::InvalidFile(format!("cannot derive the key: {}", e)))?;
+        Ok(ChaCha20Poly1305::new(Key::from_slice(&key[..])))
+    }
+}
+
+fn aad(id: &str, modulus: u32) -> Vec<u8> {
+    [AAD_PREFIX, id.as_bytes(), &modulus.to_le_bytes()].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use serde_json::Value;
+    use crate::fixtures::{temp_dir, MODULUS, PRIVATE_EXPONENT, TEST_KDF};
+    use super::{AuctionKey, Keystore, KeystoreError};
+
+    #[test]
+    fn round_trips_keys_by_id() {
+        let dir = temp_dir("keystore");
+        let keystore = Keystore::open(&dir, "correct horse").unwrap().with_kdf(TEST_KDF);
+        let key = AuctionKey { modulus: MODULUS, private_exponent: PRIVATE_EXPONENT };
+        keystore.import("owner-1", &key).unwrap();
+        assert!(keystore.contains("owner-1"));
+        assert_eq!(keystore.load("owner-1").unwrap(), key);
+        assert!(matches!(keystore.import("owner-1", &key), Err(KeystoreError::KeyExists(_))));
+
+        // the exponent is not stored in the clear, and the debug output hides it
+        let file = fs::read_to_string(dir.join("owner-1.json")).unwrap();
+        assert!(!file.contains(&PRIVATE_EXPONENT.to_string()));
+        assert!(!format!("{:?}", key).contains(&PRIVATE_EXPONENT.to_string()));
+
+        assert!(matches!(keystore.load("other"), Err(KeystoreError::UnknownKey(_))));
+        assert!(matches!(keystore.load("../owner-1"), Err(KeystoreError::InvalidId(_))));
+        let wrong = Keystore::open(&dir, "wrong horse").unwrap();
+        assert!(matches!(wrong.load("owner-1"), Err(KeystoreError::Decryption(_))));
+    }
+
+    #[test]
+    fn detects_tampering() {
+        let dir = temp_dir("keystore-tamper");
+        let keystore = Keystore::open(&dir, "pass").unwrap().with_kdf(TEST_KDF);
+        keystore.import("a", &AuctionKey { modulus: MODULUS, private_exponent: PRIVATE_EXPONENT }).unwrap();
+
+        let mut file: Value = serde_json::from_str(&fs::read_to_string(dir.join("a.json")).unwrap()).unwrap();
+        file["modulus"] = Value::from(MODULUS - 2);
+        fs::write(dir.join("a.json"), file.to_string()).unwrap();
+        assert!(matches!(keystore.load("a"), Err(KeystoreError::Decryption(_))));
+
+        // a key file copied under another id does not open either
+        keystore.import("b", &AuctionKey { modulus: MODULUS, private_exponent: PRIVATE_EXPONENT }).unwrap();
+        fs::copy(dir.join("b.json"), dir.join("c.json")).unwrap();
+        assert!(matches!(keystore.load("c"), Err(KeystoreError::Decryption(_))));
+    }
+}
@@ -1,33 +1,55 @@
 #![feature(step_trait)]
 
-mod air;
-mod public_input;
 mod private_input;
 mod generate_execution_trace;
-mod utils;
-mod columns;
-
-use p3_challenger::{HashChallenger, SerializingChallenger64};
-use p3_commit::ExtensionMmcs;
-use p3_dft::Radix2DitParallel;
-use p3_field::AbstractField;
-use p3_field::extension::BinomialExtensionField;
-use p3_fri::{FriConfig, TwoAdicFriPcs};
-use p3_keccak::Keccak256Hash;
-use p3_merkle_tree::MerkleTreeMmcs;
-use p3_goldilocks::Goldilocks;
-use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher64};
-use p3_uni_stark::{prove, verify, StarkConfig};
+mod trace_dump;
+#[cfg(test)]
+mod mutation;
+mod reference;
+mod prove;
+mod solidity;
+mod ingest;
+mod http;
+mod service;
+mod job_store;
+mod keystore;
+mod threshold;
+mod time_lock;
+mod bid_proof;
+#[cfg(test)]
+mod fixtures;
+#[cfg(test)]
+mod simulator;
+
+use std::net::TcpListener;
+use p3_field::{AbstractField, PrimeField64};
+use p3_uni_stark::StarkGenericConfig;
+use rand::rngs::OsRng;
+use rand::Rng;
 use tracing_forest::util::LevelFilter;
 use tracing_forest::ForestLayer;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{EnvFilter, Registry};
-use crate::air::ProverAir;
-use crate::generate_execution_trace::generate_execution_trace;
+use zeroize::Zeroizing;
+use silent_bid_verifier::abi::encode_public_values;
+use silent_bid_verifier::config::{EvmConfig, MyConfig, Val, CONFIG_ID, EVM_CONFIG_ID};
+use silent_bid_verifier::proof_file::ProofFile;
+use silent_bid_verifier::public_input::{parse_address, Decryption, PublicBid, NUM_STATISTICS};
+use silent_bid_verifier::receipt::{self, BidReceipt};
+use silent_bid_verifier::reveal::{check_revealed, commitment, RevealedBid};
+use silent_bid_verifier::utils::{bytes_to_address, bytes_to_hex};
+use silent_bid_verifier::whitelist::Whitelist;
+use silent_bid_verifier::{verify_auction, AuctionPublicValues, AuctionStatistics};
+use crate::generate_execution_trace::{generate_execution_trace, revealed_bids, trace_statistics};
+use crate::prove::{prove_auction, prove_auction_evm, prove_no_contest};
+use crate::keystore::{AuctionKey, Keystore, DEMO_KEY, DEMO_PUBLIC_EXPONENT};
 use crate::private_input::PrivateInput;
-use crate::public_input::PublicBid;
-use crate::utils::address_to_bytes;
+use crate::service::ProvingService;
+use crate::time_lock::{TimeLock, DEMO_GENERATOR};
+use crate::trace_dump::{read_trace, write_trace};
+
+const PASSPHRASE_VAR: &str = "SILENT_BID_PASSPHRASE";
 
 fn main() {
     let env_filter = EnvFilter::builder()
@@ -39,68 +61,316 @@ fn main() {
         .with(ForestLayer::default())
         .init();
 
-    type Val = Goldilocks;
-    type Challenge = BinomialExtensionField<Val, 2>;
+    // `--verify-proof <path>` only verifies a proof file written by `--write-proof <path>`, and with `--reveal <path>`
+    // checks the bids listed in the file against those it revealed, with `--receipt <path>` a bidder's receipt.
+    // `--evm` proves with, or verifies a proof of, the EVM-friendly config.
+    let args: Vec<String> = std::env::args().collect();
+    let evm = args.iter().any(|arg| arg == "--evm");
+    if let Some(path) = arg_value(&args, "--verify-proof") {
+        if evm {
+            verify_proof_file(ProofFile::<EvmConfig>::read(path, EVM_CONFIG_ID).expect("cannot read the proof file"), &args);
+        } else {
+            verify_proof_file(ProofFile::<MyConfig>::read(path, CONFIG_ID).expect("cannot read the proof file"), &args);
+        }
+        return;
+    }
 
-    type ByteHash = Keccak256Hash;
-    type FieldHash = SerializingHasher64<ByteHash>;
-    let byte_hash = ByteHash {};
-    let field_hash = FieldHash::new(byte_hash);
+    // `--emit-solidity <path>` writes the generated Solidity constraint evaluator of the auction AIR.
+    if let Some(path) = arg_value(&args, "--emit-solidity") {
+        std::fs::write(path, solidity::auction_constraint_evaluator()).expect("cannot write the evaluator contract");
+        return;
+    }
 
-    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
-    let compress = MyCompress::new(byte_hash);
+    // `--emit-bid-solidity <path>` writes the one of the bid proof AIR, for checking the proofs of submitted bids.
+    if let Some(path) = arg_value(&args, "--emit-bid-solidity") {
+        std::fs::write(path, solidity::bid_constraint_evaluator()).expect("cannot write the evaluator contract");
+        return;
+    }
 
-    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
-    let val_mmcs = ValMmcs::new(field_hash, compress);
+    // `--prove-bid <amount>` encrypts a bid to the demo key with a random nonce, prints it and writes to
+    // `--write-proof <path>` a proof that it is well formed and within `--deposit <wei>`, for `--bidder <address>`
+    // to submit to the auction `--auction-id <id>`.
+    if let Some(amount) = arg_value(&args, "--prove-bid") {
+        let deposit = arg_value(&args, "--deposit").expect("--deposit is required").parse().expect("invalid deposit");
+        let bidder = parse_address(arg_value(&args, "--bidder").expect("--bidder is required")).expect("invalid bidder address");
+        let auction_id = arg_value(&args, "--auction-id").expect("--auction-id is required").parse().expect("invalid auction id");
+        let nonce = OsRng.gen_range(0..1000);
+        let (encrypted_amount, proof_file) = bid_proof::prove_bid(
+            amount.parse().expect("invalid amount"), nonce, DEMO_PUBLIC_EXPONENT, DEMO_KEY.0, deposit, bidder, auction_id,
+        ).unwrap_or_else(|err| panic!("{}", err));
+        println!("encrypted amount: 0x{}", bytes_to_hex(&encrypted_amount));
+        if let Some(path) = arg_value(&args, "--write-proof") {
+            proof_file.write(path).expect("cannot write the proof file");
+        }
+        return;
+    }
 
-    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
-    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    // Keys live encrypted in `--keystore <dir>` (`keys` by default), opened with the `SILENT_BID_PASSPHRASE`
+    // environment variable. `--import-key <id>` reads `<modulus> <private exponent>` from stdin and stores them as `id`.
+    let keystore = || {
+        let passphrase = std::env::var(PASSPHRASE_VAR).unwrap_or_else(|_| panic!("{} is not set", PASSPHRASE_VAR));
+        Keystore::open(arg_value(&args, "--keystore").unwrap_or("keys"), passphrase).expect("cannot open the keystore")
+    };
+    if let Some(id) = arg_value(&args, "--import-key") {
+        let mut line = Zeroizing::new(String::new());
+        std::io::stdin().read_line(&mut line).expect("cannot read the key");
+        let numbers = Zeroizing::new(line.split_whitespace().map(|n| n.parse().expect("invalid key")).collect::<Vec<u32>>());
+        let [modulus, private_exponent] = numbers[..] else { panic!("expected `<modulus> <private exponent>`") };
+        keystore().import(id, &AuctionKey { modulus, private_exponent }).expect("cannot import the key");
+        return;
+    }
 
-    type Dft = Radix2DitParallel<Val>;
-    let dft = Dft::default();
+    // `--serve <addr>` runs the proving service with the keystore's keys on `--workers <n>` threads, keeping its jobs
+    // in `--job-dir <dir>` (`jobs` by default).
+    if let Some(addr) = arg_value(&args, "--serve") {
+        let workers = arg_value(&args, "--workers")
+            .map(|n| n.parse().expect("invalid worker count"))
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+        let listener = TcpListener::bind(addr).expect("cannot listen");
+        println!("serving on {} with {} workers", listener.local_addr().unwrap(), workers);
+        let dir = arg_value(&args, "--job-dir").unwrap_or("jobs");
+        ProvingService::open(keystore(), workers, dir).expect("cannot open the job store").serve(listener);
+        return;
+    }
 
-    type Challenger = SerializingChallenger64<Val, HashChallenger<u8, ByteHash, 32>>;
+    let bidders = vec![
+        PublicBid::from_hex("0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfe5", "211be84e0b6176170000000000000000").unwrap(),
+        PublicBid::from_hex("0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfdd", "d1188229623701140000000000000000").unwrap(),
+        PublicBid::from_hex("0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfdd", "8f8eb7400b6176170000000000000000").unwrap(),
+    ];
 
+    // `--logs <path|http://host:port>` takes the bids from the `BidSubmitted` logs of a saved `eth_getLogs` response
+    // or of a local node, of the `--contract <address>` only (required for a node). `--bid-hash <u64>` is the hash
+    // the contract accumulated, checked before proving.
+    let contract = arg_value(&args, "--contract").map(|address| parse_address(address).expect("invalid contract address"));
+    let bidders = match arg_value(&args, "--logs") {
+        Some(url) if url.starts_with("http://") => {
+            ingest::fetch_bids(url, contract.expect("--contract is required with a node")).expect("cannot fetch the bids")
+        }
+        Some(path) => ingest::read_bids_file(path, contract).expect("cannot read the bids"),
+        None => bidders,
+    };
+    if let Some(expected) = arg_value(&args, "--bid-hash") {
+        let expected = expected.parse().expect("invalid bid hash");
+        if let Err(err) = ingest::check_bid_hash(&bidders, expected) {
+            panic!("{}", err);
+        }
+    }
 
-    let fri_config = FriConfig {
-        log_blowup: 3,
-        num_queries: 80,
-        proof_of_work_bits: 16,
-        mmcs: challenge_mmcs,
+    // `--key <id>` decrypts the auction key from the keystore. The public demo key is only used when asked for with
+    // `--demo-key`, never as a fallback.
+    let load_key = || match arg_value(&args, "--key") {
+        Some(id) => keystore().load(id).expect("cannot load the key"),
+        None if args.iter().any(|arg| arg == "--demo-key") => AuctionKey::demo(),
+        None => panic!("--key <id> is required, or --demo-key for the public demo key"),
     };
-    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
-    let pcs = Pcs::new(dft, val_mmcs, fri_config);
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+    // Threshold decryption: `--split-key <operators>` prints one share of the key's exponent per operator,
+    // `--share <share>` prints an operator's partial decryptions of the bids and its commitment, and
+    // `--partials <path>` proves from every operator's partials (the modulus and the public exponent on the first line,
+    // then one operator per line) without the key.
+    if let Some(operators) = arg_value(&args, "--split-key") {
+        let key = load_key();
+        let operators = operators.parse().expect("invalid operator count");
+        for share in threshold::split_exponent(key.private_exponent, key.modulus, operators, &mut OsRng) {
+            println!("{}", share);
+        }
+        return;
+    }
+    if let Some(share) = arg_value(&args, "--share") {
+        let modulus = load_key().modulus;
+        let partials = threshold::partial_decryptions(share.parse().expect("invalid share"), modulus, &bidders);
+        println!("{}", partials.iter().map(u32::to_string).collect::<Vec<_>>().join(" "));
+        println!("commitment: {:?}", threshold::share_commitment(&partials));
+        return;
+    }
 
+    // Time-lock encryption, modulo the demo modulus: `--time-lock <squarings>` opens the bids by solving their puzzles,
+    // without any key, and `--time-lock-bid <value>` prints the encryption of a bid value for that many squarings.
+    if let Some(value) = arg_value(&args, "--time-lock-bid") {
+        let squarings = arg_value(&args, "--time-lock").expect("--time-lock is required").parse().expect("invalid squarings");
+        let time_lock = TimeLock::setup(DEMO_KEY.0, DEMO_GENERATOR, squarings);
+        println!("0x{}", bytes_to_hex(&time_lock.encrypt(value.parse().expect("invalid value"), 4, &mut OsRng)));
+        return;
+    }
 
-    let private_input = PrivateInput::new(Goldilocks::from_canonical_u64(1875143437), Goldilocks::from_canonical_u64(561461413));
-    let bidders = vec![
-        PublicBid {bidder: "0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfe5".to_string(), encrypted_amount: "211be84e0b6176170000000000000000".to_string()},
-        PublicBid {bidder: "0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfdd".to_string(), encrypted_amount: "d1188229623701140000000000000000".to_string()},
-        PublicBid {bidder: "0x95222290DD7278Aa3Ddd389Cc1E1d165CC4BAfdd".to_string(), encrypted_amount: "8f8eb7400b6176170000000000000000".to_string()},
-    ];
+    let (private_input, n) = match (arg_value(&args, "--partials"), arg_value(&args, "--time-lock")) {
+        (Some(path), _) => {
+            let (modulus, public_exponent, partials) =
+                threshold::read_partials(path).expect("cannot read the partial decryptions");
+            (PrivateInput::threshold(Val::from_canonical_u32(modulus), public_exponent, partials), modulus)
+        }
+        (None, Some(squarings)) => {
+            let squarings = squarings.parse().expect("invalid squarings");
+            (PrivateInput::time_lock(Val::from_canonical_u32(DEMO_KEY.0), squarings), DEMO_KEY.0)
+        }
+        (None, None) => {
+            let key = load_key();
+            (key.private_input(), key.modulus)
+        }
+    };
+    let decryption = match private_input.squarings {
+        Some(squarings) => Decryption::TimeLock(squarings as u64),
+        None if !private_input.partial_decryptions.is_empty() => Decryption::Threshold {
+            commitments: private_input.partial_decryptions.iter().map(|partials| threshold::share_commitment(partials)).collect(),
+            public_exponent: private_input.public_exponent as u64,
+        },
+        None => Decryption::Owner,
+    };
+    // `--whitelist <path>` restricts the auction to the addresses listed in the file, one per line: the bids of others
+    // are invalid.
+    let whitelist = arg_value(&args, "--whitelist").map(|path| read_whitelist(path).unwrap_or_else(|err| panic!("{}", err)));
+    let whitelist_root = whitelist.as_ref().map(Whitelist::root);
+    let private_input = match whitelist {
+        Some(whitelist) => private_input.with_whitelist(whitelist),
+        None => private_input,
+    };
+    // `--reveal <path>` commits to every decrypted bid and writes them to the file, one per line.
+    let reveal = arg_value(&args, "--reveal");
+    let private_input = match reveal {
+        Some(_) => private_input.with_reveal(),
+        None => private_input,
+    };
+    // `--receipts <dir>` commits to every decrypted bid with a random salt and writes the receipt of bid `i` to
+    // `<dir>/<i>.txt`, for its bidder only.
+    let receipts = arg_value(&args, "--receipts");
+    let salts: Vec<u64> = match receipts {
+        Some(_) => bidders.iter().map(|_| OsRng.gen_range(0..Val::ORDER_U64)).collect(),
+        None => vec![],
+    };
+    let private_input = match receipts {
+        Some(_) => private_input.with_receipts(salts.clone()),
+        None => private_input,
+    };
+    // `--statistics <list>` also publishes the comma separated statistics among `valid`, `invalid` and `sum`.
+    let statistics = arg_value(&args, "--statistics").map(|names| parse_statistics(names).unwrap_or_else(|err| panic!("{}", err)));
+    let private_input = match statistics {
+        Some(_) => private_input.with_statistics(),
+        None => private_input,
+    };
+    let modulus = n as u64;
+    let (trace, winner_add, winner_amount, hash_value) = generate_execution_trace(&bidders, &private_input);
+    drop(private_input);
 
-    let (trace, winner_add, winner_amount, hash_value) = generate_execution_trace(&bidders, &private_input, 561461413, 1875143437);
+    // `--dump-trace <path>` writes the trace as CSV, or JSON for a `.json` path.
+    // `--load-trace <path>` checks and proves a previously dumped (possibly edited) trace instead.
+    // Neither holds the columns of the optional gadgets: the membership columns of a whitelist, the reveal, receipt
+    // and statistics columns, nor those of a time-lock or a threshold decryption.
+    let owner = matches!(decryption, Decryption::Owner);
+    let extended = whitelist_root.is_some() || reveal.is_some() || receipts.is_some() || statistics.is_some() || !owner;
+    if extended && ["--dump-trace", "--load-trace", "--no-contest"].iter().any(|flag| args.iter().any(|arg| arg == flag)) {
+        panic!(
+            "--whitelist, --reveal, --receipts, --statistics, --time-lock and --partials do not support --dump-trace, \
+             --load-trace or --no-contest"
+        );
+    }
+    if let Some(path) = arg_value(&args, "--dump-trace") {
+        write_trace(path, &trace).expect("cannot dump the trace");
+    }
+    let trace = match arg_value(&args, "--load-trace") {
+        Some(path) => read_trace(path).expect("cannot load the trace"),
+        None => trace,
+    };
     println!("hash input: {:?}", hash_value);
-    println!("winner: {:?}, amount: {:?}", winner_add, winner_amount);
-    let address_bytes = address_to_bytes(&winner_add);
-    let address_bytes_goldilocks: [Goldilocks; 20] = address_bytes.iter().map(|e| Goldilocks::from_canonical_u8(*e)).collect::<Vec<Goldilocks>>().try_into().expect("slice with incorrect length");
-
-    let mut public_input = vec![
-        Goldilocks::from_canonical_u64(1875143437), //modulo
-        hash_value, // hash value
-        Goldilocks::from_canonical_u64(311), // base in Rolling Hash
-        winner_amount,
-    ];
-    public_input.extend(address_bytes_goldilocks);
+    println!("winner: {:?}, amount: {:?}", bytes_to_address(&winner_add), winner_amount);
+    let mut public_values = AuctionPublicValues::new(modulus, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner_add)
+        .with_decryption(decryption);
+    if let Some(root) = whitelist_root {
+        println!("whitelist root: {:?}", root);
+        public_values = public_values.with_whitelist(root);
+    }
+    if let Some(switched_on) = statistics {
+        let statistics = AuctionStatistics::select(trace_statistics(&trace), switched_on);
+        println!("statistics: {:?}, average bid: {:?}", statistics, statistics.average_bid());
+        public_values = public_values.with_statistics(statistics);
+    }
+    if let Some(path) = reveal {
+        let revealed = revealed_bids(&trace);
+        std::fs::write(path, revealed.iter().map(|bid| format!("{}\n", bid)).collect::<String>()).expect("cannot write the revealed bids");
+        public_values = public_values.with_reveal(commitment(&revealed));
+        println!("reveal commitment: {:?}", public_values.reveal.unwrap());
+    }
+    if let Some(dir) = receipts {
+        let revealed = revealed_bids(&trace);
+        std::fs::create_dir_all(dir).expect("cannot create the receipts directory");
+        for (i, receipt) in receipt::issue(&revealed, &salts).iter().enumerate() {
+            std::fs::write(format!("{}/{}.txt", dir, i), receipt.to_string()).expect("cannot write the receipt");
+        }
+        public_values = public_values.with_receipts(receipt::commitment(&revealed, &salts));
+        println!("receipts commitment: {:?}", public_values.receipts.unwrap());
+    }
+    if public_values.decryption != Decryption::Owner {
+        println!("decryption: {:?}", public_values.decryption);
+    } else if let Ok(calldata) = encode_public_values(&public_values) {
+        println!("public values calldata: 0x{}", bytes_to_hex(&calldata));
+    }
+
+    // `--no-contest` proves that no bid is valid instead, and fails when there is a winner.
+    let write_proof = arg_value(&args, "--write-proof");
+    let result = if args.iter().any(|arg| arg == "--no-contest") {
+        let public_values = AuctionPublicValues::no_contest(public_values.modulus, public_values.hash_value);
+        prove_no_contest(bidders, trace, public_values.modulus, public_values.hash_value)
+            .map(|file| check_and_write(file, &public_values, write_proof))
+    } else if evm {
+        prove_auction_evm(bidders, trace, &public_values).map(|file| check_and_write(file, &public_values, write_proof))
+    } else {
+        prove_auction(bidders, trace, &public_values).map(|file| check_and_write(file, &public_values, write_proof))
+    };
+    if let Err(err) = result {
+        panic!("{}", err);
+    }
+}
+
+fn check_and_write<SC: StarkGenericConfig>(proof_file: ProofFile<SC>, public_values: &AuctionPublicValues, path: Option<&str>) {
+    if let Err(err) = verify_auction(&proof_file.to_bytes(), public_values) {
+        panic!("verification failed: {}", err);
+    }
+    if let Some(path) = path {
+        proof_file.write(path).expect("cannot write the proof file");
+    }
+}
+
+fn verify_proof_file<SC: StarkGenericConfig>(proof_file: ProofFile<SC>, args: &[String]) {
+    let public_values = AuctionPublicValues::from_canonical(&proof_file.public_values).expect("invalid public values");
+    match verify_auction(&proof_file.to_bytes(), &public_values) {
+        Ok(outcome) => println!("proof verified: {:?}", outcome),
+        Err(err) => panic!("verification failed: {}", err),
+    }
+    if let Some(path) = arg_value(args, "--reveal") {
+        let revealed = read_revealed(path).unwrap_or_else(|err| panic!("{}", err));
+        check_revealed(&public_values, &revealed).unwrap_or_else(|err| panic!("{}", err));
+        println!("revealed bids match the proof: {}", revealed.len());
+    }
+    if let Some(path) = arg_value(args, "--receipt") {
+        let file = std::fs::read_to_string(path).unwrap_or_else(|err| panic!("cannot read {}: {}", path, err));
+        let receipt = BidReceipt::parse(&file).unwrap_or_else(|err| panic!("{}", err));
+        receipt.check(&public_values).unwrap_or_else(|err| panic!("{}", err));
+        println!("receipt matches the proof: {} bid {} ({})", bytes_to_address(&receipt.bidder), receipt.bid_amount,
+            if receipt.is_valid { "valid" } else { "invalid" });
+    }
+}
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+fn parse_statistics(names: &str) -> Result<[bool; NUM_STATISTICS], String> {
+    let mut switched_on = [false; NUM_STATISTICS];
+    for name in names.split(',') {
+        let index = ["valid", "invalid", "sum"].iter().position(|known| *known == name)
+            .ok_or_else(|| format!("unknown statistic {}, expected valid, invalid or sum", name))?;
+        switched_on[index] = true;
+    }
+    Ok(switched_on)
+}
 
-    let air = ProverAir {public_input: bidders};
-    let proof = prove(&config, &air , &mut challenger, trace, &public_input);
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    verify(&config, &air, &mut challenger, &proof, &public_input).expect("verification failed");
+fn read_whitelist(path: &str) -> Result<Whitelist, String> {
+    let file = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    let addresses = file.lines().map(str::trim).filter(|line| !line.is_empty()).map(parse_address).collect::<Result<_, _>>()?;
+    Whitelist::new(addresses)
+}
+
+fn read_revealed(path: &str) -> Result<Vec<RevealedBid>, String> {
+    let file = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    file.lines().filter(|line| !line.trim().is_empty()).map(RevealedBid::parse).collect()
+}
 
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
 }
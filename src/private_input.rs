@@ -1,14 +1,101 @@
-#[derive(Clone)]
-pub struct PrivateInput<F> {
+use p3_field::Field;
+use silent_bid_verifier::whitelist::Whitelist;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// The owner's RSA key and what else the trace is generated from, zeroized when dropped. It is not `Clone`, so that
+/// the key lives in this one place until the trace is generated.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct PrivateInput<F: Field> {
+    /// The public modulus, as the trace holds it.
+    #[zeroize(skip)]
     pub private_modulus: F,
-    pub private_exponent: F,
+    pub private_exponent: u32,
+    /// In threshold mode, every operator's partial decryption of every non-zero 4 encrypted bytes, in trace order.
+    /// Empty with a single owner.
+    pub partial_decryptions: Vec<Vec<u32>>,
+    /// In threshold mode, the public exponent the trace re-encrypts every decryption with.
+    #[zeroize(skip)]
+    pub public_exponent: u32,
+    /// In time-lock mode, the number of squarings of every bid's puzzle. Nothing is private then.
+    pub squarings: Option<u32>,
+    /// The addresses allowed to win, in auctions restricted to them. Public as well.
+    #[zeroize(skip)]
+    pub whitelist: Option<Whitelist>,
+    /// Whether the trace commits to every decrypted bid, in full reveal mode.
+    pub reveal: bool,
+    /// The salt of every bid's receipt, in auctions issuing receipts.
+    pub receipt_salts: Option<Vec<u64>>,
+    /// Whether the trace counts the statistics of the bids, in auctions publishing them.
+    pub statistics: bool,
 }
 
-impl <F> PrivateInput<F> {
-    pub fn new(private_modulus: F, private_exponent: F) -> Self {
+impl <F: Field> PrivateInput<F> {
+    pub fn new(private_modulus: F, private_exponent: u32) -> Self {
+        Self {
+            private_modulus,
+            private_exponent,
+            partial_decryptions: vec![],
+            public_exponent: 0,
+            squarings: None,
+            whitelist: None,
+            reveal: false,
+            receipt_salts: None,
+            statistics: false,
+        }
+    }
+
+    /// The input of a threshold decryption: no private exponent, only the operators' partial decryptions and the
+    /// public exponent.
+    pub fn threshold(private_modulus: F, public_exponent: u32, partial_decryptions: Vec<Vec<u32>>) -> Self {
         Self {
             private_modulus,
-            private_exponent
+            private_exponent: 0,
+            partial_decryptions,
+            public_exponent,
+            squarings: None,
+            whitelist: None,
+            reveal: false,
+            receipt_salts: None,
+            statistics: false,
+        }
+    }
+
+    /// The input of a time-lock decryption, see `time_lock`.
+    pub fn time_lock(modulus: F, squarings: u32) -> Self {
+        Self {
+            private_modulus: modulus,
+            private_exponent: 0,
+            partial_decryptions: vec![],
+            public_exponent: 0,
+            squarings: Some(squarings),
+            whitelist: None,
+            reveal: false,
+            receipt_salts: None,
+            statistics: false,
         }
     }
-}
\ No newline at end of file
+
+    /// Restricts the auction to the addresses of `whitelist`, see `silent_bid_verifier::gadgets::MerkleWhitelist`.
+    pub fn with_whitelist(mut self, whitelist: Whitelist) -> Self {
+        self.whitelist = Some(whitelist);
+        self
+    }
+
+    /// Commits to every decrypted bid, see `silent_bid_verifier::gadgets::BidReveal`.
+    pub fn with_reveal(mut self) -> Self {
+        self.reveal = true;
+        self
+    }
+
+    /// Commits to every decrypted bid hashed with its salt, see `silent_bid_verifier::receipt`.
+    pub fn with_receipts(mut self, salts: Vec<u64>) -> Self {
+        self.receipt_salts = Some(salts);
+        self
+    }
+
+    /// Counts the statistics of the bids, see `silent_bid_verifier::gadgets::BidStatistics`.
+    pub fn with_statistics(mut self) -> Self {
+        self.statistics = true;
+        self
+    }
+}
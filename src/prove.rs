@@ -0,0 +1,122 @@
+use p3_air::BaseAir;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark::prove;
+use silent_bid_verifier::air::ProverAir;
+use silent_bid_verifier::config::{
+    make_challenger, make_config, make_evm_challenger, make_evm_config, EvmConfig, MyConfig, Val, CONFIG_ID, EVM_CONFIG_ID,
+};
+use silent_bid_verifier::debug_builder::check_constraints;
+use silent_bid_verifier::proof_file::ProofFile;
+use silent_bid_verifier::public_input::{Decryption, PublicBid};
+use silent_bid_verifier::AuctionPublicValues;
+
+/// Checks `trace` against the auction AIR and proves it. The proof file can be checked with `verify_auction`.
+pub fn prove_auction(
+    bidders: Vec<PublicBid>,
+    trace: RowMajorMatrix<Val>,
+    public_values: &AuctionPublicValues,
+) -> Result<ProofFile<MyConfig>, String> {
+    let (air, public_values) = checked_air(bidders, &trace, public_values)?;
+    let proof = prove(&make_config(), &air, &mut make_challenger(), trace, &public_values);
+    Ok(ProofFile::new(CONFIG_ID, &public_values, proof))
+}
+
+/// Proves that none of `bidders` placed a valid bid, so that the auction ends without a winner and the owner keeps
+/// its collateral. Fails when `trace` has a winner.
+pub fn prove_no_contest(
+    bidders: Vec<PublicBid>,
+    trace: RowMajorMatrix<Val>,
+    modulus: u64,
+    hash_value: u64,
+) -> Result<ProofFile<MyConfig>, String> {
+    prove_auction(bidders, trace, &AuctionPublicValues::no_contest(modulus, hash_value))
+}
+
+/// `prove_auction` with `EvmConfig`, whose transcript and proof file layout a Solidity verifier can replay.
+pub fn prove_auction_evm(
+    bidders: Vec<PublicBid>,
+    trace: RowMajorMatrix<Val>,
+    public_values: &AuctionPublicValues,
+) -> Result<ProofFile<EvmConfig>, String> {
+    if public_values.decryption != Decryption::Owner {
+        return Err("the Solidity constraint evaluator only supports owner decryption".to_string());
+    }
+    let extended = public_values.reveal.is_some() || public_values.receipts.is_some();
+    if public_values.whitelist.is_some() || !public_values.statistics.is_empty() || extended {
+        return Err("the Solidity constraint evaluator does not support whitelists, statistics, full reveal nor receipts".to_string());
+    }
+    let (air, public_values) = checked_air(bidders, &trace, public_values)?;
+    let proof = prove(&make_evm_config(), &air, &mut make_evm_challenger(), trace, &public_values);
+    Ok(ProofFile::new(EVM_CONFIG_ID, &public_values, proof))
+}
+
+fn checked_air(
+    bidders: Vec<PublicBid>,
+    trace: &RowMajorMatrix<Val>,
+    public_values: &AuctionPublicValues,
+) -> Result<(ProverAir, Vec<Val>), String> {
+    let air = ProverAir::for_auction(bidders, public_values);
+    let width = <ProverAir as BaseAir<Val>>::width(&air);
+    if trace.width() != width {
+        return Err(format!("trace has {} columns, the AIR of the public values takes {}", trace.width(), width));
+    }
+    let public_values = public_values.to_field_elements::<Val>();
+    if let Err(report) = check_constraints(&air, trace, &public_values) {
+        return Err(format!("trace does not satisfy the constraints\n{}", report));
+    }
+    Ok((air, public_values))
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_goldilocks::Goldilocks;
+    use silent_bid_verifier::config::{MyConfig, CONFIG_ID};
+    use silent_bid_verifier::proof_file::{ProofFile, FORMAT_VERSION, MAGIC};
+    use silent_bid_verifier::utils::bytes_to_address;
+    use silent_bid_verifier::{verify_auction, AuctionPublicValues, VerifyError};
+    use crate::fixtures::{auction_trace, demo_bids};
+    use super::prove_auction;
+
+    fn proof_file() -> (ProofFile<MyConfig>, AuctionPublicValues) {
+        let (trace, public_values) = auction_trace(&demo_bids());
+        let public_values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        (prove_auction(demo_bids(), trace, &public_values).unwrap(), public_values)
+    }
+
+    #[test]
+    fn round_trips_binary_and_json() {
+        let (file, _) = proof_file();
+        let expected: Vec<Goldilocks> = file.public_values().unwrap();
+
+        let from_bytes = ProofFile::<MyConfig>::from_bytes(&file.to_bytes(), CONFIG_ID).unwrap();
+        assert_eq!(from_bytes.public_values::<Goldilocks>().unwrap(), expected);
+        assert_eq!(from_bytes.to_bytes(), file.to_bytes());
+
+        let from_json = ProofFile::<MyConfig>::from_json(&file.to_json(), CONFIG_ID).unwrap();
+        assert_eq!(from_json.to_bytes(), file.to_bytes());
+    }
+
+    #[test]
+    fn rejects_other_versions_and_configs() {
+        let (file, _) = proof_file();
+        let mut bytes = file.to_bytes();
+        bytes[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        assert!(ProofFile::<MyConfig>::from_bytes(&bytes, CONFIG_ID).is_err());
+        assert!(ProofFile::<MyConfig>::from_bytes(&file.to_bytes(), "other-config").is_err());
+        assert!(ProofFile::<MyConfig>::from_json(&file.to_json(), "other-config").is_err());
+    }
+
+    #[test]
+    fn verifier_returns_the_outcome() {
+        let (file, public_values) = proof_file();
+        let outcome = verify_auction(&file.to_bytes(), &public_values).unwrap();
+        assert_eq!(bytes_to_address(&outcome.winner), "0x95222290dd7278aa3ddd389cc1e1d165cc4bafdd");
+        assert_eq!(outcome.winner_amount, 3035);
+
+        let mut other_winner = public_values.clone();
+        other_winner.winner_address[19] = 0xe5;
+        assert!(matches!(verify_auction(&file.to_bytes(), &other_winner), Err(VerifyError::InvalidPublicValues(_))));
+        assert!(matches!(verify_auction(&file.to_bytes()[1..], &public_values), Err(VerifyError::InvalidProofFile(_))));
+    }
+}
@@ -1,6 +0,0 @@
-#[derive(Clone)]
-pub struct PublicBid {
-    pub bidder: String,
-    pub encrypted_amount: String,
-}
-
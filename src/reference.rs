@@ -0,0 +1,183 @@
+#[cfg(test)]
+use p3_field::PrimeField64;
+#[cfg(test)]
+use silent_bid_verifier::columns::{ADDRESS_BYTES, BASE};
+use silent_bid_verifier::public_input::PublicBid;
+#[cfg(test)]
+use silent_bid_verifier::AuctionOutcome;
+
+/// Owner key of the 32-bit RSA scheme, see `document/proving_service.md`.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug)]
+pub struct RsaKey {
+    pub modulus: u32,
+    pub private_exponent: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AuctionRules {
+    /// Base of the rolling hash over addresses and encrypted bids.
+    #[cfg(test)]
+    pub hash_base: u64,
+    /// A decrypted value is `amount * nonce_modulus + nonce`.
+    pub nonce_modulus: u64,
+}
+
+impl Default for AuctionRules {
+    fn default() -> Self {
+        Self {
+            #[cfg(test)]
+            hash_base: BASE as u64,
+            nonce_modulus: 1000,
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecryptedBid {
+    pub bidder: [u8; ADDRESS_BYTES],
+    /// False when a decrypted 4-byte chunk does not fit in 16 bits.
+    pub valid: bool,
+    pub amount: u64,
+    pub nonce: u64,
+}
+
+pub(crate) fn pow_mod(base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut base = base % modulus;
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result
+}
+
+pub(crate) fn encrypted_chunks(bid: &PublicBid) -> Vec<u32> {
+    let mut encrypted = bid.encrypted_amount.clone();
+    encrypted.resize(encrypted.len().next_multiple_of(4), 0);
+    encrypted.chunks(4).map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap())).collect()
+}
+
+/// Decrypts every 4 bytes of the bid to a 16-bit limb, least significant first. Zero chunks are skipped without
+/// taking a limb, and the bid is invalid from the first limb that does not fit in 16 bits.
+#[cfg(test)]
+pub fn decrypt_bid(bid: &PublicBid, key: &RsaKey, rules: &AuctionRules) -> DecryptedBid {
+    let mut value: u64 = 0;
+    let mut gap: u64 = 1;
+    let mut valid = true;
+    for chunk in encrypted_chunks(bid) {
+        if chunk == 0 {
+            continue;
+        }
+        let limb = pow_mod(chunk as u64, key.private_exponent as u64, key.modulus as u64);
+        if limb > u16::MAX as u64 {
+            valid = false;
+            break;
+        }
+        value += limb * gap;
+        gap <<= 16;
+    }
+    DecryptedBid {
+        bidder: bid.bidder,
+        valid,
+        amount: if valid { value / rules.nonce_modulus } else { 0 },
+        nonce: if valid { value % rules.nonce_modulus } else { 0 },
+    }
+}
+
+/// Rolling hash of every bidder address and encrypted bid, 4 little-endian bytes per term.
+#[cfg(test)]
+pub fn hash_bids<F: PrimeField64>(bids: &[PublicBid], rules: &AuctionRules) -> F {
+    let base = F::from_canonical_u64(rules.hash_base);
+    let mut hash = F::zero();
+    let mut lim = F::one();
+    for bid in bids {
+        let terms = bid.bidder.chunks(4).map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()));
+        for term in terms.chain(encrypted_chunks(bid)) {
+            hash += F::from_canonical_u32(term) * lim;
+            lim *= base;
+        }
+    }
+    hash
+}
+
+/// Runs the auction without building a trace: decrypts and validates every bid, hashes them, and picks the
+/// highest valid amount. Ties go to the earliest bid.
+#[cfg(test)]
+pub fn run_auction<F: PrimeField64>(bids: &[PublicBid], key: &RsaKey, rules: &AuctionRules) -> AuctionOutcome {
+    let mut winner = [0u8; ADDRESS_BYTES];
+    let mut winner_amount = 0;
+    for bid in bids {
+        let decrypted = decrypt_bid(bid, key, rules);
+        if decrypted.valid && decrypted.amount > winner_amount {
+            winner = decrypted.bidder;
+            winner_amount = decrypted.amount;
+        }
+    }
+    AuctionOutcome {
+        winner,
+        winner_amount,
+        hash_value: hash_bids::<F>(bids, rules).as_canonical_u64(),
+    }
+}
+
+/// Encrypts `amount` and `nonce` the way a bidder does: 16-bit limbs, least significant first, each raised to
+/// `public_exponent` and written as 4 little-endian bytes. `limbs` pads the bid with encrypted zeros.
+pub fn encrypt_bid(amount: u64, nonce: u64, public_exponent: u32, modulus: u32, limbs: usize, rules: &AuctionRules) -> Vec<u8> {
+    let mut value = amount * rules.nonce_modulus + nonce;
+    let mut bytes = vec![];
+    for _ in 0..limbs {
+        let limb = value & 0xffff;
+        value >>= 16;
+        let encrypted = pow_mod(limb, public_exponent as u64, modulus as u64) as u32;
+        bytes.extend(encrypted.to_le_bytes());
+    }
+    assert_eq!(value, 0, "bid does not fit in {} limbs", limbs);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::PrimeField64;
+    use p3_goldilocks::Goldilocks;
+    use proptest::prelude::*;
+    use silent_bid_verifier::air::ProverAir;
+    use silent_bid_verifier::debug_builder::check_constraints;
+    use silent_bid_verifier::AuctionPublicValues;
+    use crate::fixtures::{private_input, MODULUS, PRIVATE_EXPONENT, PUBLIC_EXPONENT};
+    use silent_bid_verifier::public_input::PublicBid;
+    use crate::generate_execution_trace::generate_execution_trace;
+    use super::{encrypt_bid, run_auction, AuctionRules, RsaKey};
+
+    fn bid_strategy() -> impl Strategy<Value = PublicBid> {
+        let address = prop::array::uniform20(any::<u8>());
+        // small amounts make ties and zero bids likely
+        let well_formed = (prop_oneof![0u64..3, 0u64..1_000_000_000], 0u64..1000)
+            .prop_map(|(amount, nonce)| encrypt_bid(amount, nonce, PUBLIC_EXPONENT, MODULUS, 4, &AuctionRules::default()));
+        let garbage = prop::collection::vec(any::<u8>(), 16);
+        (address, prop_oneof![4 => well_formed, 1 => garbage])
+            .prop_map(|(bidder, encrypted_amount)| PublicBid { bidder, encrypted_amount })
+    }
+
+    proptest! {
+        #[test]
+        fn trace_generator_matches_reference(bids in prop::collection::vec(bid_strategy(), 1..5)) {
+            let key = RsaKey { modulus: MODULUS, private_exponent: PRIVATE_EXPONENT };
+            let expected = run_auction::<Goldilocks>(&bids, &key, &AuctionRules::default());
+
+            let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&bids, &private_input());
+            prop_assert_eq!(winner, expected.winner);
+            prop_assert_eq!(winner_amount.as_canonical_u64(), expected.winner_amount);
+            prop_assert_eq!(hash_value.as_canonical_u64(), expected.hash_value);
+
+            // and the trace satisfies the constraints, whatever the addresses
+            let public_values = AuctionPublicValues::new(MODULUS as u64, expected.hash_value, expected.winner_amount, winner);
+            let report = check_constraints(&ProverAir::new(bids), &trace, &public_values.to_field_elements());
+            prop_assert!(report.is_ok(), "{}", report.unwrap_err());
+        }
+    }
+}
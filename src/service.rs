@@ -0,0 +1,503 @@
+//! The proving service: auction jobs are submitted over HTTP, proved by a pool of worker threads and their status and
+//! proofs fetched back.
+//!
+//! | request | response |
+//! | --- | --- |
+//! | `POST /jobs` with a `JobRequest` | `202 {"id": ..}` |
+//! | `GET /jobs/{id}` | `200` `JobView` |
+//! | `GET /jobs/{id}/proof` | `200` the binary proof file, `409` until the job is done |
+//!
+//! Jobs are kept in a `JobStore`: a job submitted again for the same auction, bids, key and config is the same job,
+//! and jobs that were queued or running when the service stopped are resumed when it starts again.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use p3_field::PrimeField64;
+use serde::{Deserialize, Serialize};
+use silent_bid_verifier::config::Val;
+use silent_bid_verifier::public_input::{BidHash, PublicBid};
+use silent_bid_verifier::utils::bytes_to_address;
+use silent_bid_verifier::AuctionPublicValues;
+use crate::generate_execution_trace::generate_execution_trace;
+use crate::http::{self, Request, Response};
+use crate::ingest::check_bid_hash;
+use crate::job_store::{JobStore, StoredJob};
+use crate::keystore::Keystore;
+use crate::prove::{prove_auction, prove_auction_evm};
+
+pub type JobId = u64;
+
+/// A bid as published, in hex.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BidInput {
+    pub bidder: String,
+    pub encrypted_amount: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JobRequest {
+    pub auction_id: String,
+    /// The id of the owner's key in the service's keystore; the key itself never travels with the bids.
+    pub key_id: String,
+    /// In the order the contract received them.
+    pub bids: Vec<BidInput>,
+    /// The hash the contract accumulated, checked before the job is accepted.
+    #[serde(default)]
+    pub bid_hash: Option<u64>,
+    /// Prove with `EvmConfig`.
+    #[serde(default)]
+    pub evm: bool,
+}
+
+impl JobRequest {
+    pub fn public_bids(&self) -> Result<Vec<PublicBid>, String> {
+        self.bids.iter().map(|bid| PublicBid::from_hex(&bid.bidder, &bid.encrypted_amount)).collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done { winner: String, winner_amount: u64, hash_value: u64 },
+    Failed { error: String },
+}
+
+/// What `GET /jobs/{id}` returns.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JobView {
+    pub id: JobId,
+    pub auction_id: String,
+    #[serde(flatten)]
+    pub status: JobStatus,
+}
+
+#[derive(Debug)]
+pub enum ServiceError {
+    UnknownKey(String),
+    InvalidJob(String),
+    Store(String),
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::UnknownKey(id) => write!(f, "unknown key {}", id),
+            ServiceError::InvalidJob(e) => write!(f, "invalid job: {}", e),
+            ServiceError::Store(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+/// What makes two requests the same job: the auction id, the hash of its bids, the key id and `evm`.
+type JobKey = (String, u64, String, bool);
+
+fn job_key(request: &JobRequest, bids: &[PublicBid]) -> JobKey {
+    (request.auction_id.clone(), BidHash::<Val>::of(bids), request.key_id.clone(), request.evm)
+}
+
+#[derive(Default)]
+struct Jobs {
+    next_id: JobId,
+    jobs: BTreeMap<JobId, StoredJob>,
+    /// The job of each auction id, bid-list hash, key id and config.
+    by_bids: HashMap<JobKey, JobId>,
+    queue: VecDeque<JobId>,
+}
+
+struct Shared {
+    keystore: Keystore,
+    store: JobStore,
+    jobs: Mutex<Jobs>,
+    queued: Condvar,
+}
+
+/// Handle to the job queue and its workers; clones share them.
+#[derive(Clone)]
+pub struct ProvingService {
+    shared: Arc<Shared>,
+}
+
+impl ProvingService {
+    /// Opens the job store in `dir`, queues the jobs it has not finished and starts `workers` proving threads. They
+    /// run for the rest of the process. Keys are decrypted from `keystore` for each job, only while its trace is
+    /// generated.
+    pub fn open(keystore: Keystore, workers: usize, dir: impl AsRef<Path>) -> Result<Self, String> {
+        let (store, stored) = JobStore::open(dir)?;
+        let mut jobs = Jobs::default();
+        for job in stored {
+            if let Ok(bids) = job.request.public_bids() {
+                jobs.by_bids.insert(job_key(&job.request, &bids), job.id);
+            }
+            if matches!(job.status, JobStatus::Queued | JobStatus::Running) {
+                jobs.queue.push_back(job.id);
+            }
+            jobs.next_id = job.id;
+            jobs.jobs.insert(job.id, job);
+        }
+
+        let shared = Arc::new(Shared { keystore, store, jobs: Mutex::new(jobs), queued: Condvar::new() });
+        for _ in 0..workers {
+            let shared = shared.clone();
+            thread::spawn(move || work(&shared));
+        }
+        Ok(Self { shared })
+    }
+
+    /// Checks and queues a job. A job for the same auction id, bids, key and config as an earlier one is that job,
+    /// queued again if it failed.
+    pub fn submit(&self, request: JobRequest) -> Result<JobId, ServiceError> {
+        if !self.shared.keystore.contains(&request.key_id) {
+            return Err(ServiceError::UnknownKey(request.key_id));
+        }
+        let bids = request.public_bids().map_err(ServiceError::InvalidJob)?;
+        if bids.is_empty() {
+            return Err(ServiceError::InvalidJob("no bids".to_string()));
+        }
+        if let Some(expected) = request.bid_hash {
+            check_bid_hash(&bids, expected).map_err(|e| ServiceError::InvalidJob(e.to_string()))?;
+        }
+
+        let key = job_key(&request, &bids);
+
+        let mut jobs = self.shared.jobs.lock().unwrap();
+        let jobs = &mut *jobs;
+        if let Some(&id) = jobs.by_bids.get(&key) {
+            let job = jobs.jobs.get_mut(&id).unwrap();
+            if matches!(job.status, JobStatus::Failed { .. }) {
+                *job = StoredJob { id, request, status: JobStatus::Queued, public_values: None };
+                self.shared.store.save(job).map_err(ServiceError::Store)?;
+                jobs.queue.push_back(id);
+                self.shared.queued.notify_one();
+            }
+            return Ok(id);
+        }
+        let id = jobs.next_id + 1;
+        let job = StoredJob { id, request, status: JobStatus::Queued, public_values: None };
+        self.shared.store.save(&job).map_err(ServiceError::Store)?;
+        jobs.next_id = id;
+        jobs.jobs.insert(id, job);
+        jobs.by_bids.insert(key, id);
+        jobs.queue.push_back(id);
+        self.shared.queued.notify_one();
+        Ok(id)
+    }
+
+    pub fn status(&self, id: JobId) -> Option<JobView> {
+        let jobs = self.shared.jobs.lock().unwrap();
+        let job = jobs.jobs.get(&id)?;
+        Some(JobView { id, auction_id: job.request.auction_id.clone(), status: job.status.clone() })
+    }
+
+    /// The binary proof file of a done job.
+    pub fn proof(&self, id: JobId) -> Option<Vec<u8>> {
+        let done = matches!(self.status(id)?.status, JobStatus::Done { .. });
+        if done { self.shared.store.load_proof(id) } else { None }
+    }
+
+    pub fn handle(&self, request: &Request) -> Response {
+        let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("POST", ["jobs"]) => {
+                let job = match serde_json::from_slice(&request.body) {
+                    Ok(job) => job,
+                    Err(e) => return Response::error(400, format!("invalid job: {}", e)),
+                };
+                match self.submit(job) {
+                    Ok(id) => Response::json(202, &serde_json::json!({ "id": id })),
+                    Err(e @ ServiceError::Store(_)) => Response::error(500, e.to_string()),
+                    Err(e) => Response::error(400, e.to_string()),
+                }
+            }
+            ("GET", ["jobs", id]) => match id.parse().ok().and_then(|id| self.status(id)) {
+                Some(view) => Response::json(200, &view),
+                None => Response::error(404, "unknown job"),
+            },
+            ("GET", ["jobs", id, "proof"]) => {
+                let Some(view) = id.parse().ok().and_then(|id| self.status(id)) else {
+                    return Response::error(404, "unknown job");
+                };
+                match self.proof(view.id) {
+                    Some(proof) => Response::bytes(proof),
+                    None => Response::error(409, "the job is not done"),
+                }
+            }
+            (_, ["jobs", ..]) => Response::error(405, "method not allowed"),
+            _ => Response::error(404, "not found"),
+        }
+    }
+
+    /// Serves the HTTP API on `listener`, one thread per connection.
+    pub fn serve(&self, listener: TcpListener) {
+        for stream in listener.incoming().flatten() {
+            let service = self.clone();
+            thread::spawn(move || service.connection(stream));
+        }
+    }
+
+    fn connection(&self, mut stream: TcpStream) {
+        let response = match http::read_request(&mut stream) {
+            Ok(request) => self.handle(&request),
+            Err(e) => Response::error(400, e.to_string()),
+        };
+        let _ = http::write_response(&mut stream, &response);
+    }
+}
+
+fn work(shared: &Shared) {
+    loop {
+        let job = {
+            let mut jobs = shared.queued.wait_while(shared.jobs.lock().unwrap(), |jobs| jobs.queue.is_empty()).unwrap();
+            let id = jobs.queue.pop_front().unwrap();
+            let job = jobs.jobs.get_mut(&id).unwrap();
+            job.status = JobStatus::Running;
+            job.clone()
+        };
+        // a job that panics fails alone instead of taking its worker down
+        let status = panic::catch_unwind(AssertUnwindSafe(|| prove_job(shared, &job)))
+            .unwrap_or_else(|_| Err("the prover panicked".to_string()))
+            .unwrap_or_else(|error| JobStatus::Failed { error });
+
+        let mut jobs = shared.jobs.lock().unwrap();
+        let job = jobs.jobs.get_mut(&job.id).unwrap();
+        job.status = status;
+        if let Err(e) = shared.store.save(job) {
+            // the job stays queued on disk and is proved again after a restart
+            eprintln!("job {}: {}", job.id, e);
+        }
+    }
+}
+
+/// Proves a job, resuming from its stored trace if it has one. A job whose worker stopped after storing the proof,
+/// which drops the trace, is done.
+fn prove_job(shared: &Shared, job: &StoredJob) -> Result<JobStatus, String> {
+    if let (Some(values), Some(_)) = (&job.public_values, shared.store.load_proof(job.id)) {
+        return Ok(done(&AuctionPublicValues::from_canonical(values)?));
+    }
+    let request = &job.request;
+    let bids = request.public_bids()?;
+    let (trace, public_values) = match &job.public_values {
+        Some(values) => (shared.store.load_trace(job.id)?, AuctionPublicValues::from_canonical(values)?),
+        None => {
+            let public_values;
+            let trace = {
+                let key = shared.keystore.load(&request.key_id).map_err(|e| e.to_string())?;
+                let (trace, winner, winner_amount, hash_value) =
+                    generate_execution_trace(&bids, &key.private_input());
+                public_values = AuctionPublicValues::new(
+                    key.modulus as u64,
+                    hash_value.as_canonical_u64(),
+                    winner_amount.as_canonical_u64(),
+                    winner,
+                );
+                trace
+                // `key` is zeroized here, before proving
+            };
+            shared.store.save_trace(job.id, &trace)?;
+            let values = public_values.to_field_elements::<Val>().iter().map(|v| v.as_canonical_u64()).collect();
+            let mut jobs = shared.jobs.lock().unwrap();
+            let stored = jobs.jobs.get_mut(&job.id).unwrap();
+            stored.public_values = Some(values);
+            shared.store.save(stored)?;
+            (trace, public_values)
+        }
+    };
+    let proof = if request.evm {
+        prove_auction_evm(bids, trace, &public_values)?.to_bytes()
+    } else {
+        prove_auction(bids, trace, &public_values)?.to_bytes()
+    };
+    shared.store.save_proof(job.id, &proof)?;
+    Ok(done(&public_values))
+}
+
+fn done(public_values: &AuctionPublicValues) -> JobStatus {
+    JobStatus::Done {
+        winner: bytes_to_address(&public_values.winner_address),
+        winner_amount: public_values.winner_amount,
+        hash_value: public_values.hash_value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use p3_field::PrimeField64;
+    use silent_bid_verifier::utils::bytes_to_hex;
+    use silent_bid_verifier::{verify_auction, AuctionPublicValues};
+    use crate::fixtures::{auction_trace, demo_bids, demo_keystore, temp_dir, MODULUS, PRIVATE_EXPONENT};
+    use crate::job_store::{JobStore, StoredJob};
+    use crate::http::{self, Request};
+    use crate::keystore::AuctionKey;
+    use crate::prove::prove_auction;
+    use super::{BidInput, JobId, JobRequest, JobStatus, JobView, ProvingService};
+
+    /// Talks to a service over HTTP, as a client in another process would.
+    struct Client {
+        url: String,
+    }
+
+    impl Client {
+        fn submit(&self, job: &JobRequest) -> Result<JobId, String> {
+            let (status, body) = http::send(&format!("{}/jobs", self.url), "POST", &serde_json::to_vec(job).unwrap())?;
+            let body: serde_json::Value = serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+            match status {
+                202 => Ok(body["id"].as_u64().unwrap()),
+                _ => Err(body["error"].to_string()),
+            }
+        }
+
+        fn status(&self, id: JobId) -> JobView {
+            let (_, body) = http::send(&format!("{}/jobs/{}", self.url, id), "GET", &[]).unwrap();
+            serde_json::from_slice(&body).unwrap()
+        }
+
+        fn proof(&self, id: JobId) -> (u16, Vec<u8>) {
+            http::send(&format!("{}/jobs/{}/proof", self.url, id), "GET", &[]).unwrap()
+        }
+    }
+
+    /// Polls `status` until the job is done or failed.
+    fn wait(status: impl Fn() -> JobView) -> JobView {
+        let start = Instant::now();
+        loop {
+            let view = status();
+            if !matches!(view.status, JobStatus::Queued | JobStatus::Running) {
+                return view;
+            }
+            assert!(start.elapsed() < Duration::from_secs(300), "job {} is stuck", view.id);
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    fn demo_job() -> JobRequest {
+        let bids = demo_bids()
+            .iter()
+            .map(|bid| BidInput { bidder: bid.bidder_hex(), encrypted_amount: bytes_to_hex(&bid.encrypted_amount) })
+            .collect();
+        JobRequest { auction_id: "demo".to_string(), key_id: "demo".to_string(), bids, bid_hash: None, evm: false }
+    }
+
+    fn request(method: &str, path: &str, body: &[u8]) -> Request {
+        Request { method: method.to_string(), path: path.to_string(), body: body.to_vec() }
+    }
+
+    #[test]
+    fn proves_jobs_submitted_over_http() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = Client { url: format!("http://{}", listener.local_addr().unwrap()) };
+        let service = ProvingService::open(demo_keystore("service-http-keys"), 2, temp_dir("service-http")).unwrap();
+        thread::spawn(move || service.serve(listener));
+
+        let (_, public_values) = auction_trace(&demo_bids());
+        let public_values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        let job = JobRequest { bid_hash: Some(public_values.hash_value), ..demo_job() };
+        let ids = [client.submit(&job).unwrap(), client.submit(&JobRequest { evm: true, ..job }).unwrap()];
+
+        for id in ids {
+            let view = wait(|| client.status(id));
+            assert_eq!(view.auction_id, "demo");
+            assert_eq!(
+                view.status,
+                JobStatus::Done {
+                    winner: "0x95222290dd7278aa3ddd389cc1e1d165cc4bafdd".to_string(),
+                    winner_amount: 3035,
+                    hash_value: public_values.hash_value,
+                }
+            );
+            let (status, proof) = client.proof(id);
+            assert_eq!(status, 200);
+            assert_eq!(verify_auction(&proof, &public_values).unwrap().winner_amount, 3035);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_jobs() {
+        // no workers, so jobs stay queued
+        let service = ProvingService::open(demo_keystore("service-invalid-keys"), 0, temp_dir("service-invalid")).unwrap();
+        let submit = |job: &JobRequest| service.handle(&request("POST", "/jobs", &serde_json::to_vec(job).unwrap())).status;
+
+        assert_eq!(submit(&JobRequest { key_id: "other".to_string(), ..demo_job() }), 400);
+        let mut bad_hex = demo_job();
+        bad_hex.bids[0].encrypted_amount.push('x');
+        assert_eq!(submit(&bad_hex), 400);
+        assert_eq!(submit(&JobRequest { bids: vec![], ..demo_job() }), 400);
+        assert_eq!(submit(&JobRequest { bid_hash: Some(1), ..demo_job() }), 400);
+        assert_eq!(service.handle(&request("POST", "/jobs", b"{")).status, 400);
+
+        assert_eq!(submit(&demo_job()), 202);
+        assert_eq!(service.status(1).unwrap().status, JobStatus::Queued);
+        assert_eq!(service.handle(&request("GET", "/jobs/1/proof", &[])).status, 409);
+        assert_eq!(service.handle(&request("GET", "/jobs/2", &[])).status, 404);
+        assert_eq!(service.handle(&request("DELETE", "/jobs/1", &[])).status, 405);
+    }
+
+    #[test]
+    fn resubmitting_a_job_returns_it() {
+        let keystore = demo_keystore("service-idempotent-keys");
+        keystore.import("copy", &AuctionKey { modulus: MODULUS, private_exponent: PRIVATE_EXPONENT }).unwrap();
+        let service = ProvingService::open(keystore, 0, temp_dir("service-idempotent")).unwrap();
+        let id = service.submit(demo_job()).unwrap();
+        assert_eq!(service.submit(demo_job()).unwrap(), id);
+        // the same bids proved with another key or config are another job
+        assert_ne!(service.submit(JobRequest { evm: true, ..demo_job() }).unwrap(), id);
+        assert_ne!(service.submit(JobRequest { key_id: "copy".to_string(), ..demo_job() }).unwrap(), id);
+        assert_ne!(service.submit(JobRequest { auction_id: "other".to_string(), ..demo_job() }).unwrap(), id);
+        let mut other_bids = demo_job();
+        other_bids.bids.pop();
+        assert_ne!(service.submit(other_bids).unwrap(), id);
+    }
+
+    #[test]
+    fn resumes_jobs_after_a_restart() {
+        let dir = temp_dir("service-restart");
+        let queued = ProvingService::open(demo_keystore("service-restart-keys"), 0, &dir).unwrap().submit(demo_job()).unwrap();
+
+        // a job whose worker stopped after storing the trace
+        let (store, _) = JobStore::open(&dir).unwrap();
+        let (trace, public_values) = auction_trace(&demo_bids());
+        let interrupted = StoredJob {
+            id: queued + 1,
+            request: JobRequest { auction_id: "interrupted".to_string(), ..demo_job() },
+            status: JobStatus::Running,
+            public_values: Some(public_values.iter().map(|v| v.as_canonical_u64()).collect()),
+        };
+        store.save_trace(interrupted.id, &trace).unwrap();
+        store.save(&interrupted).unwrap();
+        // and one whose worker stopped after storing the proof, which dropped the trace
+        let proved = StoredJob {
+            id: queued + 2,
+            request: JobRequest { auction_id: "proved".to_string(), ..demo_job() },
+            ..interrupted.clone()
+        };
+        let values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        let proof = prove_auction(demo_bids(), trace.clone(), &values).unwrap();
+        store.save_trace(proved.id, &trace).unwrap();
+        store.save(&proved).unwrap();
+        store.save_proof(proved.id, &proof.to_bytes()).unwrap();
+
+        let service = ProvingService::open(demo_keystore("service-restart-keys"), 1, &dir).unwrap();
+        let public_values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        for id in [queued, interrupted.id, proved.id] {
+            assert!(matches!(wait(|| service.status(id).unwrap()).status, JobStatus::Done { winner_amount: 3035, .. }));
+            verify_auction(&service.proof(id).unwrap(), &public_values).unwrap();
+        }
+        assert_eq!(service.submit(demo_job()).unwrap(), queued);
+        assert_eq!(service.submit(JobRequest { auction_id: "new".to_string(), ..demo_job() }).unwrap(), queued + 3);
+
+        // and finished jobs stay finished
+        let service = ProvingService::open(demo_keystore("service-restart-keys"), 0, &dir).unwrap();
+        assert!(matches!(service.status(interrupted.id).unwrap().status, JobStatus::Done { .. }));
+        assert!(service.proof(interrupted.id).is_some());
+    }
+}
@@ -0,0 +1,513 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use p3_goldilocks::Goldilocks;
+use silent_bid_verifier::public_input::{Address, BidHash, PublicBid};
+use silent_bid_verifier::bid_proof::BidPublicValues;
+use silent_bid_verifier::{verify_auction, verify_bid, AuctionOutcome, AuctionPublicValues, AuctionResolution, VerifyError};
+
+/// Amounts of the deposit currency.
+pub type Wei = u128;
+
+/// What the owner fixes when creating the auction (the initial setup phase).
+#[derive(Clone, Debug)]
+pub struct AuctionParams {
+    pub owner: Address,
+    /// RSA modulus of the owner key that bids are encrypted to.
+    pub modulus: u64,
+    /// Deposit required with every bid. A winner pays its amount out of its deposits.
+    pub deposit: Wei,
+    /// Bids are accepted from `bid_start` (inclusive) to `bid_end` (exclusive).
+    pub bid_start: u64,
+    pub bid_end: u64,
+    /// The owner's result must be accepted before this time, or the auction times out.
+    pub proof_deadline: u64,
+    /// Posted by the owner at creation, returned on settlement and slashed to the bidders on a timeout.
+    pub collateral: Wei,
+    /// Public exponent of the owner key, set when every bid must come with a proof that it is well formed.
+    pub public_exponent: Option<u64>,
+    /// Tells the auction apart from the others of the contract, so that a bid proof made for one does not verify in
+    /// another.
+    pub auction_id: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuctionPhase {
+    /// Before `bid_start`.
+    Created,
+    Bidding,
+    /// Bidding closed, waiting for the owner's proof until `proof_deadline`.
+    Opening,
+    Settled,
+    /// The proof deadline passed without an accepted result.
+    TimedOut,
+}
+
+/// What the contract logs, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuctionEvent {
+    BidSubmitted(PublicBid),
+    AuctionSettled(AuctionOutcome),
+}
+
+#[derive(Debug)]
+pub enum SimulatorError {
+    /// The call is not allowed in the current phase.
+    WrongPhase { expected: AuctionPhase, actual: AuctionPhase },
+    WrongDeposit { expected: Wei, actual: Wei },
+    /// The auction requires a proof with every bid, see `submit_proven_bid`.
+    BidProofRequired,
+    /// The bid proof does not verify against the submitted bid and the deposit.
+    BidRejected(VerifyError),
+    NotOwner,
+    /// The proof does not verify against the accumulated hash and the claimed result.
+    ProofRejected(VerifyError),
+    NothingToWithdraw,
+}
+
+impl fmt::Display for SimulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimulatorError::WrongPhase { expected, actual } => write!(f, "auction is {:?}, expected {:?}", actual, expected),
+            SimulatorError::WrongDeposit { expected, actual } => write!(f, "deposit of {} wei, expected {}", actual, expected),
+            SimulatorError::BidProofRequired => write!(f, "the bid must come with a proof"),
+            SimulatorError::BidRejected(e) => write!(f, "bid rejected: {}", e),
+            SimulatorError::NotOwner => write!(f, "only the owner can submit the result"),
+            SimulatorError::ProofRejected(e) => write!(f, "proof rejected: {}", e),
+            SimulatorError::NothingToWithdraw => write!(f, "nothing to withdraw"),
+        }
+    }
+}
+
+impl std::error::Error for SimulatorError {}
+
+/// In-memory model of the auction contract, for testing the whole lifecycle offline: bidders deposit and submit
+/// encrypted bids during the window, the contract accumulates their hash, the owner submits the result with a
+/// proof that is checked by `verify_auction`, and deposits are then withdrawn. If the owner does not prove a result
+/// before the deadline, bidders reclaim their deposits plus a share of the owner's collateral.
+///
+/// Time only moves through `advance_to`. Balances are what each address can withdraw; nothing is transferred
+/// before settlement.
+pub struct AuctionSimulator {
+    params: AuctionParams,
+    now: u64,
+    bids: Vec<PublicBid>,
+    hash: BidHash<Goldilocks>,
+    deposits: HashMap<Address, Wei>,
+    outcome: Option<AuctionOutcome>,
+    withdrawn: HashSet<Address>,
+    /// The part of the collateral paid out to bidders after a timeout.
+    collateral_paid: Wei,
+    events: Vec<AuctionEvent>,
+}
+
+impl AuctionSimulator {
+    pub fn new(params: AuctionParams, now: u64) -> Self {
+        assert!(params.bid_start < params.bid_end, "empty bidding window");
+        assert!(params.bid_end <= params.proof_deadline, "proof deadline before the end of bidding");
+        Self {
+            params,
+            now,
+            bids: vec![],
+            hash: BidHash::default(),
+            deposits: HashMap::new(),
+            outcome: None,
+            withdrawn: HashSet::new(),
+            collateral_paid: 0,
+            events: vec![],
+        }
+    }
+
+    pub fn advance_to(&mut self, timestamp: u64) {
+        assert!(timestamp >= self.now, "time cannot go backwards");
+        self.now = timestamp;
+    }
+
+    pub fn phase(&self) -> AuctionPhase {
+        if self.outcome.is_some() {
+            AuctionPhase::Settled
+        } else if self.now < self.params.bid_start {
+            AuctionPhase::Created
+        } else if self.now < self.params.bid_end {
+            AuctionPhase::Bidding
+        } else if self.now < self.params.proof_deadline {
+            AuctionPhase::Opening
+        } else {
+            AuctionPhase::TimedOut
+        }
+    }
+
+    fn expect_phase(&self, expected: AuctionPhase) -> Result<(), SimulatorError> {
+        let actual = self.phase();
+        if actual != expected {
+            return Err(SimulatorError::WrongPhase { expected, actual });
+        }
+        Ok(())
+    }
+
+    pub fn submit_bid(&mut self, bidder: Address, encrypted_amount: Vec<u8>, deposit: Wei) -> Result<(), SimulatorError> {
+        self.check_bid(deposit)?;
+        if self.params.public_exponent.is_some() {
+            return Err(SimulatorError::BidProofRequired);
+        }
+        self.accept_bid(bidder, encrypted_amount, deposit);
+        Ok(())
+    }
+
+    /// `submit_bid` with a proof that the bid is well formed and within the deposit, checked by `verify_bid`
+    /// against the owner's public key, so that invalid bids never enter the auction. The proof must have been made
+    /// for `bidder` and this auction, so that a bid copied from another bidder or auction is refused.
+    pub fn submit_proven_bid(&mut self, bidder: Address, encrypted_amount: Vec<u8>, deposit: Wei, proof: &[u8]) -> Result<(), SimulatorError> {
+        self.check_bid(deposit)?;
+        if let Some(public_exponent) = self.params.public_exponent {
+            let public_values = BidPublicValues {
+                modulus: self.params.modulus,
+                public_exponent,
+                deposit: u64::try_from(deposit).unwrap_or(u64::MAX),
+                encrypted_amount: encrypted_amount.clone(),
+                bidder,
+                auction_id: self.params.auction_id,
+            };
+            verify_bid(proof, &public_values).map_err(SimulatorError::BidRejected)?;
+        }
+        self.accept_bid(bidder, encrypted_amount, deposit);
+        Ok(())
+    }
+
+    fn check_bid(&self, deposit: Wei) -> Result<(), SimulatorError> {
+        self.expect_phase(AuctionPhase::Bidding)?;
+        if deposit != self.params.deposit {
+            return Err(SimulatorError::WrongDeposit { expected: self.params.deposit, actual: deposit });
+        }
+        Ok(())
+    }
+
+    fn accept_bid(&mut self, bidder: Address, encrypted_amount: Vec<u8>, deposit: Wei) {
+        let bid = PublicBid { bidder, encrypted_amount };
+        self.hash.absorb(&bid);
+        *self.deposits.entry(bidder).or_default() += deposit;
+        self.bids.push(bid.clone());
+        self.events.push(AuctionEvent::BidSubmitted(bid));
+    }
+
+    /// The bids in submission order, as the owner reads them from the `BidSubmitted` events.
+    pub fn bids(&self) -> &[PublicBid] {
+        &self.bids
+    }
+
+    pub fn hash_value(&self) -> u64 {
+        self.hash.value()
+    }
+
+    pub fn events(&self) -> &[AuctionEvent] {
+        &self.events
+    }
+
+    pub fn outcome(&self) -> Option<&AuctionOutcome> {
+        self.outcome.as_ref()
+    }
+
+    /// How the auction ended, `None` while it is still open.
+    pub fn resolution(&self) -> Option<AuctionResolution> {
+        match self.phase() {
+            AuctionPhase::Settled => self.outcome.clone().map(AuctionResolution::from),
+            AuctionPhase::TimedOut => Some(AuctionResolution::TimedOut),
+            _ => None,
+        }
+    }
+
+    /// The public values a result claim is checked against: the owner's modulus and the accumulated hash come
+    /// from the contract, only the winner and amount from the caller.
+    pub fn public_values(&self, winner: Address, winner_amount: u64) -> AuctionPublicValues {
+        AuctionPublicValues::new(self.params.modulus, self.hash_value(), winner_amount, winner)
+    }
+
+    /// The owner's result, accepted only between the end of bidding and the proof deadline, and with a proof of it.
+    /// A no-contest proof (no winner) settles the auction without a payment.
+    pub fn submit_result(
+        &mut self,
+        caller: Address,
+        winner: Address,
+        winner_amount: u64,
+        proof: &[u8],
+    ) -> Result<AuctionOutcome, SimulatorError> {
+        if caller != self.params.owner {
+            return Err(SimulatorError::NotOwner);
+        }
+        self.expect_phase(AuctionPhase::Opening)?;
+        let outcome = verify_auction(proof, &self.public_values(winner, winner_amount)).map_err(SimulatorError::ProofRejected)?;
+        self.outcome = Some(outcome.clone());
+        self.events.push(AuctionEvent::AuctionSettled(outcome.clone()));
+        Ok(outcome)
+    }
+
+    /// What `account` may withdraw once the auction is over.
+    ///
+    /// - Settled: losing bidders get their deposits back, the winner its deposits minus the winning amount, and the
+    ///   owner that amount plus its collateral. A winner who bid more than it deposited pays all of its deposits.
+    /// - Timed out: bidders get their deposits back plus a share of the collateral in proportion to their deposits,
+    ///   rounded down, and the last bidder to withdraw also the rounding remainder, so that the whole collateral is
+    ///   paid out; the owner gets nothing.
+    pub fn balance(&self, account: Address) -> Wei {
+        if self.withdrawn.contains(&account) {
+            return 0;
+        }
+        let deposits = self.deposits.get(&account).copied().unwrap_or(0);
+        match self.resolution() {
+            None => 0,
+            Some(AuctionResolution::TimedOut) => deposits + self.collateral_share(account),
+            Some(AuctionResolution::NoContest { .. }) => {
+                let collateral = if account == self.params.owner { self.params.collateral } else { 0 };
+                deposits + collateral
+            }
+            Some(AuctionResolution::Won(outcome)) => {
+                let payment = (outcome.winner_amount as Wei).min(self.deposits.get(&outcome.winner).copied().unwrap_or(0));
+                let mut balance = if account == outcome.winner { deposits - payment } else { deposits };
+                if account == self.params.owner {
+                    balance += payment + self.params.collateral;
+                }
+                balance
+            }
+        }
+    }
+
+    pub fn withdraw(&mut self, account: Address) -> Result<Wei, SimulatorError> {
+        if self.resolution().is_none() {
+            return Err(SimulatorError::WrongPhase { expected: AuctionPhase::Settled, actual: self.phase() });
+        }
+        let balance = self.balance(account);
+        if balance == 0 {
+            return Err(SimulatorError::NothingToWithdraw);
+        }
+        if self.resolution() == Some(AuctionResolution::TimedOut) {
+            self.collateral_paid += self.collateral_share(account);
+        }
+        self.withdrawn.insert(account);
+        Ok(balance)
+    }
+
+    /// The part of the collateral `account` gets after a timeout: the rest of it for the last bidder to withdraw.
+    fn collateral_share(&self, account: Address) -> Wei {
+        let deposits = self.deposits.get(&account).copied().unwrap_or(0);
+        if deposits == 0 {
+            return 0;
+        }
+        let last = self.deposits.keys().all(|bidder| *bidder == account || self.withdrawn.contains(bidder));
+        if last {
+            return self.params.collateral - self.collateral_paid;
+        }
+        let total: Wei = self.deposits.values().sum();
+        self.params.collateral * deposits / total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::PrimeField64;
+    use silent_bid_verifier::public_input::Address;
+    use silent_bid_verifier::{AuctionPublicValues, AuctionResolution, VerifyError};
+    use crate::bid_proof::prove_bid;
+    use crate::fixtures::{auction_trace, demo_bids, private_input, MODULUS, PUBLIC_EXPONENT};
+    use crate::generate_execution_trace::generate_execution_trace;
+    use crate::prove::{prove_auction, prove_no_contest};
+    use super::{AuctionEvent, AuctionParams, AuctionPhase, AuctionSimulator, SimulatorError};
+
+    const OWNER: Address = [0x11; 20];
+    const DEPOSIT: u128 = 5000;
+    const COLLATERAL: u128 = 9000;
+
+    fn simulator() -> AuctionSimulator {
+        let params = AuctionParams {
+            owner: OWNER,
+            modulus: MODULUS as u64,
+            deposit: DEPOSIT,
+            bid_start: 100,
+            bid_end: 200,
+            proof_deadline: 300,
+            collateral: COLLATERAL,
+            public_exponent: None,
+            auction_id: 7,
+        };
+        AuctionSimulator::new(params, 0)
+    }
+
+    #[test]
+    fn runs_the_whole_lifecycle() {
+        let mut auction = simulator();
+        let bids = demo_bids();
+        assert!(matches!(
+            auction.submit_bid(bids[0].bidder, bids[0].encrypted_amount.clone(), DEPOSIT),
+            Err(SimulatorError::WrongPhase { actual: AuctionPhase::Created, .. })
+        ));
+
+        auction.advance_to(100);
+        assert!(matches!(
+            auction.submit_bid(bids[0].bidder, bids[0].encrypted_amount.clone(), DEPOSIT - 1),
+            Err(SimulatorError::WrongDeposit { .. })
+        ));
+        for bid in &bids {
+            auction.submit_bid(bid.bidder, bid.encrypted_amount.clone(), DEPOSIT).unwrap();
+        }
+        assert!(auction.submit_result(OWNER, [0; 20], 0, &[]).is_err());
+
+        auction.advance_to(200);
+        assert!(auction.submit_bid(bids[0].bidder, bids[0].encrypted_amount.clone(), DEPOSIT).is_err());
+
+        // the owner proves over the bids as the contract logged them
+        let logged: Vec<_> = auction.events().iter().filter_map(|event| match event {
+            AuctionEvent::BidSubmitted(bid) => Some(bid.clone()),
+            _ => None,
+        }).collect();
+        assert_eq!(logged, auction.bids());
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&logged, &private_input());
+        assert_eq!(hash_value.as_canonical_u64(), auction.hash_value());
+        let public_values = auction.public_values(winner, winner_amount.as_canonical_u64());
+        let proof = prove_auction(logged, trace, &public_values).unwrap().to_bytes();
+
+        assert!(matches!(auction.submit_result(bids[0].bidder, winner, 3035, &proof), Err(SimulatorError::NotOwner)));
+        assert!(matches!(
+            auction.submit_result(OWNER, winner, 3034, &proof),
+            Err(SimulatorError::ProofRejected(VerifyError::InvalidPublicValues(_)))
+        ));
+        assert!(matches!(auction.withdraw(OWNER), Err(SimulatorError::WrongPhase { .. })));
+
+        let outcome = auction.submit_result(OWNER, winner, 3035, &proof).unwrap();
+        assert_eq!(outcome.winner, bids[1].bidder);
+        assert_eq!(auction.phase(), AuctionPhase::Settled);
+        assert_eq!(auction.outcome(), Some(&outcome));
+        assert!(auction.submit_result(OWNER, winner, 3035, &proof).is_err());
+
+        // the winner placed two bids and pays the winning amount out of both deposits
+        assert_eq!(auction.withdraw(bids[0].bidder).unwrap(), DEPOSIT);
+        assert_eq!(auction.withdraw(winner).unwrap(), 2 * DEPOSIT - 3035);
+        assert_eq!(auction.withdraw(OWNER).unwrap(), 3035 + COLLATERAL);
+        assert!(matches!(auction.withdraw(winner), Err(SimulatorError::NothingToWithdraw)));
+    }
+
+    #[test]
+    fn rejects_a_proof_over_other_bids() {
+        let mut auction = simulator();
+        auction.advance_to(150);
+        let bids = demo_bids();
+        for bid in &bids[1..] {
+            auction.submit_bid(bid.bidder, bid.encrypted_amount.clone(), DEPOSIT).unwrap();
+        }
+        auction.advance_to(200);
+
+        // a proof over all demo bids commits to a hash the contract never accumulated
+        let (trace, public_values) = auction_trace(&bids);
+        let public_values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        let proof = prove_auction(bids, trace, &public_values).unwrap().to_bytes();
+        assert!(matches!(
+            auction.submit_result(OWNER, public_values.winner_address, public_values.winner_amount, &proof),
+            Err(SimulatorError::ProofRejected(VerifyError::InvalidPublicValues(_)))
+        ));
+        assert_eq!(auction.phase(), AuctionPhase::Opening);
+    }
+
+    #[test]
+    fn timeout_refunds_bidders_and_slashes_the_owner() {
+        let mut auction = simulator();
+        auction.advance_to(100);
+        let bids = demo_bids();
+        for bid in &bids {
+            auction.submit_bid(bid.bidder, bid.encrypted_amount.clone(), DEPOSIT).unwrap();
+        }
+        auction.advance_to(250);
+        assert!(auction.resolution().is_none());
+        assert!(matches!(auction.withdraw(bids[0].bidder), Err(SimulatorError::WrongPhase { .. })));
+
+        auction.advance_to(300);
+        assert_eq!(auction.resolution(), Some(AuctionResolution::TimedOut));
+        // a valid proof is too late now
+        let (trace, public_values) = auction_trace(&bids);
+        let public_values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        let proof = prove_auction(bids.clone(), trace, &public_values).unwrap().to_bytes();
+        assert!(matches!(
+            auction.submit_result(OWNER, public_values.winner_address, public_values.winner_amount, &proof),
+            Err(SimulatorError::WrongPhase { actual: AuctionPhase::TimedOut, .. })
+        ));
+
+        // one bid of three deposits gets a third of the collateral, the bidder of two bids the rest
+        assert_eq!(auction.withdraw(bids[0].bidder).unwrap(), DEPOSIT + COLLATERAL / 3);
+        assert_eq!(auction.withdraw(bids[1].bidder).unwrap(), 2 * DEPOSIT + 2 * COLLATERAL / 3);
+        assert!(matches!(auction.withdraw(OWNER), Err(SimulatorError::NothingToWithdraw)));
+    }
+
+    #[test]
+    fn timeout_pays_out_the_whole_collateral() {
+        let collateral = 1000;
+        let mut auction = AuctionSimulator::new(AuctionParams { collateral, ..simulator().params }, 100);
+        let bidders = [[0x22; 20], [0x33; 20], [0x44; 20]];
+        for bidder in bidders {
+            auction.submit_bid(bidder, vec![1, 0, 0, 0, 0, 0, 0, 0], DEPOSIT).unwrap();
+        }
+        auction.advance_to(300);
+        // a third of 1000 rounds down, the last bidder gets the remainder
+        let payouts: Vec<_> = bidders.iter().map(|bidder| auction.withdraw(*bidder).unwrap()).collect();
+        assert_eq!(payouts, [DEPOSIT + 333, DEPOSIT + 333, DEPOSIT + 334]);
+        assert_eq!(payouts.iter().sum::<u128>(), 3 * DEPOSIT + collateral);
+    }
+
+    #[test]
+    fn no_contest_proof_settles_without_a_winner() {
+        let mut auction = simulator();
+        auction.advance_to(100);
+        // a bid of amount 0 (nonce 1, and 1 encrypts to itself) cannot win; the zero limb pads it like the demo bids
+        let bidder = [0x22; 20];
+        auction.submit_bid(bidder, vec![1, 0, 0, 0, 0, 0, 0, 0], DEPOSIT).unwrap();
+        auction.advance_to(200);
+
+        let (trace, winner, _, hash_value) = generate_execution_trace(auction.bids(), &private_input());
+        assert_eq!(winner, [0; 20]);
+        assert_eq!(hash_value.as_canonical_u64(), auction.hash_value());
+        let proof = prove_no_contest(auction.bids().to_vec(), trace, MODULUS as u64, auction.hash_value()).unwrap().to_bytes();
+        let outcome = auction.submit_result(OWNER, [0; 20], 0, &proof).unwrap();
+        assert!(outcome.is_no_contest());
+        assert_eq!(auction.resolution(), Some(AuctionResolution::NoContest { hash_value: auction.hash_value() }));
+
+        assert_eq!(auction.withdraw(bidder).unwrap(), DEPOSIT);
+        assert_eq!(auction.withdraw(OWNER).unwrap(), COLLATERAL);
+    }
+
+    #[test]
+    fn bid_proofs_keep_invalid_bids_out() {
+        let mut auction = AuctionSimulator::new(AuctionParams { public_exponent: Some(PUBLIC_EXPONENT as u64), ..simulator().params }, 100);
+        let bidder = [0x22; 20];
+        let auction_id = auction.params.auction_id;
+        let (encrypted_amount, proof) = prove_bid(3035, 123, PUBLIC_EXPONENT, MODULUS, DEPOSIT as u64, bidder, auction_id).unwrap();
+        assert!(matches!(
+            auction.submit_bid(bidder, encrypted_amount.clone(), DEPOSIT),
+            Err(SimulatorError::BidProofRequired)
+        ));
+
+        // a bid of more than the deposit cannot be proven, and a proof does not carry over to another bid
+        assert!(prove_bid(DEPOSIT as u64 + 1, 0, PUBLIC_EXPONENT, MODULUS, DEPOSIT as u64, bidder, auction_id).is_err());
+        let (other, _) = prove_bid(10, 1, PUBLIC_EXPONENT, MODULUS, DEPOSIT as u64, bidder, auction_id).unwrap();
+        assert!(matches!(
+            auction.submit_proven_bid(bidder, other, DEPOSIT, &proof.to_bytes()),
+            Err(SimulatorError::BidRejected(VerifyError::InvalidPublicValues(_)))
+        ));
+
+        // nor to another bidder copying the bid, or to the same bid in another auction
+        assert!(matches!(
+            auction.submit_proven_bid([0x33; 20], encrypted_amount.clone(), DEPOSIT, &proof.to_bytes()),
+            Err(SimulatorError::BidRejected(VerifyError::InvalidPublicValues(_)))
+        ));
+        let (_, other_auction) = prove_bid(3035, 123, PUBLIC_EXPONENT, MODULUS, DEPOSIT as u64, bidder, auction_id + 1).unwrap();
+        assert!(matches!(
+            auction.submit_proven_bid(bidder, encrypted_amount.clone(), DEPOSIT, &other_auction.to_bytes()),
+            Err(SimulatorError::BidRejected(VerifyError::InvalidPublicValues(_)))
+        ));
+
+        auction.submit_proven_bid(bidder, encrypted_amount, DEPOSIT, &proof.to_bytes()).unwrap();
+        assert_eq!(auction.bids().len(), 1);
+    }
+
+    #[test]
+    fn no_contest_cannot_hide_a_winner() {
+        let bids = demo_bids();
+        let (trace, public_values) = auction_trace(&bids);
+        let public_values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        assert!(prove_no_contest(bids, trace, public_values.modulus, public_values.hash_value).is_err());
+    }
+}
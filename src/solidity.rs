@@ -0,0 +1,572 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use p3_air::Air;
+use p3_field::PrimeField64;
+use p3_uni_stark::{get_log_quotient_degree, get_symbolic_constraints, Entry, SymbolicAirBuilder, SymbolicExpression};
+use silent_bid_verifier::air::ProverAir;
+use silent_bid_verifier::bid_proof::{BidProofAir, NUM_BID_PUBLIC_VALUES};
+use silent_bid_verifier::config::Val;
+use silent_bid_verifier::public_input::NUM_PUBLIC_VALUES;
+use silent_bid_verifier::utils::bytes_to_hex;
+
+/// One step of a `ConstraintProgram`. Operands are indices of earlier steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Step {
+    Local(usize),
+    Next(usize),
+    Public(usize),
+    IsFirstRow,
+    IsLastRow,
+    IsTransition,
+    Constant(u64),
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+    Neg(usize),
+}
+
+/// The constraints of an AIR as a straight-line program without repeated steps. `constraints` lists the steps that
+/// must vanish, in the order the AIR asserts them.
+#[derive(Clone, Debug)]
+pub struct ConstraintProgram {
+    pub steps: Vec<Step>,
+    pub constraints: Vec<usize>,
+    pub width: usize,
+    pub num_public_values: usize,
+    pub log_quotient_degree: usize,
+}
+
+#[derive(Default)]
+struct Lowering {
+    steps: Vec<Step>,
+    known: HashMap<Step, usize>,
+    /// Shared subexpressions already lowered, by address, so that reused `Rc`s are walked once.
+    lowered: HashMap<usize, usize>,
+}
+
+impl Lowering {
+    fn push(&mut self, step: Step) -> usize {
+        if let Some(index) = self.known.get(&step) {
+            return *index;
+        }
+        self.steps.push(step);
+        self.known.insert(step, self.steps.len() - 1);
+        self.steps.len() - 1
+    }
+
+    fn lower_shared<F: PrimeField64>(&mut self, expr: &Rc<SymbolicExpression<F>>) -> usize {
+        let address = Rc::as_ptr(expr) as usize;
+        if let Some(index) = self.lowered.get(&address) {
+            return *index;
+        }
+        let index = self.lower(expr);
+        self.lowered.insert(address, index);
+        index
+    }
+
+    fn lower<F: PrimeField64>(&mut self, expr: &SymbolicExpression<F>) -> usize {
+        let step = match expr {
+            SymbolicExpression::Variable(v) => match v.entry {
+                Entry::Main { offset: 0 } => Step::Local(v.index),
+                Entry::Main { offset: 1 } => Step::Next(v.index),
+                Entry::Public => Step::Public(v.index),
+                entry => panic!("{:?} variables are not supported", entry),
+            },
+            SymbolicExpression::IsFirstRow => Step::IsFirstRow,
+            SymbolicExpression::IsLastRow => Step::IsLastRow,
+            SymbolicExpression::IsTransition => Step::IsTransition,
+            SymbolicExpression::Constant(c) => Step::Constant(c.as_canonical_u64()),
+            SymbolicExpression::Add { x, y, .. } => Step::Add(self.lower_shared(x), self.lower_shared(y)),
+            SymbolicExpression::Sub { x, y, .. } => Step::Sub(self.lower_shared(x), self.lower_shared(y)),
+            SymbolicExpression::Mul { x, y, .. } => Step::Mul(self.lower_shared(x), self.lower_shared(y)),
+            SymbolicExpression::Neg { x, .. } => Step::Neg(self.lower_shared(x)),
+        };
+        self.push(step)
+    }
+}
+
+impl ConstraintProgram {
+    pub fn from_air<F, A>(air: &A, num_public_values: usize) -> Self
+    where
+        F: PrimeField64,
+        A: Air<SymbolicAirBuilder<F>>,
+    {
+        let mut lowering = Lowering::default();
+        let constraints = get_symbolic_constraints::<F, A>(air, 0, num_public_values)
+            .iter()
+            .map(|c| lowering.lower(c))
+            .collect();
+        Self {
+            steps: lowering.steps,
+            constraints,
+            width: air.width(),
+            num_public_values,
+            log_quotient_degree: get_log_quotient_degree::<F, A>(air, 0, num_public_values),
+        }
+    }
+}
+
+const TEMPLATE: &str = include_str!("solidity/ConstraintEvaluator.sol");
+
+/// Opcodes of the encoded program, matching the `OP_*` constants of the template.
+const OP_LOCAL: u8 = 0;
+const OP_NEXT: u8 = 1;
+const OP_PUBLIC: u8 = 2;
+const OP_IS_FIRST_ROW: u8 = 3;
+const OP_IS_LAST_ROW: u8 = 4;
+const OP_IS_TRANSITION: u8 = 5;
+const OP_CONSTANT: u8 = 6;
+const OP_ADD: u8 = 7;
+const OP_SUB: u8 = 8;
+const OP_MUL: u8 = 9;
+const OP_NEG: u8 = 10;
+
+impl ConstraintProgram {
+    /// The steps as read by the generated contract: an opcode byte per step followed by its operands, indices as
+    /// big-endian `u16` and constants as big-endian `u64`.
+    pub fn encode_steps(&self) -> Vec<u8> {
+        assert!(self.steps.len() <= u16::MAX as usize, "too many steps to encode");
+        let mut bytes = vec![];
+        for step in &self.steps {
+            let (op, operands): (u8, &[usize]) = match step {
+                Step::Local(c) => (OP_LOCAL, &[*c]),
+                Step::Next(c) => (OP_NEXT, &[*c]),
+                Step::Public(p) => (OP_PUBLIC, &[*p]),
+                Step::IsFirstRow => (OP_IS_FIRST_ROW, &[]),
+                Step::IsLastRow => (OP_IS_LAST_ROW, &[]),
+                Step::IsTransition => (OP_IS_TRANSITION, &[]),
+                Step::Constant(c) => {
+                    bytes.push(OP_CONSTANT);
+                    bytes.extend(c.to_be_bytes());
+                    continue;
+                }
+                Step::Add(x, y) => (OP_ADD, &[*x, *y]),
+                Step::Sub(x, y) => (OP_SUB, &[*x, *y]),
+                Step::Mul(x, y) => (OP_MUL, &[*x, *y]),
+                Step::Neg(x) => (OP_NEG, &[*x]),
+            };
+            bytes.push(op);
+            for operand in operands {
+                bytes.extend((*operand as u16).to_be_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// The constraint step indices as big-endian `u16`.
+    pub fn encode_constraints(&self) -> Vec<u8> {
+        self.constraints.iter().flat_map(|c| (*c as u16).to_be_bytes()).collect()
+    }
+}
+
+/// Renders a Solidity contract checking the constraints of `program` at the out-of-domain point. The program is
+/// embedded as bytes and interpreted, which keeps the contract under the EVM code size limit. The contract takes the
+/// openings and challenges on trust: it checks neither the transcript, nor the Merkle openings, nor FRI, so it is a
+/// building block of an on-chain verifier and not one.
+pub fn generate_constraint_evaluator(contract_name: &str, program: &ConstraintProgram) -> String {
+    TEMPLATE
+        .replace("{{CONTRACT_NAME}}", contract_name)
+        .replace("{{TRACE_WIDTH}}", &program.width.to_string())
+        .replace("{{NUM_PUBLIC_VALUES}}", &program.num_public_values.to_string())
+        .replace("{{LOG_QUOTIENT_DEGREE}}", &program.log_quotient_degree.to_string())
+        .replace("{{NUM_STEPS}}", &program.steps.len().to_string())
+        .replace("{{PROGRAM}}", &bytes_to_hex(&program.encode_steps()))
+        .replace("{{CONSTRAINTS}}", &bytes_to_hex(&program.encode_constraints()))
+}
+
+/// The constraint evaluator of the auction AIR of an owner decryption, the only one the calldata layout of
+/// `abi::encode_public_values` has room for.
+pub fn auction_constraint_evaluator() -> String {
+    let program = ConstraintProgram::from_air::<Val, _>(&ProverAir::new(vec![]), NUM_PUBLIC_VALUES);
+    generate_constraint_evaluator("AuctionConstraintEvaluator", &program)
+}
+
+/// The constraint evaluator of the bid proof AIR, which an on-chain verifier of the proofs sent with every bid
+/// builds on. Like the auction's, it takes the openings and challenges on trust.
+pub fn bid_constraint_evaluator() -> String {
+    let program = ConstraintProgram::from_air::<Val, _>(&BidProofAir, NUM_BID_PUBLIC_VALUES);
+    generate_constraint_evaluator("BidConstraintEvaluator", &program)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process::Command;
+    use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues};
+    use p3_field::{AbstractField, Field, PrimeField64};
+    use p3_goldilocks::Goldilocks;
+    use p3_matrix::dense::RowMajorMatrixView;
+    use p3_matrix::stack::VerticalPair;
+    use p3_matrix::Matrix;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use revm::db::{CacheDB, EmptyDB};
+    use revm::primitives::{keccak256, AccountInfo, Address, Bytecode, ExecutionResult, Output, TxKind, U256};
+    use revm::Evm;
+    use silent_bid_verifier::abi::encode_public_values;
+    use silent_bid_verifier::air::ProverAir;
+    use silent_bid_verifier::columns::NUM_BID_COLS;
+    use silent_bid_verifier::config::{EvmConfig, MyConfig, CONFIG_ID, EVM_CONFIG_ID};
+    use silent_bid_verifier::debug_builder::SectionBuilder;
+    use silent_bid_verifier::proof_file::{ProofFile, EVM_MAGIC, FORMAT_VERSION};
+    use silent_bid_verifier::public_input::NUM_PUBLIC_VALUES;
+    use silent_bid_verifier::utils::hex_to_bytes;
+    use silent_bid_verifier::{verify_auction, AuctionPublicValues};
+    use crate::fixtures::{auction_trace, demo_bids};
+    use crate::prove::prove_auction_evm;
+    use super::{auction_constraint_evaluator, ConstraintProgram, Step};
+
+    /// Row selectors at the point the constraints are evaluated on.
+    #[derive(Clone, Copy, Debug)]
+    struct Selectors<F> {
+        is_first_row: F,
+        is_last_row: F,
+        is_transition: F,
+    }
+
+    impl ConstraintProgram {
+        /// Folds the constraints with powers of `alpha` like the STARK verifier: `acc = acc * alpha + constraint`.
+        fn eval<F: Field>(&self, local: &[F], next: &[F], public_values: &[F], selectors: Selectors<F>, alpha: F) -> F {
+            let mut values: Vec<F> = Vec::with_capacity(self.steps.len());
+            for step in &self.steps {
+                let value = match *step {
+                    Step::Local(i) => local[i],
+                    Step::Next(i) => next[i],
+                    Step::Public(i) => public_values[i],
+                    Step::IsFirstRow => selectors.is_first_row,
+                    Step::IsLastRow => selectors.is_last_row,
+                    Step::IsTransition => selectors.is_transition,
+                    Step::Constant(c) => F::from_canonical_u64(c),
+                    Step::Add(x, y) => values[x] + values[y],
+                    Step::Sub(x, y) => values[x] - values[y],
+                    Step::Mul(x, y) => values[x] * values[y],
+                    Step::Neg(x) => -values[x],
+                };
+                values.push(value);
+            }
+            self.constraints.iter().fold(F::zero(), |acc, c| acc * alpha + values[*c])
+        }
+    }
+
+    /// Folds the constraints of an AIR on two concrete rows, as the STARK verifier does at the out-of-domain point.
+    struct FoldingBuilder<'a, F: Field> {
+        main: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+        public_values: &'a [F],
+        selectors: Selectors<F>,
+        alpha: F,
+        acc: F,
+    }
+
+    impl<'a, F: Field> AirBuilder for FoldingBuilder<'a, F> {
+        type F = F;
+        type Expr = F;
+        type Var = F;
+        type M = VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>;
+
+        fn main(&self) -> Self::M {
+            VerticalPair::new(self.main.top.clone(), self.main.bottom.clone())
+        }
+
+        fn is_first_row(&self) -> F {
+            self.selectors.is_first_row
+        }
+
+        fn is_last_row(&self) -> F {
+            self.selectors.is_last_row
+        }
+
+        fn is_transition_window(&self, _: usize) -> F {
+            self.selectors.is_transition
+        }
+
+        fn assert_zero<I: Into<F>>(&mut self, x: I) {
+            self.acc = self.acc * self.alpha + x.into();
+        }
+    }
+
+    impl<F: Field> AirBuilderWithPublicValues for FoldingBuilder<'_, F> {
+        type PublicVar = F;
+
+        fn public_values(&self) -> &[F] {
+            self.public_values
+        }
+    }
+
+    impl<F: Field> SectionBuilder for FoldingBuilder<'_, F> {}
+
+    fn fold_air(local: &[Goldilocks], next: &[Goldilocks], public_values: &[Goldilocks], selectors: Selectors<Goldilocks>, alpha: Goldilocks) -> Goldilocks {
+        let mut builder = FoldingBuilder {
+            main: VerticalPair::new(RowMajorMatrixView::new_row(local), RowMajorMatrixView::new_row(next)),
+            public_values,
+            selectors,
+            alpha,
+            acc: Goldilocks::zero(),
+        };
+        ProverAir::new(vec![]).eval(&mut builder);
+        builder.acc
+    }
+
+    fn program() -> ConstraintProgram {
+        ConstraintProgram::from_air::<Goldilocks, _>(&ProverAir::new(vec![]), NUM_PUBLIC_VALUES)
+    }
+
+    fn random_values(rng: &mut StdRng, len: usize) -> Vec<Goldilocks> {
+        (0..len).map(|_| Goldilocks::from_wrapped_u64(rng.gen())).collect()
+    }
+
+    fn random_selectors(rng: &mut StdRng) -> Selectors<Goldilocks> {
+        let values = random_values(rng, 3);
+        Selectors { is_first_row: values[0], is_last_row: values[1], is_transition: values[2] }
+    }
+
+    #[test]
+    fn program_matches_air_on_random_rows() {
+        let program = program();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let local = random_values(&mut rng, NUM_BID_COLS);
+            let next = random_values(&mut rng, NUM_BID_COLS);
+            let public_values = random_values(&mut rng, NUM_PUBLIC_VALUES);
+            let selectors = random_selectors(&mut rng);
+            let alpha = random_values(&mut rng, 1)[0];
+            assert_eq!(
+                program.eval(&local, &next, &public_values, selectors, alpha),
+                fold_air(&local, &next, &public_values, selectors, alpha),
+            );
+        }
+    }
+
+    #[test]
+    fn program_vanishes_on_the_demo_trace() {
+        let program = program();
+        let (trace, public_values) = auction_trace(&demo_bids());
+        let height = trace.height();
+        let alpha = Goldilocks::from_canonical_u64(0x1234_5678);
+        for row in 0..height {
+            let selectors = Selectors {
+                is_first_row: Goldilocks::from_bool(row == 0),
+                is_last_row: Goldilocks::from_bool(row == height - 1),
+                is_transition: Goldilocks::from_bool(row != height - 1),
+            };
+            let local = trace.row_slice(row).to_vec();
+            let next = trace.row_slice((row + 1) % height).to_vec();
+            assert_eq!(program.eval(&local, &next, &public_values, selectors, alpha), Goldilocks::zero(), "row {}", row);
+        }
+    }
+
+    /// Reads the encoded steps back the way the contract does.
+    fn decode_steps(bytes: &[u8]) -> Vec<Step> {
+        let read_u16 = |pos: usize| u16::from_be_bytes([bytes[pos], bytes[pos + 1]]) as usize;
+        let mut steps = vec![];
+        let mut pc = 0;
+        while pc < bytes.len() {
+            let op = bytes[pc];
+            pc += 1;
+            let (step, len) = match op {
+                0 => (Step::Local(read_u16(pc)), 2),
+                1 => (Step::Next(read_u16(pc)), 2),
+                2 => (Step::Public(read_u16(pc)), 2),
+                3 => (Step::IsFirstRow, 0),
+                4 => (Step::IsLastRow, 0),
+                5 => (Step::IsTransition, 0),
+                6 => (Step::Constant(u64::from_be_bytes(bytes[pc..pc + 8].try_into().unwrap())), 8),
+                7 => (Step::Add(read_u16(pc), read_u16(pc + 2)), 4),
+                8 => (Step::Sub(read_u16(pc), read_u16(pc + 2)), 4),
+                9 => (Step::Mul(read_u16(pc), read_u16(pc + 2)), 4),
+                10 => (Step::Neg(read_u16(pc)), 2),
+                op => panic!("unknown opcode {}", op),
+            };
+            steps.push(step);
+            pc += len;
+        }
+        steps
+    }
+
+    #[test]
+    fn encoded_program_decodes_to_its_steps() {
+        let program = program();
+        assert_eq!(decode_steps(&program.encode_steps()), program.steps);
+        let constraints: Vec<usize> = program.encode_constraints()
+            .chunks(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]) as usize)
+            .collect();
+        assert_eq!(constraints, program.constraints);
+    }
+
+    /// Compiles `source` with `solc` and returns the runtime code of `contract`.
+    fn compile(source: &str, contract: &str) -> Vec<u8> {
+        let dir = std::env::temp_dir().join(format!("silent-bid-solc-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}.sol", contract));
+        fs::write(&path, source).unwrap();
+        let output = Command::new(std::env::var("SOLC").unwrap_or_else(|_| "solc".to_string()))
+            .args(["--optimize", "--bin-runtime", "--overwrite", "-o"])
+            .arg(&dir)
+            .arg(&path)
+            .output()
+            .expect("cannot run solc");
+        assert!(output.status.success(), "solc failed\n{}", String::from_utf8_lossy(&output.stderr));
+        let code = fs::read_to_string(dir.join(format!("{}.bin-runtime", contract))).unwrap();
+        hex_to_bytes(code.trim()).unwrap()
+    }
+
+    /// Runs a call to `code` deployed in an empty in-memory EVM and returns its output.
+    fn call(code: &[u8], calldata: Vec<u8>) -> Result<Vec<u8>, String> {
+        let address = Address::repeat_byte(0x42);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(address, AccountInfo {
+            code: Some(Bytecode::new_raw(code.to_vec().into())),
+            ..Default::default()
+        });
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = Address::repeat_byte(0x01);
+                tx.transact_to = TxKind::Call(address);
+                tx.data = calldata.into();
+                tx.gas_limit = 30_000_000;
+            })
+            .build();
+        match evm.transact().map_err(|e| format!("{:?}", e))?.result {
+            ExecutionResult::Success { output: Output::Call(bytes), .. } => Ok(bytes.to_vec()),
+            result => Err(format!("{:?}", result)),
+        }
+    }
+
+    enum Arg<'a> {
+        Word(u64),
+        Array(&'a [Goldilocks]),
+    }
+
+    fn abi_call(signature: &str, args: &[Arg]) -> Vec<u8> {
+        let word = |v: u64| U256::from(v).to_be_bytes::<32>();
+        let mut head = keccak256(signature.as_bytes())[..4].to_vec();
+        let mut tail = vec![];
+        for arg in args {
+            match arg {
+                Arg::Word(v) => head.extend(word(*v)),
+                Arg::Array(values) => {
+                    head.extend(word((args.len() * 32 + tail.len()) as u64));
+                    tail.extend(word(values.len() as u64));
+                    for v in values.iter() {
+                        tail.extend(word(v.as_canonical_u64()));
+                    }
+                }
+            }
+        }
+        head.extend(tail);
+        head
+    }
+
+    fn two_adic_generator(bits: usize) -> Goldilocks {
+        (bits..32).fold(Goldilocks::from_canonical_u64(1753635133440165772), |g, _| g.square())
+    }
+
+    fn vanishing_at(degree_bits: usize, shift: Goldilocks, point: Goldilocks) -> Goldilocks {
+        (point * shift.inverse()).exp_u64(1 << degree_bits) - Goldilocks::one()
+    }
+
+    #[test]
+    #[ignore = "needs solc, from SOLC or the PATH; CI provides it"]
+    fn generated_contract_checks_a_real_proof() {
+        let program = program();
+        let code = compile(&auction_constraint_evaluator(), "AuctionConstraintEvaluator");
+        let bids = demo_bids();
+        let (trace, public_values) = auction_trace(&bids);
+        let abi_values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        let proof_file = prove_auction_evm(bids, trace.clone(), &abi_values).unwrap();
+        verify_auction(&proof_file.to_bytes(), &abi_values).unwrap();
+        let public_values: Vec<Goldilocks> = proof_file.public_values.iter().map(|v| Goldilocks::from_canonical_u64(*v)).collect();
+
+        // the calldata layout of the public values expands to the ones the proof is bound to
+        let calldata = [&keccak256(b"publicValuesFromAbi(uint256,uint256,uint256,uint256,address)")[..4], &encode_public_values(&abi_values).unwrap()].concat();
+        let output = call(&code, calldata).unwrap();
+        let expanded: Vec<u64> = output[64..].chunks(32).map(|word| U256::from_be_slice(word).to::<u64>()).collect();
+        assert_eq!(expanded, proof_file.public_values);
+
+        // every window of the proven trace satisfies the contract's constraints, and not with a lower winning amount
+        let height = trace.height();
+        let alpha = Goldilocks::from_canonical_u64(0x1234_5678);
+        let eval = |row: usize, public_values: &[Goldilocks]| {
+            let local = trace.row_slice(row).to_vec();
+            let next = trace.row_slice((row + 1) % height).to_vec();
+            let output = call(&code, abi_call(
+                "evalConstraints(uint256[],uint256[],uint256[],uint256,uint256,uint256,uint256)",
+                &[Arg::Array(&local), Arg::Array(&next), Arg::Array(public_values),
+                  Arg::Word((row == 0) as u64), Arg::Word((row == height - 1) as u64), Arg::Word((row != height - 1) as u64),
+                  Arg::Word(alpha.as_canonical_u64())],
+            )).unwrap();
+            let selectors = Selectors {
+                is_first_row: Goldilocks::from_bool(row == 0),
+                is_last_row: Goldilocks::from_bool(row == height - 1),
+                is_transition: Goldilocks::from_bool(row != height - 1),
+            };
+            let expected = program.eval(&local, &next, public_values, selectors, alpha);
+            assert_eq!(U256::from_be_slice(&output), U256::from(expected.as_canonical_u64()), "row {}", row);
+            expected
+        };
+        for row in 0..height {
+            assert_eq!(eval(row, &public_values), Goldilocks::zero(), "row {}", row);
+        }
+        let mut lower = public_values.clone();
+        lower[3] -= Goldilocks::one();
+        assert!((0..height).any(|row| eval(row, &lower) != Goldilocks::zero()));
+
+
+        // quotient chunks consistent with the folded constraints of two proven rows at a base field `zeta`
+        let mut rng = StdRng::seed_from_u64(1);
+        let local = trace.row_slice(0).to_vec();
+        let next = trace.row_slice(1).to_vec();
+        let degree_bits = height.trailing_zeros() as usize;
+        let zeta = random_values(&mut rng, 1)[0];
+        let z_h = zeta.exp_u64(1 << degree_bits) - Goldilocks::one();
+        let last_point = two_adic_generator(degree_bits).inverse();
+        let selectors = Selectors {
+            is_first_row: z_h / (zeta - Goldilocks::one()),
+            is_last_row: z_h / (zeta - last_point),
+            is_transition: zeta - last_point,
+        };
+        let quotient = program.eval(&local, &next, &public_values, selectors, alpha) / z_h;
+        let num_chunks = 1 << program.log_quotient_degree;
+        let chunk_generator = two_adic_generator(degree_bits + program.log_quotient_degree);
+        let shifts: Vec<Goldilocks> = (0..num_chunks)
+            .map(|i| Goldilocks::from_canonical_u64(7) * chunk_generator.exp_u64(i as u64))
+            .collect();
+        let zps: Vec<Goldilocks> = (0..num_chunks).map(|i| {
+            (0..num_chunks).filter(|j| *j != i)
+                .map(|j| vanishing_at(degree_bits, shifts[j], zeta) / vanishing_at(degree_bits, shifts[j], shifts[i]))
+                .product()
+        }).collect();
+        let mut chunks = random_values(&mut rng, num_chunks);
+        let rest: Goldilocks = (1..num_chunks).map(|i| zps[i] * chunks[i]).sum();
+        chunks[0] = (quotient - rest) / zps[0];
+        let mut openings: Vec<Goldilocks> = chunks.iter().flat_map(|c| [*c, Goldilocks::zero()]).collect();
+
+        let check = |openings: &[Goldilocks]| call(&code, abi_call(
+            "checkConstraintsAt(uint256,uint256,uint256,uint256[],uint256[],uint256[],uint256[])",
+            &[Arg::Word(degree_bits as u64), Arg::Word(zeta.as_canonical_u64()), Arg::Word(alpha.as_canonical_u64()),
+              Arg::Array(&local), Arg::Array(&next), Arg::Array(&public_values), Arg::Array(openings)],
+        )).unwrap();
+        assert_eq!(U256::from_be_slice(&check(&openings)), U256::from(1));
+        openings[0] += Goldilocks::one();
+        assert_eq!(U256::from_be_slice(&check(&openings)), U256::ZERO);
+    }
+
+    #[test]
+    fn evm_proofs_use_their_own_layout_and_verify() {
+        let (trace, public_values) = auction_trace(&demo_bids());
+        let public_values = AuctionPublicValues::from_field_elements(&public_values).unwrap();
+        let file = prove_auction_evm(demo_bids(), trace, &public_values).unwrap();
+        let bytes = file.to_bytes();
+        assert!(bytes.starts_with(&EVM_MAGIC));
+        assert_eq!(bytes[EVM_MAGIC.len()..EVM_MAGIC.len() + 4], FORMAT_VERSION.to_be_bytes());
+
+        let read = ProofFile::<EvmConfig>::from_bytes(&bytes, EVM_CONFIG_ID).unwrap();
+        assert_eq!(read.to_bytes(), bytes);
+        assert!(ProofFile::<MyConfig>::from_bytes(&bytes, CONFIG_ID).is_err());
+
+        let outcome = verify_auction(&bytes, &public_values).unwrap();
+        assert_eq!(outcome.winner_amount, 3035);
+        assert!(verify_auction(&bytes[..bytes.len() - 1], &public_values).is_err());
+    }
+}
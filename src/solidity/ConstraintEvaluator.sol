@@ -0,0 +1,297 @@
+// SPDX-License-Identifier: MIT
+// Generated by `cargo run -- --emit-solidity <path>` from the silent-bid auction AIR, do not edit.
+pragma solidity ^0.8.20;
+
+/// Constraint evaluator of a silent-bid STARK: given the trace and quotient openings at the out-of-domain point
+/// `zeta` and the constraint challenge `alpha`, checks that the folded constraints equal the quotient times the
+/// vanishing polynomial of the trace domain. Field elements are in the quadratic extension of Goldilocks
+/// (X^2 = 7), packed in a uint256 as `c0 | c1 << 64` with both coefficients canonical.
+///
+/// This is not a proof verifier. It trusts its arguments: it neither derives `zeta` and `alpha` from the Fiat-Shamir
+/// transcript nor checks the Merkle openings of the trace and quotient commitments nor the FRI low-degree test, so
+/// anyone can make `checkConstraintsAt` return true without a proof. Only a verifier that has checked all three may
+/// act on its answer.
+contract {{CONTRACT_NAME}} {
+    uint256 internal constant P = 0xFFFFFFFF00000001;
+    uint256 internal constant MASK = 0xFFFFFFFFFFFFFFFF;
+    uint256 internal constant W = 7;
+    /// Multiplicative generator of Goldilocks, the shift of the quotient domain.
+    uint256 internal constant GENERATOR = 7;
+    /// Generator of the subgroup of order 2^TWO_ADICITY.
+    uint256 internal constant TWO_ADIC_GENERATOR = 1753635133440165772;
+    uint256 internal constant TWO_ADICITY = 32;
+    uint256 internal constant ONE = 1;
+    /// The extension generator X.
+    uint256 internal constant X = 1 << 64;
+
+    uint256 public constant TRACE_WIDTH = {{TRACE_WIDTH}};
+    uint256 public constant NUM_PUBLIC_VALUES = {{NUM_PUBLIC_VALUES}};
+    uint256 public constant LOG_QUOTIENT_DEGREE = {{LOG_QUOTIENT_DEGREE}};
+
+    uint8 internal constant OP_LOCAL = 0;
+    uint8 internal constant OP_NEXT = 1;
+    uint8 internal constant OP_PUBLIC = 2;
+    uint8 internal constant OP_IS_FIRST_ROW = 3;
+    uint8 internal constant OP_IS_LAST_ROW = 4;
+    uint8 internal constant OP_IS_TRANSITION = 5;
+    uint8 internal constant OP_CONSTANT = 6;
+    uint8 internal constant OP_ADD = 7;
+    uint8 internal constant OP_SUB = 8;
+    uint8 internal constant OP_MUL = 9;
+    uint8 internal constant OP_NEG = 10;
+
+    uint256 internal constant NUM_STEPS = {{NUM_STEPS}};
+    /// The constraints of the AIR, one opcode per step followed by big-endian operands.
+    bytes internal constant PROGRAM = hex"{{PROGRAM}}";
+    /// Big-endian u16 indices of the steps that must vanish, in the order they are folded.
+    bytes internal constant CONSTRAINTS = hex"{{CONSTRAINTS}}";
+
+    struct Selectors {
+        uint256 isFirstRow;
+        uint256 isLastRow;
+        uint256 isTransition;
+    }
+
+    /// Folds the constraints at arbitrary row values, mostly for testing the generated program.
+    function evalConstraints(
+        uint256[] memory local,
+        uint256[] memory next,
+        uint256[] memory publicValues,
+        uint256 isFirstRow,
+        uint256 isLastRow,
+        uint256 isTransition,
+        uint256 alpha
+    ) public pure returns (uint256) {
+        checkOpenings(local, next, publicValues);
+        require(isCanonical(isFirstRow) && isCanonical(isLastRow) && isCanonical(isTransition), "non-canonical selector");
+        require(isCanonical(alpha), "non-canonical alpha");
+        return foldConstraints(local, next, publicValues, Selectors(isFirstRow, isLastRow, isTransition), alpha);
+    }
+
+    /// `quotientChunks` holds the 2 opened columns of each of the 2^LOG_QUOTIENT_DEGREE quotient chunks.
+    function checkConstraintsAt(
+        uint256 degreeBits,
+        uint256 zeta,
+        uint256 alpha,
+        uint256[] memory local,
+        uint256[] memory next,
+        uint256[] memory publicValues,
+        uint256[] memory quotientChunks
+    ) public pure returns (bool) {
+        checkOpenings(local, next, publicValues);
+        require(quotientChunks.length == 2 << LOG_QUOTIENT_DEGREE, "wrong number of quotient openings");
+        for (uint256 i = 0; i < quotientChunks.length; i++) {
+            require(isCanonical(quotientChunks[i]), "non-canonical quotient opening");
+        }
+        require(isCanonical(zeta) && isCanonical(alpha), "non-canonical challenge");
+        require(degreeBits + LOG_QUOTIENT_DEGREE <= TWO_ADICITY, "trace too large");
+
+        uint256 zH = esub(esquareN(zeta, degreeBits), ONE);
+        uint256 folded = foldConstraints(local, next, publicValues, selectorsAt(degreeBits, zeta, zH), alpha);
+        return folded == emul(quotientAt(degreeBits, zeta, quotientChunks), zH);
+    }
+
+    /// Expands the calldata layout of the public values (`abi::encode_public_values` in Rust) to the public values
+    /// of the AIR: the four numbers, then one element per byte of the winner address.
+    function publicValuesFromAbi(
+        uint256 modulus,
+        uint256 hashValue,
+        uint256 base,
+        uint256 winnerAmount,
+        address winner
+    ) public pure returns (uint256[] memory values) {
+        values = new uint256[](NUM_PUBLIC_VALUES);
+        values[0] = modulus;
+        values[1] = hashValue;
+        values[2] = base;
+        values[3] = winnerAmount;
+        bytes20 winnerBytes = bytes20(winner);
+        for (uint256 i = 0; i < 20; i++) {
+            values[4 + i] = uint8(winnerBytes[i]);
+        }
+    }
+
+    function checkOpenings(uint256[] memory local, uint256[] memory next, uint256[] memory publicValues) internal pure {
+        require(local.length == TRACE_WIDTH && next.length == TRACE_WIDTH, "wrong trace width");
+        require(publicValues.length == NUM_PUBLIC_VALUES, "wrong number of public values");
+        for (uint256 i = 0; i < TRACE_WIDTH; i++) {
+            require(isCanonical(local[i]) && isCanonical(next[i]), "non-canonical trace opening");
+        }
+        for (uint256 i = 0; i < NUM_PUBLIC_VALUES; i++) {
+            require(publicValues[i] < P, "non-canonical public value");
+        }
+    }
+
+    function selectorsAt(uint256 degreeBits, uint256 zeta, uint256 zH) internal pure returns (Selectors memory) {
+        uint256 lastPoint = baseInv(twoAdicGenerator(degreeBits));
+        return Selectors(
+            emul(zH, einv(esub(zeta, ONE))),
+            emul(zH, einv(esub(zeta, lastPoint))),
+            esub(zeta, lastPoint)
+        );
+    }
+
+    /// Recombines the quotient chunks, each living on the coset `GENERATOR * g^i` of the trace domain size.
+    function quotientAt(uint256 degreeBits, uint256 zeta, uint256[] memory quotientChunks) internal pure returns (uint256 quotient) {
+        uint256 numChunks = 1 << LOG_QUOTIENT_DEGREE;
+        uint256 chunkGenerator = twoAdicGenerator(degreeBits + LOG_QUOTIENT_DEGREE);
+        uint256[] memory shifts = new uint256[](numChunks);
+        shifts[0] = GENERATOR;
+        for (uint256 i = 1; i < numChunks; i++) {
+            shifts[i] = mulmod(shifts[i - 1], chunkGenerator, P);
+        }
+        for (uint256 i = 0; i < numChunks; i++) {
+            uint256 zps = ONE;
+            for (uint256 j = 0; j < numChunks; j++) {
+                if (j == i) {
+                    continue;
+                }
+                uint256 atZeta = vanishingAt(degreeBits, shifts[j], zeta);
+                uint256 atFirstPoint = vanishingAt(degreeBits, shifts[j], shifts[i]);
+                zps = emul(zps, emul(atZeta, einv(atFirstPoint)));
+            }
+            uint256 chunk = eadd(quotientChunks[2 * i], emul(quotientChunks[2 * i + 1], X));
+            quotient = eadd(quotient, emul(zps, chunk));
+        }
+    }
+
+    /// Vanishing polynomial of the coset `shift * <g>` of size 2^degreeBits at `point`.
+    function vanishingAt(uint256 degreeBits, uint256 shift, uint256 point) internal pure returns (uint256) {
+        return esub(esquareN(emul(point, baseInv(shift)), degreeBits), ONE);
+    }
+
+    function foldConstraints(
+        uint256[] memory local,
+        uint256[] memory next,
+        uint256[] memory publicValues,
+        Selectors memory sels,
+        uint256 alpha
+    ) internal pure returns (uint256 acc) {
+        bytes memory program = PROGRAM;
+        uint256[] memory t = new uint256[](NUM_STEPS);
+        uint256 pc = 0;
+        for (uint256 i = 0; i < NUM_STEPS; i++) {
+            uint8 op = uint8(program[pc]);
+            pc += 1;
+            uint256 value;
+            if (op == OP_LOCAL) {
+                value = local[readU16(program, pc)];
+                pc += 2;
+            } else if (op == OP_NEXT) {
+                value = next[readU16(program, pc)];
+                pc += 2;
+            } else if (op == OP_PUBLIC) {
+                value = publicValues[readU16(program, pc)];
+                pc += 2;
+            } else if (op == OP_IS_FIRST_ROW) {
+                value = sels.isFirstRow;
+            } else if (op == OP_IS_LAST_ROW) {
+                value = sels.isLastRow;
+            } else if (op == OP_IS_TRANSITION) {
+                value = sels.isTransition;
+            } else if (op == OP_CONSTANT) {
+                value = readU64(program, pc);
+                pc += 8;
+            } else if (op == OP_NEG) {
+                value = eneg(t[readU16(program, pc)]);
+                pc += 2;
+            } else {
+                value = binary(op, t[readU16(program, pc)], t[readU16(program, pc + 2)]);
+                pc += 4;
+            }
+            t[i] = value;
+        }
+
+        bytes memory constraints = CONSTRAINTS;
+        for (uint256 i = 0; i < constraints.length; i += 2) {
+            acc = eadd(emul(acc, alpha), t[readU16(constraints, i)]);
+        }
+    }
+
+    function binary(uint8 op, uint256 x, uint256 y) internal pure returns (uint256) {
+        if (op == OP_ADD) {
+            return eadd(x, y);
+        } else if (op == OP_SUB) {
+            return esub(x, y);
+        } else if (op == OP_MUL) {
+            return emul(x, y);
+        }
+        revert("unknown opcode");
+    }
+
+    function readU16(bytes memory data, uint256 pos) internal pure returns (uint256) {
+        return (uint256(uint8(data[pos])) << 8) | uint256(uint8(data[pos + 1]));
+    }
+
+    function readU64(bytes memory data, uint256 pos) internal pure returns (uint256 value) {
+        for (uint256 i = 0; i < 8; i++) {
+            value = (value << 8) | uint256(uint8(data[pos + i]));
+        }
+    }
+
+    function isCanonical(uint256 a) internal pure returns (bool) {
+        return a >> 128 == 0 && (a & MASK) < P && (a >> 64) < P;
+    }
+
+    function pack(uint256 c0, uint256 c1) internal pure returns (uint256) {
+        return c0 | (c1 << 64);
+    }
+
+    function eadd(uint256 a, uint256 b) internal pure returns (uint256) {
+        return pack(addmod(a & MASK, b & MASK, P), addmod(a >> 64, b >> 64, P));
+    }
+
+    function esub(uint256 a, uint256 b) internal pure returns (uint256) {
+        return pack(addmod(a & MASK, P - (b & MASK), P), addmod(a >> 64, P - (b >> 64), P));
+    }
+
+    function eneg(uint256 a) internal pure returns (uint256) {
+        return esub(0, a);
+    }
+
+    function emul(uint256 a, uint256 b) internal pure returns (uint256) {
+        uint256 a0 = a & MASK;
+        uint256 a1 = a >> 64;
+        uint256 b0 = b & MASK;
+        uint256 b1 = b >> 64;
+        uint256 c0 = addmod(mulmod(a0, b0, P), mulmod(W, mulmod(a1, b1, P), P), P);
+        uint256 c1 = addmod(mulmod(a0, b1, P), mulmod(a1, b0, P), P);
+        return pack(c0, c1);
+    }
+
+    function esquareN(uint256 a, uint256 n) internal pure returns (uint256) {
+        for (uint256 i = 0; i < n; i++) {
+            a = emul(a, a);
+        }
+        return a;
+    }
+
+    /// (c0 + c1 X)^-1 = (c0 - c1 X) / (c0^2 - W c1^2)
+    function einv(uint256 a) internal pure returns (uint256) {
+        uint256 a0 = a & MASK;
+        uint256 a1 = a >> 64;
+        uint256 norm = addmod(mulmod(a0, a0, P), P - mulmod(W, mulmod(a1, a1, P), P), P);
+        uint256 normInv = baseInv(norm);
+        return pack(mulmod(a0, normInv, P), mulmod((P - a1) % P, normInv, P));
+    }
+
+    function baseInv(uint256 a) internal pure returns (uint256 result) {
+        require(a != 0, "inverse of zero");
+        result = 1;
+        uint256 e = P - 2;
+        while (e > 0) {
+            if (e & 1 == 1) {
+                result = mulmod(result, a, P);
+            }
+            a = mulmod(a, a, P);
+            e >>= 1;
+        }
+    }
+
+    function twoAdicGenerator(uint256 bits) internal pure returns (uint256 g) {
+        g = TWO_ADIC_GENERATOR;
+        for (uint256 i = bits; i < TWO_ADICITY; i++) {
+            g = mulmod(g, g, P);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Threshold decryption: the auction key's private exponent is split among operators, so that no single one can
+//! decrypt a bid early.
+//!
+//! The exponent `d` is split into additive shares modulo `φ(n)`. Each operator raises every non-zero 4 encrypted
+//! bytes to its share and publishes a commitment to the results, its partial decryptions; the product of all
+//! operators' partials is the decryption. The prover combines them in the trace, and the AIR checks every operator's
+//! commitment and that the product re-encrypts into the encrypted bytes with the public exponent.
+
+use std::fs;
+use std::path::Path;
+use p3_field::{AbstractField, PrimeField64};
+use rand::Rng;
+use silent_bid_verifier::columns::MAX_OPERATORS;
+use silent_bid_verifier::config::Val;
+use silent_bid_verifier::poseidon2::{hash, share_input, DIGEST};
+use silent_bid_verifier::public_input::PublicBid;
+use crate::reference::{encrypted_chunks, pow_mod};
+
+/// Euler's totient of a 32-bit modulus, by trial division. Only the dealer splitting a key needs it.
+pub fn totient(modulus: u32) -> u64 {
+    let mut n = modulus as u64;
+    let mut phi = n;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            while n.is_multiple_of(p) {
+                n /= p;
+            }
+            phi -= phi / p;
+        }
+        p += 1;
+    }
+    if n > 1 {
+        phi -= phi / n;
+    }
+    phi
+}
+
+/// Splits `private_exponent` into `operators` random shares that add up to it modulo `φ(modulus)`.
+pub fn split_exponent(private_exponent: u32, modulus: u32, operators: usize, rng: &mut impl Rng) -> Vec<u64> {
+    assert!((1..=MAX_OPERATORS).contains(&operators), "threshold decryption supports 1 to {} operators", MAX_OPERATORS);
+    let phi = totient(modulus);
+    let mut shares: Vec<u64> = (1..operators).map(|_| rng.gen_range(0..phi)).collect();
+    let sum = shares.iter().fold(0, |sum, share| (sum + share) % phi);
+    shares.push((private_exponent as u64 % phi + phi - sum) % phi);
+    shares
+}
+
+/// One operator's partial decryptions of `bids`: every non-zero 4 encrypted bytes raised to its `share`, in trace
+/// order.
+pub fn partial_decryptions(share: u64, modulus: u32, bids: &[PublicBid]) -> Vec<u32> {
+    bids.iter()
+        .flat_map(encrypted_chunks)
+        .filter(|chunk| *chunk != 0)
+        .map(|chunk| pow_mod(chunk as u64, share, modulus as u64) as u32)
+        .collect()
+}
+
+/// Reads the modulus and the public exponent on the first line, then every operator's partial decryptions, one
+/// operator per line.
+pub fn read_partials(path: impl AsRef<Path>) -> Result<(u32, u32, Vec<Vec<u32>>), String> {
+    let text = fs::read_to_string(path.as_ref()).map_err(|e| format!("cannot read {}: {}", path.as_ref().display(), e))?;
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let mut key = lines.next().ok_or("no modulus")?.split_whitespace();
+    let modulus = key.next().ok_or("no modulus")?.parse().map_err(|_| "invalid modulus")?;
+    let public_exponent = key.next().ok_or("no public exponent")?.parse().map_err(|_| "invalid public exponent")?;
+    let partials = lines
+        .map(|line| line.split_whitespace().map(|p| p.parse().map_err(|_| format!("invalid partial decryption {}", p))).collect())
+        .collect::<Result<Vec<Vec<u32>>, String>>()?;
+    if !(1..=MAX_OPERATORS).contains(&partials.len()) || partials.iter().any(|p| p.len() != partials[0].len()) {
+        return Err(format!("expected 1 to {} operators with as many partial decryptions each", MAX_OPERATORS));
+    }
+    Ok((modulus, public_exponent, partials))
+}
+
+/// The public commitment to an operator's partial decryptions, the chain the AIR hashes them into: from the zero
+/// digest, the Poseidon2 hash of the chain and each partial, see `poseidon2::share_input`.
+pub fn share_commitment(partials: &[u32]) -> [u64; DIGEST] {
+    let chain = partials.iter().fold([Val::zero(); DIGEST], |chain, partial| {
+        hash(share_input(chain, Val::from_canonical_u32(*partial)))
+    });
+    chain.map(|x| x.as_canonical_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::{AbstractField, PrimeField64};
+    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::Matrix;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use silent_bid_verifier::air::ProverAir;
+    use silent_bid_verifier::columns::{bid_col, share_col, NUM_SHARE_COLS, PUBLIC_EXPONENT_BITS};
+    use silent_bid_verifier::config::Val;
+    use silent_bid_verifier::debug_builder::check_constraints;
+    use silent_bid_verifier::public_input::Decryption;
+    use silent_bid_verifier::utils::bytes_to_address;
+    use silent_bid_verifier::{verify_auction, AuctionPublicValues, VerifyError};
+    use crate::fixtures::{demo_bids, threshold_partials, MODULUS, PRIVATE_EXPONENT, PUBLIC_EXPONENT};
+    use crate::generate_execution_trace::generate_execution_trace;
+    use crate::private_input::PrivateInput;
+    use crate::prove::{prove_auction, prove_auction_evm};
+    use crate::time_lock::inverse_mod;
+    use super::{share_commitment, split_exponent};
+
+    /// The trace combining `partials` and the public values with the commitments of `committed`.
+    fn threshold_auction(partials: Vec<Vec<u32>>, committed: &[Vec<u32>]) -> (RowMajorMatrix<Val>, AuctionPublicValues) {
+        let private_input = PrivateInput::threshold(Val::from_canonical_u32(MODULUS), PUBLIC_EXPONENT, partials);
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&demo_bids(), &private_input);
+        let commitments = committed.iter().map(|partials| share_commitment(partials)).collect();
+        let public_values =
+            AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner)
+                .with_decryption(Decryption::Threshold { commitments, public_exponent: PUBLIC_EXPONENT as u64 });
+        (trace, public_values)
+    }
+
+    /// The sections of the constraints that `trace` fails, with their gadget.
+    fn failing_sections(trace: &RowMajorMatrix<Val>, public_values: &AuctionPublicValues) -> Vec<(&'static str, &'static str)> {
+        let air = ProverAir::for_auction(demo_bids(), public_values);
+        let report = check_constraints(&air, trace, &public_values.to_field_elements()).unwrap_err();
+        let mut sections: Vec<_> = report.failures.iter().map(|failure| (failure.gadget, failure.section)).collect();
+        sections.dedup();
+        sections
+    }
+
+    #[test]
+    fn combines_the_operators_partial_decryptions() {
+        let shares = split_exponent(PRIVATE_EXPONENT, MODULUS, 3, &mut StdRng::seed_from_u64(7));
+        assert!(shares.iter().all(|share| *share != PRIVATE_EXPONENT as u64));
+        let bids = demo_bids();
+        let partials = threshold_partials();
+        let (trace, public_values) = threshold_auction(partials.clone(), &partials);
+        let file = prove_auction(bids.clone(), trace.clone(), &public_values).unwrap();
+        let outcome = verify_auction(&file.to_bytes(), &public_values).unwrap();
+        assert_eq!(bytes_to_address(&outcome.winner), "0x95222290dd7278aa3ddd389cc1e1d165cc4bafdd");
+        assert_eq!(outcome.winner_amount, 3035);
+
+        // the proof is bound to every operator's commitment and to the public exponent
+        let mut other_commitment = public_values.clone();
+        let Decryption::Threshold { commitments, .. } = &mut other_commitment.decryption else { unreachable!() };
+        commitments[1][0] += 1;
+        assert!(matches!(verify_auction(&file.to_bytes(), &other_commitment), Err(VerifyError::InvalidPublicValues(_))));
+        assert!(prove_auction(bids.clone(), trace.clone(), &other_commitment).is_err());
+        let mut other_exponent = public_values.clone();
+        let Decryption::Threshold { public_exponent, .. } = &mut other_exponent.decryption else { unreachable!() };
+        *public_exponent += 2;
+        assert!(matches!(verify_auction(&file.to_bytes(), &other_exponent), Err(VerifyError::InvalidPublicValues(_))));
+        assert!(prove_auction_evm(bids, trace, &public_values).is_err());
+    }
+
+    #[test]
+    fn rejects_forged_partial_decryptions() {
+        let partials = threshold_partials();
+
+        // partials that still multiply into the decryption do not match their operators' commitments
+        let k = 2;
+        let mut compensated = partials.clone();
+        compensated[0][0] = ((compensated[0][0] as u64 * k) % MODULUS as u64) as u32;
+        let inverse = inverse_mod(k, MODULUS as u64).unwrap();
+        compensated[1][0] = ((compensated[1][0] as u64 * inverse) % MODULUS as u64) as u32;
+        let (trace, public_values) = threshold_auction(compensated, &partials);
+        assert_eq!(failing_sections(&trace, &public_values), [("shares", "share_commitment")]);
+        assert!(prove_auction(demo_bids(), trace, &public_values).is_err());
+
+        // partials their operators committed to but that do not decrypt the bid fail its re-encryption
+        let mut forged = partials;
+        forged[2][0] = forged[2][0] % (MODULUS - 1) + 1;
+        let (trace, public_values) = threshold_auction(forged.clone(), &forged);
+        assert_eq!(failing_sections(&trace, &public_values), [("shares", "reencrypt")]);
+        assert!(prove_auction(demo_bids(), trace, &public_values).is_err());
+    }
+
+    #[test]
+    fn an_error_holds_through_the_share_rows() {
+        let partials = threshold_partials();
+        let (mut trace, public_values) = threshold_auction(partials.clone(), &partials);
+
+        // an error raised on the last share row alone would leave the write row after it unchecked
+        let width = trace.width();
+        let is_share = |trace: &RowMajorMatrix<Val>, row: usize| trace.values[row * width + bid_col::IS_EXPONENT] == Val::one();
+        let last_share = (0..trace.height() - 1).find(|row| is_share(&trace, *row) && !is_share(&trace, row + 1)).unwrap();
+        trace.values[last_share * width + bid_col::IS_ERROR] = Val::one();
+        assert!(failing_sections(&trace, &public_values).contains(&("shares", "share_error")));
+    }
+
+    #[test]
+    fn a_decryption_beyond_the_modulus_fails_the_range_checks() {
+        let partials = threshold_partials();
+        let (mut trace, public_values) = threshold_auction(partials.clone(), &partials);
+        let width = trace.width();
+        let is_share = |trace: &RowMajorMatrix<Val>, row: usize| trace.values[row * width + bid_col::IS_EXPONENT] == Val::one();
+        let write = (1..trace.height()).find(|row| is_share(&trace, row - 1) && !is_share(&trace, *row)).unwrap();
+
+        // m + n re-encrypts like m: each multiply by it only adds the square to the quotient
+        let n = Val::from_canonical_u32(MODULUS);
+        let shares = write * width + width - NUM_SHARE_COLS;
+        trace.values[write * width + bid_col::CURRENT_VALUE] += n;
+        for i in 0..PUBLIC_EXPONENT_BITS {
+            if PUBLIC_EXPONENT >> (PUBLIC_EXPONENT_BITS - 1 - i) & 1 == 1 {
+                let square = trace.values[shares + share_col::SQUARES + i];
+                trace.values[shares + share_col::POWER_QUOTIENTS + i] += square;
+            }
+        }
+        let sections = failing_sections(&trace, &public_values);
+        assert!(sections.contains(&("shares", "share_bits")), "{:?}", sections);
+        assert!(!sections.contains(&("shares", "reencrypt")), "{:?}", sections);
+    }
+}
@@ -0,0 +1,168 @@
+//! Time-lock encryption: bids open after a fixed amount of sequential work rather than with an owner's key.
+//!
+//! A one-off setup picks a modulus `n`, a generator `g` and a number of squarings `T`, publishes them with the locked
+//! value `h = g^(2^T) mod n`, computed quickly from `φ(n)`, and forgets the factors of `n`. A bidder picks a random
+//! `r` and publishes the puzzle `g^r`, then every 16-bit limb times the mask `h^r`. After the deadline anyone finds
+//! the mask again by squaring the puzzle `T` times, which cannot be sped up without the factors, and the AIR checks
+//! the squarings and every limb. A puzzle whose mask is not invertible makes the bid invalid.
+//!
+//! This mode is not secure, it only shows the construction: the modulus is 31 bits and factors instantly, which
+//! gives the shortcut to every mask, and the AIR spends one row per squaring, so a `T` large enough to mean anything
+//! would not fit in a trace.
+
+use rand::Rng;
+use crate::reference::pow_mod;
+use crate::threshold::totient;
+
+/// Generator of the demo setup.
+pub const DEMO_GENERATOR: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeLock {
+    pub modulus: u32,
+    pub generator: u32,
+    pub squarings: u32,
+    /// `generator^(2^squarings) mod modulus`.
+    pub locked: u32,
+}
+
+impl TimeLock {
+    /// The setup: computes the locked value from the factors of the modulus, which must be forgotten afterwards.
+    /// The demo modulus is small enough to factor, so anyone can run it.
+    pub fn setup(modulus: u32, generator: u32, squarings: u32) -> Self {
+        let exponent = pow_mod(2, squarings as u64, totient(modulus));
+        let locked = pow_mod(generator as u64, exponent, modulus as u64) as u32;
+        Self { modulus, generator, squarings, locked }
+    }
+
+    /// Encrypts `value` as `limbs` 16-bit limbs, least significant first, after the puzzle. Zero limbs are zero
+    /// bytes, as with the owner scheme.
+    pub fn encrypt(&self, value: u64, limbs: usize, rng: &mut impl Rng) -> Vec<u8> {
+        let n = self.modulus as u64;
+        let r = rng.gen_range(1..n);
+        let mask = pow_mod(self.locked as u64, r, n);
+        let mut bytes = (pow_mod(self.generator as u64, r, n) as u32).to_le_bytes().to_vec();
+        for limb in 0..limbs {
+            let limb = (value >> (16 * limb)) & 0xffff;
+            bytes.extend(((limb * mask % n) as u32).to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// The inverse of `a` modulo `n`, `None` when they are not coprime.
+pub fn inverse_mod(a: u64, n: u64) -> Option<u64> {
+    let (mut r0, mut r1) = (n as i128, (a % n) as i128);
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    (r0 == 1).then(|| t0.rem_euclid(n as i128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::{AbstractField, PrimeField64};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use silent_bid_verifier::air::ProverAir;
+    use silent_bid_verifier::config::Val;
+    use silent_bid_verifier::debug_builder::check_constraints;
+    use silent_bid_verifier::public_input::{Decryption, PublicBid};
+    use silent_bid_verifier::utils::bytes_to_address;
+    use silent_bid_verifier::{verify_auction, AuctionPublicValues};
+    use crate::fixtures::{demo_bids, MODULUS};
+    use crate::generate_execution_trace::{generate_execution_trace, revealed_bids};
+    use crate::private_input::PrivateInput;
+    use crate::prove::prove_auction;
+    use crate::reference::encrypted_chunks;
+    use super::{inverse_mod, TimeLock, DEMO_GENERATOR};
+
+    const SQUARINGS: u32 = 100;
+
+    fn solve(time_lock: &TimeLock, puzzle: u32) -> u32 {
+        (0..time_lock.squarings).fold(puzzle as u64, |x, _| x * x % time_lock.modulus as u64) as u32
+    }
+
+    /// Decrypts a bid as the AIR does, `None` when a limb does not fit in 16 bits.
+    fn decrypt(time_lock: &TimeLock, bid: &PublicBid) -> Option<u64> {
+        let chunks = encrypted_chunks(bid);
+        let (puzzle, limbs) = chunks.split_first()?;
+        let inverse = inverse_mod(solve(time_lock, *puzzle) as u64, time_lock.modulus as u64)?;
+        let mut value = 0;
+        for (i, chunk) in limbs.iter().filter(|chunk| **chunk != 0).enumerate() {
+            let limb = *chunk as u64 * inverse % time_lock.modulus as u64;
+            if limb > u16::MAX as u64 {
+                return None;
+            }
+            value += limb << (16 * i);
+        }
+        Some(value)
+    }
+
+    #[test]
+    fn anyone_can_open_time_locked_bids() {
+        let time_lock = TimeLock::setup(MODULUS, DEMO_GENERATOR, SQUARINGS);
+        assert_eq!(solve(&time_lock, DEMO_GENERATOR), time_lock.locked);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let values = [2010 * 1000 + 123, 3035 * 1000 + 456, 3035 * 1000 + 789];
+        let bids: Vec<PublicBid> = demo_bids().into_iter().zip(values)
+            .map(|(bid, value)| PublicBid { bidder: bid.bidder, encrypted_amount: time_lock.encrypt(value, 4, &mut rng) })
+            .collect();
+        for (bid, value) in bids.iter().zip(values) {
+            assert_eq!(decrypt(&time_lock, bid), Some(value));
+        }
+
+        let private_input = PrivateInput::time_lock(Val::from_canonical_u32(MODULUS), SQUARINGS);
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&bids, &private_input);
+        let public_values =
+            AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner)
+                .with_decryption(Decryption::TimeLock(SQUARINGS as u64));
+        let file = prove_auction(bids.clone(), trace.clone(), &public_values).unwrap();
+        let outcome = verify_auction(&file.to_bytes(), &public_values).unwrap();
+        // the earliest of the tied bids wins
+        assert_eq!(bytes_to_address(&outcome.winner), "0x95222290dd7278aa3ddd389cc1e1d165cc4bafdd");
+        assert_eq!(outcome.winner_amount, 3035);
+
+        // the squarings are part of the statement
+        let fewer = public_values.clone().with_decryption(Decryption::TimeLock(SQUARINGS as u64 - 1));
+        assert!(prove_auction(bids, trace, &fewer).is_err());
+    }
+
+    #[test]
+    fn a_mask_sharing_a_factor_with_the_modulus_invalidates_the_bid() {
+        let time_lock = TimeLock::setup(MODULUS, DEMO_GENERATOR, SQUARINGS);
+        let mut rng = StdRng::seed_from_u64(5);
+        // 36383 divides the modulus, and so does every square of it
+        let factor = [36383u32.to_le_bytes(), 5u32.to_le_bytes(), 7u32.to_le_bytes()].concat();
+        let mut bids: Vec<PublicBid> = demo_bids().into_iter()
+            .map(|bid| PublicBid { bidder: bid.bidder, encrypted_amount: time_lock.encrypt(2010 * 1000 + 123, 4, &mut rng) })
+            .collect();
+        bids[0].encrypted_amount = factor;
+        assert_eq!(decrypt(&time_lock, &bids[0]), None);
+
+        let private_input = PrivateInput::time_lock(Val::from_canonical_u32(MODULUS), SQUARINGS);
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&bids, &private_input);
+        assert!(!revealed_bids(&trace)[0].is_valid);
+        assert_eq!(winner, bids[1].bidder);
+        let public_values =
+            AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner)
+                .with_decryption(Decryption::TimeLock(SQUARINGS as u64));
+        let file = prove_auction(bids, trace, &public_values).unwrap();
+        assert_eq!(verify_auction(&file.to_bytes(), &public_values).unwrap().winner_amount, 2010);
+    }
+
+    #[test]
+    #[should_panic(expected = "public values do not match the configured AIR")]
+    fn the_squarings_cannot_be_left_out() {
+        let private_input = PrivateInput::time_lock(Val::from_canonical_u32(MODULUS), SQUARINGS);
+        let (trace, winner, winner_amount, hash_value) = generate_execution_trace(&demo_bids(), &private_input);
+        let public_values =
+            AuctionPublicValues::new(MODULUS as u64, hash_value.as_canonical_u64(), winner_amount.as_canonical_u64(), winner);
+        let air = ProverAir::for_decryption(demo_bids(), &Decryption::TimeLock(SQUARINGS as u64));
+        let _ = check_constraints(&air, &trace, &public_values.to_field_elements());
+    }
+}
@@ -0,0 +1,143 @@
+use core::borrow::Borrow;
+use std::fs;
+use std::path::Path;
+use p3_field::PrimeField64;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use serde::{Deserialize, Serialize};
+use silent_bid_verifier::columns::{BidCols, Phase, NUM_BID_COLS};
+
+/// Columns written before the `BidCols` values. They are derived from the flags and ignored when reading.
+const TAG_COLUMNS: [&str; 3] = ["row", "bidder", "phase"];
+
+#[derive(Serialize, Deserialize)]
+struct TraceDump {
+    columns: Vec<String>,
+    rows: Vec<DumpRow>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DumpRow {
+    row: usize,
+    /// Index of the bidder in submission order, `None` on the padding rows.
+    bidder: Option<usize>,
+    phase: Phase,
+    values: Vec<u64>,
+}
+
+fn tag_rows<F: PrimeField64>(trace: &RowMajorMatrix<F>) -> Vec<DumpRow> {
+    let mut bidder: Option<usize> = None;
+    (0..trace.height())
+        .map(|row| {
+            let values = trace.row_slice(row);
+            let cols: &BidCols<F> = (*values).borrow();
+            // a dump holds the `BidCols` only, with no operator shares to multiply in
+            let phase = Phase::of(cols, false);
+            if phase == Phase::NewBidder {
+                bidder = Some(bidder.map_or(0, |b| b + 1));
+            }
+            DumpRow {
+                row,
+                bidder: if phase == Phase::Dummy { None } else { bidder },
+                phase,
+                values: values.iter().map(|v| v.as_canonical_u64()).collect(),
+            }
+        })
+        .collect()
+}
+
+fn parse_value<F: PrimeField64>(value: u64, row: usize) -> Result<F, String> {
+    if value >= F::ORDER_U64 {
+        return Err(format!("value {} on row {} is not a canonical field element", value, row));
+    }
+    Ok(F::from_canonical_u64(value))
+}
+
+fn check_columns(columns: &[String]) -> Result<(), String> {
+    let expected = BidCols::<u8>::column_names();
+    if columns != expected.as_slice() {
+        return Err(format!("columns do not match BidCols, expected {}", expected.join(",")));
+    }
+    Ok(())
+}
+
+pub fn trace_to_csv<F: PrimeField64>(trace: &RowMajorMatrix<F>) -> String {
+    let mut csv = TAG_COLUMNS.join(",");
+    for name in BidCols::<F>::column_names() {
+        csv.push(',');
+        csv.push_str(&name);
+    }
+    csv.push('\n');
+    for row in tag_rows(trace) {
+        let bidder = row.bidder.map_or(String::new(), |b| b.to_string());
+        csv.push_str(&format!("{},{},{}", row.row, bidder, row.phase.name()));
+        for value in row.values {
+            csv.push_str(&format!(",{}", value));
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+pub fn trace_from_csv<F: PrimeField64>(csv: &str) -> Result<RowMajorMatrix<F>, String> {
+    let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<String> = lines.next().ok_or("empty trace")?.split(',').map(|c| c.trim().to_string()).collect();
+    if header.len() < TAG_COLUMNS.len() || header[..TAG_COLUMNS.len()] != TAG_COLUMNS {
+        return Err(format!("header must start with {}", TAG_COLUMNS.join(",")));
+    }
+    check_columns(&header[TAG_COLUMNS.len()..])?;
+
+    let mut values = vec![];
+    for (row, line) in lines.enumerate() {
+        let cells: Vec<&str> = line.split(',').collect();
+        if cells.len() != TAG_COLUMNS.len() + NUM_BID_COLS {
+            return Err(format!("row {} has {} cells, expected {}", row, cells.len(), TAG_COLUMNS.len() + NUM_BID_COLS));
+        }
+        for cell in &cells[TAG_COLUMNS.len()..] {
+            let value = cell.trim().parse::<u64>().map_err(|_| format!("invalid value {:?} on row {}", cell, row))?;
+            values.push(parse_value(value, row)?);
+        }
+    }
+    Ok(RowMajorMatrix::new(values, NUM_BID_COLS))
+}
+
+pub fn trace_to_json<F: PrimeField64>(trace: &RowMajorMatrix<F>) -> String {
+    let dump = TraceDump {
+        columns: BidCols::<F>::column_names(),
+        rows: tag_rows(trace),
+    };
+    serde_json::to_string_pretty(&dump).expect("trace dump is serializable")
+}
+
+pub fn trace_from_json<F: PrimeField64>(json: &str) -> Result<RowMajorMatrix<F>, String> {
+    let dump: TraceDump = serde_json::from_str(json).map_err(|e| format!("invalid trace json: {}", e))?;
+    check_columns(&dump.columns)?;
+
+    let mut values = vec![];
+    for (row, dump_row) in dump.rows.iter().enumerate() {
+        if dump_row.values.len() != NUM_BID_COLS {
+            return Err(format!("row {} has {} values, expected {}", row, dump_row.values.len(), NUM_BID_COLS));
+        }
+        for value in &dump_row.values {
+            values.push(parse_value(*value, row)?);
+        }
+    }
+    Ok(RowMajorMatrix::new(values, NUM_BID_COLS))
+}
+
+/// Writes `trace` as JSON when `path` ends with `.json`, as CSV otherwise.
+pub fn write_trace<F: PrimeField64>(path: impl AsRef<Path>, trace: &RowMajorMatrix<F>) -> Result<(), String> {
+    let path = path.as_ref();
+    let content = if is_json(path) { trace_to_json(trace) } else { trace_to_csv(trace) };
+    fs::write(path, content).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+}
+
+pub fn read_trace<F: PrimeField64>(path: impl AsRef<Path>) -> Result<RowMajorMatrix<F>, String> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    if is_json(path) { trace_from_json(&content) } else { trace_from_csv(&content) }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
@@ -0,0 +1,816 @@
+//! Byte layout of proofs for on-chain verification, close to `abi.encodePacked` so that a Solidity verifier reads
+//! it with `calldataload` at fixed offsets:
+//!
+//! - `u8` is one byte, so a 32-byte digest is one `bytes32` and `Vec<u8>` is a length word followed by the bytes.
+//! - Every other integer is a big-endian `uint256` word; field elements are the word of their `u64` value.
+//! - `bool` and the tag of an `Option` are one byte, 0 or 1.
+//! - Sequences, maps and strings are a length word followed by their elements (key then value for maps, UTF-8
+//!   bytes for strings). Arrays, tuples and structs are their elements in order, without a length.
+//! - An enum is the word of its variant index followed by the variant's fields.
+//!
+//! Floats and `char` are not supported, and the layout is not self-describing: it decodes only into the type it
+//! was encoded from.
+//!
+//! The auction's public values and outcome use the standard Ethereum ABI instead (`abi.encode`), so that contracts
+//! take them as ordinary `uint256`, `address` and `bytes` parameters. Decoding is strict: every value has exactly
+//! one accepted encoding.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+use crate::columns::ADDRESS_BYTES;
+use crate::public_input::{Address, AuctionOutcome, AuctionPublicValues, AuctionStatistics, Decryption};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiError(pub String);
+
+impl fmt::Display for AbiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl ser::StdError for AbiError {}
+
+impl ser::Error for AbiError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        AbiError(msg.to_string())
+    }
+}
+
+impl de::Error for AbiError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        AbiError(msg.to_string())
+    }
+}
+
+pub fn to_abi_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>, AbiError> {
+    let mut encoder = Encoder { bytes: Vec::new() };
+    value.serialize(&mut encoder)?;
+    Ok(encoder.bytes)
+}
+
+/// Decodes `bytes` into `T`, rejecting trailing bytes.
+pub fn from_abi_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, AbiError> {
+    let mut decoder = Decoder { bytes };
+    let value = T::deserialize(&mut decoder)?;
+    if !decoder.bytes.is_empty() {
+        return Err(AbiError("trailing bytes after the encoded value".to_string()));
+    }
+    Ok(value)
+}
+
+/// The big-endian `uint256` word of `value`.
+pub fn word(value: u128) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// Length of `encode_public_values`: `(uint256 modulus, uint256 hashValue, uint256 base, uint256 winnerAmount,
+/// address winner)`.
+pub const PUBLIC_VALUES_ABI_LEN: usize = 5 * 32;
+/// Length of `encode_outcome`: `(address winner, uint256 winnerAmount, uint256 hashValue)`.
+pub const OUTCOME_ABI_LEN: usize = 3 * 32;
+
+/// The `address` word: 12 zero bytes then the address.
+pub fn address_word(address: &Address) -> [u8; 32] {
+    let mut word = [0; 32];
+    word[32 - ADDRESS_BYTES..].copy_from_slice(address);
+    word
+}
+
+/// Reads a `uint256` word holding a `u64`.
+pub fn read_u64(word: &[u8]) -> Result<u64, AbiError> {
+    if word.len() != 32 || word[..24].iter().any(|b| *b != 0) {
+        return Err(AbiError("uint256 does not fit in 64 bits".to_string()));
+    }
+    Ok(u64::from_be_bytes(word[24..].try_into().unwrap()))
+}
+
+/// Reads an `address` word, rejecting dirty upper bytes.
+pub fn read_address(word: &[u8]) -> Result<Address, AbiError> {
+    if word.len() != 32 || word[..32 - ADDRESS_BYTES].iter().any(|b| *b != 0) {
+        return Err(AbiError("address word has non-zero upper bytes".to_string()));
+    }
+    Ok(word[32 - ADDRESS_BYTES..].try_into().unwrap())
+}
+
+/// Encodes the public values of an owner decryption. The Solidity constraint evaluator does not support the other
+/// schemes, statistics, full reveal, receipts nor whitelists, and public values with any of them are rejected rather
+/// than encoded without it.
+pub fn encode_public_values(public_values: &AuctionPublicValues) -> Result<Vec<u8>, AbiError> {
+    let unsupported = [
+        (public_values.decryption != Decryption::Owner, "a decryption other than the owner's"),
+        (!public_values.statistics.is_empty(), "statistics"),
+        (public_values.reveal.is_some(), "a full reveal"),
+        (public_values.receipts.is_some(), "receipts"),
+        (public_values.whitelist.is_some(), "a whitelist"),
+    ];
+    if let Some((_, what)) = unsupported.iter().find(|(set, _)| *set) {
+        return Err(AbiError(alloc::format!("the calldata layout has no room for {}", what)));
+    }
+    let mut bytes = Vec::with_capacity(PUBLIC_VALUES_ABI_LEN);
+    for value in [public_values.modulus, public_values.hash_value, public_values.base, public_values.winner_amount] {
+        bytes.extend(word(value as u128));
+    }
+    bytes.extend(address_word(&public_values.winner_address));
+    Ok(bytes)
+}
+
+pub fn decode_public_values(bytes: &[u8]) -> Result<AuctionPublicValues, AbiError> {
+    if bytes.len() != PUBLIC_VALUES_ABI_LEN {
+        return Err(AbiError(alloc::format!("public values are {} bytes, expected {}", bytes.len(), PUBLIC_VALUES_ABI_LEN)));
+    }
+    let words: Vec<&[u8]> = bytes.chunks(32).collect();
+    Ok(AuctionPublicValues {
+        modulus: read_u64(words[0])?,
+        hash_value: read_u64(words[1])?,
+        base: read_u64(words[2])?,
+        winner_amount: read_u64(words[3])?,
+        winner_address: read_address(words[4])?,
+        decryption: Decryption::Owner,
+        statistics: AuctionStatistics::default(),
+        reveal: None,
+        receipts: None,
+        whitelist: None,
+    })
+}
+
+pub fn encode_outcome(outcome: &AuctionOutcome) -> Vec<u8> {
+    let mut bytes = address_word(&outcome.winner).to_vec();
+    bytes.extend(word(outcome.winner_amount as u128));
+    bytes.extend(word(outcome.hash_value as u128));
+    bytes
+}
+
+pub fn decode_outcome(bytes: &[u8]) -> Result<AuctionOutcome, AbiError> {
+    if bytes.len() != OUTCOME_ABI_LEN {
+        return Err(AbiError(alloc::format!("outcome is {} bytes, expected {}", bytes.len(), OUTCOME_ABI_LEN)));
+    }
+    Ok(AuctionOutcome {
+        winner: read_address(&bytes[..32])?,
+        winner_amount: read_u64(&bytes[32..64])?,
+        hash_value: read_u64(&bytes[64..])?,
+    })
+}
+
+/// The arguments of a verifier call, `abi.encode(modulus, hashValue, base, winnerAmount, winner, proof)` with the
+/// binary proof file as the trailing `bytes`.
+pub fn encode_verification_input(public_values: &AuctionPublicValues, proof: &[u8]) -> Result<Vec<u8>, AbiError> {
+    let mut bytes = encode_public_values(public_values)?;
+    bytes.extend(word((PUBLIC_VALUES_ABI_LEN + 32) as u128));
+    bytes.extend(word(proof.len() as u128));
+    bytes.extend(proof);
+    bytes.resize(bytes.len() + proof.len().next_multiple_of(32) - proof.len(), 0);
+    Ok(bytes)
+}
+
+pub fn decode_verification_input(bytes: &[u8]) -> Result<(AuctionPublicValues, Vec<u8>), AbiError> {
+    let head = PUBLIC_VALUES_ABI_LEN + 32;
+    if bytes.len() < head + 32 {
+        return Err(AbiError("verification input is truncated".to_string()));
+    }
+    let public_values = decode_public_values(&bytes[..PUBLIC_VALUES_ABI_LEN])?;
+    if read_u64(&bytes[PUBLIC_VALUES_ABI_LEN..head])? != head as u64 {
+        return Err(AbiError("proof does not directly follow the head".to_string()));
+    }
+    let len = read_u64(&bytes[head..head + 32])? as usize;
+    let data = &bytes[head + 32..];
+    if len > data.len() || data.len() != len.next_multiple_of(32) || data[len..].iter().any(|b| *b != 0) {
+        return Err(AbiError("proof bytes are not padded to a whole number of words".to_string()));
+    }
+    Ok((public_values, data[..len].to_vec()))
+}
+
+struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn word(&mut self, value: u128) {
+        self.bytes.extend(word(value));
+    }
+
+    fn signed(&mut self, value: i128) -> Result<(), AbiError> {
+        let value = u128::try_from(value).map_err(|_| AbiError("negative integers are not supported".to_string()))?;
+        self.word(value);
+        Ok(())
+    }
+
+    fn len(&mut self, len: Option<usize>) -> Result<(), AbiError> {
+        let len = len.ok_or_else(|| AbiError("sequences must have a known length".to_string()))?;
+        self.word(len as u128);
+        Ok(())
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T, AbiError> {
+    Err(AbiError(alloc::format!("{} is not supported", what)))
+}
+
+impl ser::Serializer for &mut Encoder {
+    type Ok = ();
+    type Error = AbiError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), AbiError> {
+        self.bytes.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), AbiError> {
+        self.signed(v as i128)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), AbiError> {
+        self.signed(v as i128)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), AbiError> {
+        self.signed(v as i128)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), AbiError> {
+        self.signed(v as i128)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), AbiError> {
+        self.signed(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), AbiError> {
+        self.bytes.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), AbiError> {
+        self.word(v as u128);
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), AbiError> {
+        self.word(v as u128);
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), AbiError> {
+        self.word(v as u128);
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), AbiError> {
+        self.word(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), AbiError> {
+        unsupported("f32")
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), AbiError> {
+        unsupported("f64")
+    }
+
+    fn serialize_char(self, _: char) -> Result<(), AbiError> {
+        unsupported("char")
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), AbiError> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), AbiError> {
+        self.word(v.len() as u128);
+        self.bytes.extend(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), AbiError> {
+        self.bytes.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), AbiError> {
+        self.bytes.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), AbiError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), AbiError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, index: u32, _: &'static str) -> Result<(), AbiError> {
+        self.serialize_u32(index)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T) -> Result<(), AbiError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        index: u32,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), AbiError> {
+        self.word(index as u128);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, AbiError> {
+        self.len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, AbiError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, AbiError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, index: u32, _: &'static str, _: usize) -> Result<Self, AbiError> {
+        self.word(index as u128);
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, AbiError> {
+        self.len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, AbiError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, index: u32, _: &'static str, _: usize) -> Result<Self, AbiError> {
+        self.word(index as u128);
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! impl_compound {
+    ($($trait:ident :: $method:ident),*) => {$(
+        impl ser::$trait for &mut Encoder {
+            type Ok = ();
+            type Error = AbiError;
+
+            fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), AbiError> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<(), AbiError> {
+                Ok(())
+            }
+        }
+    )*};
+}
+
+impl_compound!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+impl ser::SerializeStruct for &mut Encoder {
+    type Ok = ();
+    type Error = AbiError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _: &'static str, value: &T) -> Result<(), AbiError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), AbiError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Encoder {
+    type Ok = ();
+    type Error = AbiError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _: &'static str, value: &T) -> Result<(), AbiError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), AbiError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut Encoder {
+    type Ok = ();
+    type Error = AbiError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), AbiError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), AbiError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), AbiError> {
+        Ok(())
+    }
+}
+
+struct Decoder<'de> {
+    bytes: &'de [u8],
+}
+
+impl<'de> Decoder<'de> {
+    fn take(&mut self, len: usize) -> Result<&'de [u8], AbiError> {
+        if self.bytes.len() < len {
+            return Err(AbiError("unexpected end of input".to_string()));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, AbiError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn word(&mut self) -> Result<u128, AbiError> {
+        let word = self.take(32)?;
+        if word[..16].iter().any(|b| *b != 0) {
+            return Err(AbiError("word does not fit in 128 bits".to_string()));
+        }
+        Ok(u128::from_be_bytes(word[16..].try_into().unwrap()))
+    }
+
+    fn int<T: TryFrom<u128>>(&mut self) -> Result<T, AbiError> {
+        let value = self.word()?;
+        T::try_from(value).map_err(|_| AbiError(alloc::format!("{} is out of range", value)))
+    }
+
+    fn flag(&mut self) -> Result<bool, AbiError> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(AbiError(alloc::format!("invalid flag byte {}", b))),
+        }
+    }
+
+    /// A length word, bounded by the remaining input so that a corrupted length cannot make a huge allocation.
+    fn len(&mut self) -> Result<usize, AbiError> {
+        let len: usize = self.int()?;
+        if len > self.bytes.len() {
+            return Err(AbiError(alloc::format!("length {} exceeds the remaining input", len)));
+        }
+        Ok(len)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Decoder<'de> {
+    type Error = AbiError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, AbiError> {
+        unsupported("self-describing decoding")
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_bool(self.flag()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_i8(self.int()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_i16(self.int()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_i32(self.int()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_i64(self.int()?)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_i128(self.int()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_u8(self.byte()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_u16(self.int()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_u32(self.int()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_u64(self.int()?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_u128(self.word()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _: V) -> Result<V::Value, AbiError> {
+        unsupported("f32")
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _: V) -> Result<V::Value, AbiError> {
+        unsupported("f64")
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, _: V) -> Result<V::Value, AbiError> {
+        unsupported("char")
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        let s = core::str::from_utf8(bytes).map_err(|_| AbiError("string is not valid utf-8".to_string()))?;
+        visitor.visit_borrowed_str(s)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        let len = self.len()?;
+        visitor.visit_borrowed_bytes(self.take(len)?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        if self.flag()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        let len = self.len()?;
+        visitor.visit_seq(Elements { decoder: self, remaining: len })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, AbiError> {
+        visitor.visit_seq(Elements { decoder: self, remaining: len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _: &'static str, len: usize, visitor: V) -> Result<V::Value, AbiError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, AbiError> {
+        let len = self.len()?;
+        visitor.visit_map(Elements { decoder: self, remaining: len })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, AbiError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, AbiError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _: V) -> Result<V::Value, AbiError> {
+        unsupported("decoding identifiers")
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, AbiError> {
+        unsupported("skipping values")
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// The elements of a sequence, tuple or map with a known count.
+struct Elements<'a, 'de> {
+    decoder: &'a mut Decoder<'de>,
+    remaining: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = AbiError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, AbiError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.decoder).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> de::MapAccess<'de> for Elements<'_, 'de> {
+    type Error = AbiError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, AbiError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.decoder).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, AbiError> {
+        seed.deserialize(&mut *self.decoder)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Decoder<'de> {
+    type Error = AbiError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), AbiError> {
+        let index: u32 = self.int()?;
+        let value = seed.deserialize(IntoDeserializer::<AbiError>::into_deserializer(index))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Decoder<'de> {
+    type Error = AbiError;
+
+    fn unit_variant(self) -> Result<(), AbiError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, AbiError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, AbiError> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, AbiError> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use serde::{Deserialize, Serialize};
+    use crate::public_input::{AuctionOutcome, AuctionPublicValues, AuctionStatistics, Decryption};
+    use super::{
+        decode_outcome, decode_public_values, decode_verification_input, encode_outcome, encode_public_values,
+        encode_verification_input, from_abi_bytes, to_abi_bytes, word,
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        value: u64,
+        digest: [u8; 4],
+        bytes: Vec<u8>,
+        label: String,
+        flag: Option<u32>,
+    }
+
+    #[test]
+    fn follows_the_documented_layout() {
+        let sample = Sample { value: 7, digest: [1, 2, 3, 4], bytes: vec![9], label: "ab".into(), flag: Some(5) };
+        let bytes = to_abi_bytes(&sample).unwrap();
+        let expected: Vec<u8> = [
+            &word(7)[..], &[1, 2, 3, 4], &word(1), &[9], &word(2), b"ab", &[1], &word(5),
+        ].concat();
+        assert_eq!(bytes, expected);
+        assert_eq!(from_abi_bytes::<Sample>(&bytes).unwrap(), sample);
+    }
+
+    #[test]
+    fn rejects_truncated_and_trailing_bytes() {
+        let bytes = to_abi_bytes(&(1u64, vec![2u64, 3])).unwrap();
+        assert!(from_abi_bytes::<(u64, Vec<u64>)>(&bytes[..bytes.len() - 1]).is_err());
+        assert!(from_abi_bytes::<(u64, Vec<u64>)>(&[&bytes[..], &[0]].concat()).is_err());
+        // a length larger than the input is rejected before allocating
+        assert!(from_abi_bytes::<Vec<u8>>(&word(u64::MAX as u128)).is_err());
+    }
+
+    fn public_values() -> AuctionPublicValues {
+        let mut winner = [0; 20];
+        winner[0] = 0x95;
+        winner[19] = 0xdd;
+        AuctionPublicValues::new(1875143437, 612994769488612804, 3035, winner)
+    }
+
+    #[test]
+    fn public_values_use_static_abi_words() {
+        let public_values = public_values();
+        let bytes = encode_public_values(&public_values).unwrap();
+        assert_eq!(bytes.len(), 160);
+        assert_eq!(bytes[..32], word(1875143437));
+        assert_eq!(bytes[96..128], word(3035));
+        assert_eq!(bytes[128..140], [0; 12]);
+        assert_eq!(bytes[140..], public_values.winner_address);
+        assert_eq!(decode_public_values(&bytes).unwrap(), public_values);
+
+        let mut dirty = bytes.clone();
+        dirty[128] = 1;
+        assert!(decode_public_values(&dirty).is_err());
+        let mut too_large = bytes;
+        too_large[0] = 1;
+        assert!(decode_public_values(&too_large).is_err());
+    }
+
+    #[test]
+    fn public_values_beyond_the_layout_are_rejected() {
+        let digest = [1, 2, 3, 4];
+        let statistics = AuctionStatistics { valid_bids: Some(2), ..AuctionStatistics::default() };
+        for extended in [
+            public_values().with_decryption(Decryption::TimeLock(10)),
+            public_values().with_statistics(statistics),
+            public_values().with_reveal(digest),
+            public_values().with_receipts(digest),
+            public_values().with_whitelist(digest),
+        ] {
+            assert!(encode_public_values(&extended).is_err());
+            assert!(encode_verification_input(&extended, &[7]).is_err());
+        }
+    }
+
+    #[test]
+    fn outcome_round_trips() {
+        let outcome: AuctionOutcome = public_values().outcome();
+        let bytes = encode_outcome(&outcome);
+        assert_eq!(bytes[12..32], outcome.winner);
+        assert_eq!(decode_outcome(&bytes).unwrap(), outcome);
+    }
+
+    #[test]
+    fn verification_input_pads_the_proof() {
+        let proof = vec![7; 33];
+        let bytes = encode_verification_input(&public_values(), &proof).unwrap();
+        assert_eq!(bytes.len(), 160 + 32 + 32 + 64);
+        assert_eq!(bytes[160..192], word(192));
+        assert_eq!(decode_verification_input(&bytes).unwrap(), (public_values(), proof));
+
+        let mut dirty = bytes.clone();
+        *dirty.last_mut().unwrap() = 1;
+        assert!(decode_verification_input(&dirty).is_err());
+        assert!(decode_verification_input(&bytes[..bytes.len() - 32]).is_err());
+    }
+}
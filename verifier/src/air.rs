@@ -0,0 +1,194 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use p3_air::{Air, BaseAir};
+use p3_field::Field;
+use crate::columns::{
+    column_name, ADDRESS_BYTES, NUM_BID_COLS, NUM_MEMBERSHIP_COLS, NUM_RECEIPT_COLS, NUM_REVEAL_COLS, NUM_SHARE_COLS,
+    NUM_STATISTICS_COLS, NUM_TIME_LOCK_COLS,
+};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::{
+    AuctionRule, BidReveal, BidStatistics, CommitmentGadget, DecryptionGadget, Gadget, HighestBidRule, MerkleWhitelist,
+    OperatorShares, ReceiptTree, RollingHashCommitment, RsaDecryption, TimeLockPuzzle,
+};
+use crate::public_input::{AuctionPublicValues, Decryption, PublicBid, NUM_PUBLIC_VALUES};
+
+pub struct ProverAir<D = RsaDecryption, C = RollingHashCommitment, R = HighestBidRule> {
+    pub(crate) public_input: Vec<PublicBid>,
+    pub(crate) decryption: D,
+    pub(crate) commitment: C,
+    pub(crate) rule: R,
+    /// Set when only the bids of the addresses of a whitelist are valid, see `MerkleWhitelist`.
+    pub(crate) whitelist: Option<MerkleWhitelist>,
+    /// Set in full reveal mode, see `BidReveal`, and when bidders get receipts, see `ReceiptTree`.
+    pub(crate) reveal: Option<BidReveal>,
+    pub(crate) receipts: Option<ReceiptTree>,
+    /// Set when the auction publishes statistics, see `BidStatistics`.
+    pub(crate) statistics: Option<BidStatistics>,
+    /// Set in time-lock decryption, see `TimeLockPuzzle`, and in threshold decryption, see `OperatorShares`.
+    pub(crate) time_lock: Option<TimeLockPuzzle>,
+    pub(crate) shares: Option<OperatorShares>,
+    /// The public values of the decryption and the extensions follow the auction's, whose gadgets index them.
+    pub(crate) num_public_values: usize,
+}
+
+impl ProverAir {
+    pub fn new(public_input: Vec<PublicBid>) -> Self {
+        Self::for_decryption(public_input, &Decryption::Owner)
+    }
+
+    /// The AIR of auctions whose bids are decrypted with `decryption`, see `RsaDecryption`, with `TimeLockPuzzle` in
+    /// time-lock decryption and `OperatorShares` in threshold decryption.
+    pub fn for_decryption(public_input: Vec<PublicBid>, decryption: &Decryption) -> Self {
+        let public_values = AuctionPublicValues::new(0, 0, 0, [0; ADDRESS_BYTES]).with_decryption(decryption.clone());
+        Self::for_auction(public_input, &public_values)
+    }
+
+    /// The AIR a proof for `public_values` is checked with: that of its decryption and statistics, with a whitelist
+    /// and the reveal or receipts of every bid if it has them.
+    pub fn for_auction(public_input: Vec<PublicBid>, public_values: &AuctionPublicValues) -> Self {
+        let rsa = match &public_values.decryption {
+            Decryption::Owner => RsaDecryption::default(),
+            Decryption::Threshold { commitments, .. } => RsaDecryption::threshold(commitments.len()),
+            Decryption::TimeLock(_) => RsaDecryption::time_lock(),
+        };
+        let mut air = Self::with_gadgets(public_input, rsa, RollingHashCommitment, HighestBidRule);
+        if public_values.whitelist.is_some() {
+            air.decryption.restricted = true;
+            air.whitelist = Some(MerkleWhitelist);
+        }
+        if public_values.reveal.is_some() {
+            air.reveal = Some(BidReveal { offset: air.reveal_offset(), index: public_values.reveal_index() });
+        }
+        if public_values.receipts.is_some() {
+            air.receipts = Some(ReceiptTree { offset: air.receipts_offset(), index: public_values.receipts_index() });
+        }
+        if public_values.statistics.switched_on().contains(&true) {
+            air.statistics = Some(BidStatistics::for_auction(air.statistics_offset(), public_values));
+        }
+        // the decryption columns come after all the others
+        let index = public_values.decryption_index();
+        match &public_values.decryption {
+            Decryption::Owner => {}
+            Decryption::Threshold { commitments, .. } => {
+                air.shares = Some(OperatorShares { offset: air.decryption_offset(), operators: commitments.len(), index });
+            }
+            Decryption::TimeLock(_) => air.time_lock = Some(TimeLockPuzzle { offset: air.decryption_offset(), index }),
+        }
+        air.num_public_values = public_values.to_canonical().len();
+        air
+    }
+}
+
+impl<D, C, R> ProverAir<D, C, R> {
+    pub fn with_gadgets(public_input: Vec<PublicBid>, decryption: D, commitment: C, rule: R) -> Self {
+        Self {
+            public_input,
+            decryption,
+            commitment,
+            rule,
+            whitelist: None,
+            reveal: None,
+            receipts: None,
+            statistics: None,
+            time_lock: None,
+            shares: None,
+            num_public_values: NUM_PUBLIC_VALUES,
+        }
+    }
+
+    /// The first column after the `BidCols` and the `MembershipCols`, those of the full reveal.
+    fn reveal_offset(&self) -> usize {
+        match self.whitelist {
+            Some(_) => NUM_BID_COLS + NUM_MEMBERSHIP_COLS,
+            None => NUM_BID_COLS,
+        }
+    }
+
+    /// The first column after those of the full reveal, those of the receipts.
+    fn receipts_offset(&self) -> usize {
+        match self.reveal {
+            Some(_) => self.reveal_offset() + NUM_REVEAL_COLS,
+            None => self.reveal_offset(),
+        }
+    }
+
+    /// The first column after those of the receipts, those of the statistics.
+    fn statistics_offset(&self) -> usize {
+        match self.receipts {
+            Some(_) => self.receipts_offset() + NUM_RECEIPT_COLS,
+            None => self.receipts_offset(),
+        }
+    }
+
+    /// The first column after those of the statistics, those of the time-lock or the threshold decryption.
+    fn decryption_offset(&self) -> usize {
+        match self.statistics {
+            Some(_) => self.statistics_offset() + NUM_STATISTICS_COLS,
+            None => self.statistics_offset(),
+        }
+    }
+
+    /// Whether the decryption steps of the trace are share rows, see `Phase::Share`.
+    pub fn is_threshold(&self) -> bool {
+        self.shares.is_some()
+    }
+
+    /// Panics unless there are as many public values as the decryption and the extensions of the AIR take, which
+    /// their gadgets index directly.
+    pub(crate) fn assert_public_values(&self, len: usize) {
+        assert_eq!(len, self.num_public_values, "public values do not match the configured AIR");
+    }
+
+    /// Name of column `index` of the trace, prefixed with the gadget owning it past the `BidCols`.
+    pub fn column_name(&self, index: usize) -> String {
+        match (&self.whitelist, &self.reveal, &self.receipts) {
+            (Some(whitelist), _, _) if whitelist.columns().contains(&index) => whitelist.column_name(index),
+            (_, Some(reveal), _) if reveal.columns().contains(&index) => reveal.column_name(index),
+            (_, _, Some(receipts)) if receipts.columns().contains(&index) => receipts.column_name(index),
+            _ => match (&self.statistics, &self.time_lock, &self.shares) {
+                (Some(statistics), _, _) if statistics.columns().contains(&index) => statistics.column_name(index),
+                (_, Some(time_lock), _) if time_lock.columns().contains(&index) => time_lock.column_name(index),
+                (_, _, Some(shares)) if shares.columns().contains(&index) => shares.column_name(index),
+                _ => column_name(index),
+            },
+        }
+    }
+}
+
+impl<F: Field, D: DecryptionGadget, C: CommitmentGadget, R: AuctionRule> BaseAir<F> for ProverAir<D, C, R> {
+    fn width(&self) -> usize {
+        match (self.time_lock, self.shares) {
+            (Some(_), _) => self.decryption_offset() + NUM_TIME_LOCK_COLS,
+            (_, Some(_)) => self.decryption_offset() + NUM_SHARE_COLS,
+            _ => self.decryption_offset(),
+        }
+    }
+}
+
+impl<AB: SectionBuilder, D: DecryptionGadget, C: CommitmentGadget, R: AuctionRule> Air<AB> for ProverAir<D, C, R> {
+    fn eval(&self, builder: &mut AB) {
+        self.assert_public_values(builder.public_values().len());
+        self.decryption.eval(builder);
+        self.commitment.eval(builder);
+        self.rule.eval(builder);
+        if let Some(whitelist) = &self.whitelist {
+            whitelist.eval(builder);
+        }
+        if let Some(reveal) = &self.reveal {
+            reveal.eval(builder);
+        }
+        if let Some(receipts) = &self.receipts {
+            receipts.eval(builder);
+        }
+        if let Some(statistics) = &self.statistics {
+            statistics.eval(builder);
+        }
+        if let Some(time_lock) = &self.time_lock {
+            time_lock.eval(builder);
+        }
+        if let Some(shares) = &self.shares {
+            shares.eval(builder);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! A small AIR that a bidder proves locally before submitting, showing that its encrypted amount decrypts to a
+//! valid bid within its deposit, so that the contract can refuse invalid bids before they enter the auction.
+//!
+//! Each of the `BID_LIMBS` limbs takes one run of rows raising it to the public exponent by square-and-multiply,
+//! ending on its encrypted 4 bytes. A run takes at most `EXPONENT_BITS` steps: the exponent is a field element, and
+//! a longer run could take any exponent congruent to it modulo the field order, which encrypts to other 4 bytes.
+//! The limbs add up to the value the auction AIR decrypts, zero limbs taking no place, and the last row splits it
+//! into an amount no larger than the deposit and a nonce.
+//!
+//! The bidder and the auction are public values too. The AIR leaves them unconstrained, but the transcript absorbs
+//! every public value before drawing a challenge, so a proof only verifies for the bidder and the auction it was made
+//! for and cannot be replayed by another bidder or in another auction.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField64};
+use p3_matrix::Matrix;
+use p3_uni_stark::verify;
+use crate::columns::ADDRESS_BYTES;
+use crate::config::{make_evm_challenger, make_evm_config, EvmConfig, Val, EVM_CONFIG_ID};
+use crate::debug_builder::SectionBuilder;
+use crate::proof_file::ProofFile;
+use crate::public_input::Address;
+use crate::VerifyError;
+
+/// Encrypted 16-bit limbs of a provable bid, 4 bytes each.
+pub const BID_LIMBS: usize = 4;
+pub const LIMB_BITS: usize = 16;
+/// Bound of the square-and-multiply values and quotients, which keeps their products below the field order.
+pub const VALUE_BITS: usize = 32;
+/// Bound of the amount, so that `amount * NONCE_MODULUS + nonce` has a single decomposition in the field.
+pub const AMOUNT_BITS: usize = 54;
+pub const NONCE_MODULUS: u64 = 1000;
+const NONCE_BITS: usize = 10;
+/// Bound of the deposit minus the amount, and so of the deposit.
+pub const DEPOSIT_BITS: usize = 63;
+/// The modulus must be below `2^MODULUS_BITS` for the quotients to fit in `VALUE_BITS`.
+pub const MODULUS_BITS: u32 = 31;
+/// Bound of the public exponent, and so of the steps of a limb's run.
+pub const EXPONENT_BITS: usize = 32;
+/// Bits of `EXPONENT_BITS - steps`, which is at most `EXPONENT_BITS`.
+const STEPS_BITS: usize = 6;
+
+/// Number of public values: the modulus, the public exponent, the deposit, the `BID_LIMBS` encrypted limbs, one per
+/// byte of the bidder address and the auction id.
+pub const NUM_BID_PUBLIC_VALUES: usize = 3 + BID_LIMBS + ADDRESS_BYTES + 1;
+
+trace_columns! {
+    #[derive(Clone, Debug)]
+    pub struct BidProofCols<T> {
+        /// Exactly one of the flags is raised. A limb starts on `new_limb` and is raised to the exponent over the
+        /// `is_step` rows after it, `is_padding` rows follow the last limb.
+        pub new_limb: T,
+        pub is_step: T,
+        pub is_padding: T,
+        /// One-hot index of the encrypted limb the run ends on.
+        pub chunk: [T; BID_LIMBS],
+        pub limb: T,
+        pub limb_bits: [T; LIMB_BITS],
+        pub is_zero: T,
+        pub limb_inverse: T,
+        /// What is left of the exponent, and the bit `odd` taken off it by the step.
+        pub exponent: T,
+        pub odd: T,
+        /// Steps taken by the run so far, and the bits of `EXPONENT_BITS - steps`.
+        pub steps: T,
+        pub steps_slack_bits: [T; STEPS_BITS],
+        /// The limb squared once per step, and the product of the powers taken so far.
+        pub power: T,
+        pub r: T,
+        pub q_power: T,
+        pub q_r: T,
+        pub power_bits: [T; VALUE_BITS],
+        pub r_bits: [T; VALUE_BITS],
+        pub q_power_bits: [T; VALUE_BITS],
+        pub q_r_bits: [T; VALUE_BITS],
+        /// Place of the limb in the value, advanced by 16 bits after every non-zero limb.
+        pub gap: T,
+        pub value: T,
+        pub amount: T,
+        pub nonce: T,
+        pub amount_bits: [T; AMOUNT_BITS],
+        pub nonce_bits: [T; NONCE_BITS],
+        /// Bits of `NONCE_MODULUS - 1 - nonce`.
+        pub nonce_slack_bits: [T; NONCE_BITS],
+        /// Bits of the deposit minus the amount.
+        pub deposit_slack_bits: [T; DEPOSIT_BITS],
+    }
+    width = NUM_BID_PROOF_COLS;
+    indices = bid_proof_cols;
+}
+
+/// The public values of a bid proof, all of which the contract knows when the bid is submitted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BidPublicValues {
+    pub modulus: u64,
+    pub public_exponent: u64,
+    pub deposit: u64,
+    /// The bid as submitted, `BID_LIMBS` times 4 little-endian bytes.
+    pub encrypted_amount: Vec<u8>,
+    /// The sender of the bid, and the auction it is submitted to.
+    pub bidder: Address,
+    pub auction_id: u64,
+}
+
+impl BidPublicValues {
+    /// The encrypted limbs, or an error when the bid is not `BID_LIMBS` limbs each below the modulus. A limb equal
+    /// to a multiple of the modulus would decrypt to zero without being skipped like a zero limb.
+    pub fn chunks(&self) -> Result<[u64; BID_LIMBS], String> {
+        if self.encrypted_amount.len() != 4 * BID_LIMBS {
+            return Err(format!("encrypted amount is {} bytes, expected {}", self.encrypted_amount.len(), 4 * BID_LIMBS));
+        }
+        let mut chunks = [0; BID_LIMBS];
+        for (chunk, bytes) in chunks.iter_mut().zip(self.encrypted_amount.chunks(4)) {
+            *chunk = u32::from_le_bytes(bytes.try_into().unwrap()) as u64;
+            if *chunk >= self.modulus {
+                return Err(format!("encrypted limb {} is not below the modulus", chunk));
+            }
+        }
+        Ok(chunks)
+    }
+
+    /// Checks what the AIR relies on without constraining it: the bounds of the modulus, the public exponent and the
+    /// deposit, and the shape of the encrypted amount.
+    pub fn check(&self) -> Result<(), String> {
+        if self.modulus >= 1 << MODULUS_BITS {
+            return Err(format!("modulus {} is not below 2^{}", self.modulus, MODULUS_BITS));
+        }
+        if self.public_exponent >= 1 << EXPONENT_BITS {
+            return Err(format!("public exponent {} is not below 2^{}", self.public_exponent, EXPONENT_BITS));
+        }
+        if self.deposit >= 1 << DEPOSIT_BITS {
+            return Err(format!("deposit {} is not below 2^{}", self.deposit, DEPOSIT_BITS));
+        }
+        if self.auction_id >= Val::ORDER_U64 {
+            return Err(format!("auction id {} is not a field element", self.auction_id));
+        }
+        self.chunks().map(|_| ())
+    }
+
+    pub fn to_canonical(&self) -> Result<Vec<u64>, String> {
+        let bidder = self.bidder.map(u64::from);
+        Ok([&[self.modulus, self.public_exponent, self.deposit][..], &self.chunks()?[..], &bidder[..], &[self.auction_id][..]].concat())
+    }
+
+    pub fn to_field_elements<F: PrimeField64>(&self) -> Result<Vec<F>, String> {
+        Ok(self.to_canonical()?.into_iter().map(F::from_canonical_u64).collect())
+    }
+}
+
+/// The AIR of `BidProofCols`, with the public values of `BidPublicValues`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BidProofAir;
+
+impl<F: Field> BaseAir<F> for BidProofAir {
+    fn width(&self) -> usize {
+        NUM_BID_PROOF_COLS
+    }
+}
+
+impl<AB: SectionBuilder> Air<AB> for BidProofAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let public_values: Vec<AB::Expr> = builder.public_values().iter().map(|v| (*v).into()).collect();
+        let (modulus, exponent, deposit) = (public_values[0].clone(), public_values[1].clone(), public_values[2].clone());
+        let chunks = &public_values[3..3 + BID_LIMBS];
+        let local = main.row_slice(0);
+        let next = main.row_slice(1);
+        let local: &BidProofCols<AB::Var> = (*local).borrow();
+        let next: &BidProofCols<AB::Var> = (*next).borrow();
+        let one = AB::Expr::one();
+
+        builder.section("flags");
+        builder.assert_bool(local.new_limb);
+        builder.assert_bool(local.is_step);
+        builder.assert_bool(local.is_padding);
+        builder.assert_one(local.new_limb + local.is_step + local.is_padding);
+        builder.when_first_row().assert_one(local.new_limb);
+        builder.when_last_row().assert_one(local.is_padding);
+        builder.when_transition().when(local.is_padding).assert_one(next.is_padding);
+
+        builder.section("chunk");
+        let not_padding = one.clone() - local.is_padding;
+        for j in 0..BID_LIMBS {
+            builder.assert_bool(local.chunk[j]);
+        }
+        builder.when(not_padding.clone()).assert_one(sum::<AB>(&local.chunk));
+        builder.when_first_row().assert_one(local.chunk[0]);
+
+        // the limb is 16 bits, and zero exactly when `is_zero`
+        builder.section("limb");
+        builder.assert_eq(local.limb, from_bits::<AB>(&local.limb_bits));
+        builder.assert_bool(local.is_zero);
+        builder.assert_eq(local.limb * local.limb_inverse, one.clone() - local.is_zero);
+        builder.assert_zero(local.is_zero * local.limb);
+
+        builder.section("bits");
+        let bits = [&local.limb_bits[..], &local.power_bits, &local.r_bits, &local.q_power_bits, &local.q_r_bits, &local.amount_bits, &local.nonce_bits, &local.nonce_slack_bits,
+            &local.deposit_slack_bits, &local.steps_slack_bits];
+        for bit in bits.concat() {
+            builder.assert_bool(bit);
+        }
+
+        builder.section("range");
+        builder.assert_eq(local.power, from_bits::<AB>(&local.power_bits));
+        builder.assert_eq(local.r, from_bits::<AB>(&local.r_bits));
+        builder.assert_eq(local.q_power, from_bits::<AB>(&local.q_power_bits));
+        builder.assert_eq(local.q_r, from_bits::<AB>(&local.q_r_bits));
+
+        builder.section("start");
+        builder.when(local.new_limb).assert_eq(local.power, local.limb);
+        builder.when(local.new_limb).assert_one(local.r);
+        builder.when(local.new_limb).assert_eq(local.exponent, exponent);
+        builder.when(local.new_limb).assert_zero(local.steps);
+        builder.when_first_row().assert_one(local.gap);
+        builder.when_first_row().assert_eq(local.value, local.limb);
+
+        // one square-and-multiply step, the limb and its place are kept
+        builder.section("step");
+        builder.assert_bool(local.odd);
+        let mut step = builder.when_transition();
+        let mut step = step.when(next.is_step);
+        step.assert_eq(local.exponent, next.exponent * AB::Expr::two() + next.odd);
+        step.assert_eq(next.steps, local.steps + one.clone());
+        step.assert_eq(local.power * local.power, next.q_power * modulus.clone() + next.power);
+        let factor = next.odd * local.power + one.clone() - next.odd;
+        step.assert_eq(local.r * factor, next.q_r * modulus + next.r);
+        step.assert_eq(next.limb, local.limb);
+        step.assert_eq(next.is_zero, local.is_zero);
+        step.assert_eq(next.gap, local.gap);
+        step.assert_eq(next.value, local.value);
+        for j in 0..BID_LIMBS {
+            step.assert_eq(next.chunk[j], local.chunk[j]);
+        }
+
+        // the run of a limb ends with the whole exponent taken, within `EXPONENT_BITS` steps, on the encrypted limb
+        builder.section("end");
+        let end = not_padding.clone() * (one.clone() - next.is_step);
+        let mut end_builder = builder.when_transition();
+        let mut end_builder = end_builder.when(end);
+        end_builder.assert_zero(local.exponent);
+        end_builder.assert_eq(AB::Expr::from_canonical_usize(EXPONENT_BITS) - local.steps, from_bits::<AB>(&local.steps_slack_bits));
+        let chunk = (0..BID_LIMBS).map(|j| chunks[j].clone() * local.chunk[j]).fold(AB::Expr::zero(), |acc, c| acc + c);
+        end_builder.assert_eq(local.r, chunk);
+
+        // the next limb is the next encrypted limb, placed after the non-zero ones
+        builder.section("next_limb");
+        let mut next_limb = builder.when_transition();
+        let mut next_limb = next_limb.when(next.new_limb);
+        next_limb.assert_zero(next.chunk[0]);
+        for j in 1..BID_LIMBS {
+            next_limb.assert_eq(next.chunk[j], local.chunk[j - 1]);
+        }
+        let shift = local.is_zero + (one.clone() - local.is_zero) * AB::Expr::from_canonical_u64(1 << LIMB_BITS);
+        next_limb.assert_eq(next.gap, local.gap * shift);
+        next_limb.assert_eq(next.value, local.value + next.limb * next.gap);
+
+        builder.section("padding");
+        builder.when_transition().when(not_padding * next.is_padding).assert_one(local.chunk[BID_LIMBS - 1]);
+        builder.when_transition().when(next.is_padding).assert_eq(next.value, local.value);
+
+        // the value is an amount within the deposit and a nonce
+        builder.section("amount");
+        let mut last = builder.when_last_row();
+        last.assert_eq(local.value, local.amount * AB::Expr::from_canonical_u64(NONCE_MODULUS) + local.nonce);
+        last.assert_eq(local.amount, from_bits::<AB>(&local.amount_bits));
+        last.assert_eq(local.nonce, from_bits::<AB>(&local.nonce_bits));
+        last.assert_eq(AB::Expr::from_canonical_u64(NONCE_MODULUS - 1) - local.nonce, from_bits::<AB>(&local.nonce_slack_bits));
+        last.assert_eq(deposit - local.amount, from_bits::<AB>(&local.deposit_slack_bits));
+    }
+}
+
+/// Verifies a bid proof file of `EVM_CONFIG_ID`, the config the contract replays, against the public values of the
+/// submitted bid.
+pub fn verify_bid(proof_bytes: &[u8], public_values: &BidPublicValues) -> Result<(), VerifyError> {
+    public_values.check().map_err(VerifyError::InvalidPublicValues)?;
+    let proof_file = ProofFile::<EvmConfig>::from_bytes(proof_bytes, EVM_CONFIG_ID).map_err(VerifyError::InvalidProofFile)?;
+    let expected = public_values.to_canonical().map_err(VerifyError::InvalidPublicValues)?;
+    if proof_file.public_values != expected {
+        return Err(VerifyError::InvalidPublicValues("the proof was made for another bid".to_string()));
+    }
+    let public_values: Vec<Val> = public_values.to_field_elements().map_err(VerifyError::InvalidPublicValues)?;
+    verify(&make_evm_config(), &BidProofAir, &mut make_evm_challenger(), &proof_file.proof, &public_values)
+        .map_err(|e| VerifyError::InvalidProof(format!("{:?}", e)))
+}
+
+fn sum<AB: AirBuilder>(values: &[AB::Var]) -> AB::Expr {
+    values.iter().fold(AB::Expr::zero(), |acc, v| acc + *v)
+}
+
+/// The number of little-endian `bits`.
+pub(crate) fn from_bits<AB: AirBuilder>(bits: &[AB::Var]) -> AB::Expr {
+    bits.iter().rev().fold(AB::Expr::zero(), |acc, bit| acc * AB::Expr::two() + *bit)
+}
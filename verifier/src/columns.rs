@@ -0,0 +1,267 @@
+use alloc::string::String;
+use core::mem::offset_of;
+use core::ops::Range;
+use p3_field::{AbstractField, Field};
+use serde::{Deserialize, Serialize};
+use crate::bid_proof::VALUE_BITS;
+use crate::poseidon2::{DIGEST, FULL_ROUNDS, PARTIAL_ROUNDS, WIDTH};
+
+pub const READ_BYTES: usize = 4;
+pub const DECODED_BYTES: usize = 4;
+pub const ADDRESS_BYTES: usize = 20;
+pub const BASE: usize = 311;
+/// Most operators a threshold decryption can be split among.
+pub const MAX_OPERATORS: usize = 4;
+
+// column ranges owned by each gadget, the flags before `read_bytes` are shared by all of them
+pub const FLAG_COLS: Range<usize> = 0..offset_of!(BidCols<u8>, read_bytes);
+pub const DECRYPTION_COLS: Range<usize> = offset_of!(BidCols<u8>, read_bytes)..offset_of!(BidCols<u8>, read_address);
+pub const COMMITMENT_COLS: Range<usize> = offset_of!(BidCols<u8>, read_address)..offset_of!(BidCols<u8>, bid_amount);
+pub const RULE_COLS: Range<usize> = offset_of!(BidCols<u8>, bid_amount)..NUM_BID_COLS;
+
+trace_columns! {
+    #[derive(Clone, Debug)]
+    pub struct BidCols<T> {
+        pub is_dummy: T,
+        pub new_bidder: T,
+        pub is_reading: T,
+        pub is_exponent: T,
+        pub computing_winner: T,
+        pub read_bytes: [T; READ_BYTES],
+        pub current_value: T,
+        pub quotient_value: T,
+        pub exponent_value: T,
+        pub odd_exponent: T,
+        pub r: T,
+        pub q_r: T,
+        pub decoded_bytes: [T; DECODED_BYTES],
+        pub is_error: T,
+        pub gap: T,
+        pub final_value: T,
+        pub read_address: [T; ADDRESS_BYTES],
+        pub hash_lim: T,
+        pub hash_value: T,
+        // logic
+        pub bid_amount: T,
+        pub nonce: T,
+        pub winner_amount: T,
+        pub change_winner: T,
+        pub winner_address: [T; ADDRESS_BYTES],
+    }
+    width = NUM_BID_COLS;
+    indices = bid_col;
+}
+
+/// Levels of the whitelist Merkle tree, which holds up to `2^WHITELIST_DEPTH` ranges of addresses.
+pub const WHITELIST_DEPTH: usize = 16;
+/// An address as the whitelist compares it: a 160-bit big-endian number in big-endian limbs of 5 bytes.
+pub const ADDRESS_LIMBS: usize = 4;
+pub const ADDRESS_LIMB_BYTES: usize = ADDRESS_BYTES / ADDRESS_LIMBS;
+pub const ADDRESS_LIMB_BITS: usize = ADDRESS_LIMB_BYTES * 8;
+/// Columns of one Poseidon2 permutation: the state after every full round, then the S-box output of every partial
+/// round, see `poseidon2::Rounds`.
+pub const PERMUTATION_COLS: usize = FULL_ROUNDS * WIDTH + PARTIAL_ROUNDS;
+
+trace_columns! {
+    /// Columns after `BidCols` in auctions with a whitelist. Only the new bidder rows hash, the other rows of a bidder
+    /// carry its `is_member`. They still take up every row, see their cost in `document/proving_service.md`.
+    #[derive(Clone, Debug)]
+    pub struct MembershipCols<T> {
+        pub is_member: T,
+        /// The whitelist range holding the address, see `whitelist::WhitelistRange`, and the rounds of its hash.
+        pub low: [T; ADDRESS_LIMBS],
+        pub next: [T; ADDRESS_LIMBS],
+        pub leaf_rounds: [T; PERMUTATION_COLS],
+        /// For an address that is not listed, the bits of every limb of `address - low - 1` and of
+        /// `next - address - 1` and the borrows into their upper limbs, showing that it lies strictly inside the range.
+        pub above_bits: [T; ADDRESS_LIMBS * ADDRESS_LIMB_BITS],
+        pub above_borrows: [T; ADDRESS_LIMBS - 1],
+        pub below_bits: [T; ADDRESS_LIMBS * ADDRESS_LIMB_BITS],
+        pub below_borrows: [T; ADDRESS_LIMBS - 1],
+        /// Per level, bit `l` of the leaf index (the node is a right child), the sibling digest, the pair hashed in
+        /// order and the rounds of its hash.
+        pub path_bits: [T; WHITELIST_DEPTH],
+        pub siblings: [T; WHITELIST_DEPTH * DIGEST],
+        pub level_inputs: [T; WHITELIST_DEPTH * WIDTH],
+        pub level_rounds: [T; WHITELIST_DEPTH * PERMUTATION_COLS],
+    }
+    width = NUM_MEMBERSHIP_COLS;
+    indices = membership_col;
+}
+
+trace_columns! {
+    /// Columns after the `BidCols`, and after the `MembershipCols` if any, in auctions revealing every bid. Only the
+    /// computing winner rows hash, the other rows carry `reveal_hash`.
+    #[derive(Clone, Debug)]
+    pub struct RevealCols<T> {
+        /// The hash chain of the bids revealed up to the current row.
+        pub reveal_hash: [T; DIGEST],
+        /// The rounds of the hash of the current bid, then of the chain and that hash.
+        pub leaf_rounds: [T; PERMUTATION_COLS],
+        pub chain_rounds: [T; PERMUTATION_COLS],
+    }
+    width = NUM_REVEAL_COLS;
+    indices = reveal_col;
+}
+
+/// Most bits of the public exponent a threshold decryption is re-encrypted with.
+pub const PUBLIC_EXPONENT_BITS: usize = 17;
+
+trace_columns! {
+    /// Columns after all the others in threshold decryption. Only the share rows hash their partial decryption and
+    /// only the write rows after them re-encrypt, the other rows carry `share_hash`.
+    #[derive(Clone, Debug)]
+    pub struct ShareCols<T> {
+        /// On a share row, its partial decryption and the flag of its operator.
+        pub partial: T,
+        pub operator: [T; MAX_OPERATORS],
+        /// Per operator, the hash chain of its partial decryptions up to the current row.
+        pub share_hash: [T; MAX_OPERATORS * DIGEST],
+        /// The chain of the share row's operator before its partial, and the rounds of their hash.
+        pub share_input: [T; DIGEST],
+        pub share_rounds: [T; PERMUTATION_COLS],
+        /// The bits of the public exponent, the most significant first.
+        pub exponent_bits: [T; PUBLIC_EXPONENT_BITS],
+        /// Per bit, the square of the power so far, then that square times the decryption if the bit is set, each
+        /// modulo `n` with its quotient.
+        pub squares: [T; PUBLIC_EXPONENT_BITS],
+        pub square_quotients: [T; PUBLIC_EXPONENT_BITS],
+        pub powers: [T; PUBLIC_EXPONENT_BITS],
+        pub power_quotients: [T; PUBLIC_EXPONENT_BITS],
+        /// The quotient of the read 4 bytes modulo `n`, which the last power equals.
+        pub encrypted_quotient: T,
+        /// The `VALUE_BITS` little-endian bits of each of the values above, of `n`, and on the write rows of the
+        /// decryption and of `n - 1` minus it.
+        pub squares_bits: [T; PUBLIC_EXPONENT_BITS * VALUE_BITS],
+        pub square_quotients_bits: [T; PUBLIC_EXPONENT_BITS * VALUE_BITS],
+        pub powers_bits: [T; PUBLIC_EXPONENT_BITS * VALUE_BITS],
+        pub power_quotients_bits: [T; PUBLIC_EXPONENT_BITS * VALUE_BITS],
+        pub encrypted_quotient_bits: [T; VALUE_BITS],
+        pub modulus_bits: [T; VALUE_BITS],
+        pub decryption_bits: [T; VALUE_BITS],
+        pub decryption_slack_bits: [T; VALUE_BITS],
+    }
+    width = NUM_SHARE_COLS;
+    indices = share_col;
+}
+
+/// Levels of the receipts Merkle tree, which holds up to `2^RECEIPT_DEPTH` bids.
+pub const RECEIPT_DEPTH: usize = 16;
+
+trace_columns! {
+    /// Columns after the `RevealCols`, and after the others before them if any, in auctions issuing receipts. Only the
+    /// computing winner rows insert their bid in the tree, the other rows carry `root`, `count` and `frontier`.
+    #[derive(Clone, Debug)]
+    pub struct ReceiptCols<T> {
+        /// The root of the tree of the bids inserted up to the current row, and how many they are.
+        pub root: [T; DIGEST],
+        pub count: T,
+        /// Per level, the last node inserted as a left child, the sibling of the nodes inserted after it.
+        pub frontier: [T; RECEIPT_DEPTH * DIGEST],
+        /// The random value hashed in place of the nonce, and the rounds of the hash of the current bid.
+        pub salt: T,
+        pub leaf_rounds: [T; PERMUTATION_COLS],
+        /// Per level, bit `l` of the index of the current bid (the node is a right child), the pair hashed in order
+        /// and the rounds of its hash.
+        pub index_bits: [T; RECEIPT_DEPTH],
+        pub level_inputs: [T; RECEIPT_DEPTH * WIDTH],
+        pub level_rounds: [T; RECEIPT_DEPTH * PERMUTATION_COLS],
+    }
+    width = NUM_RECEIPT_COLS;
+    indices = receipt_col;
+}
+
+trace_columns! {
+    /// Columns after the `ReceiptCols`, and after the others before them if any, in auctions publishing statistics.
+    /// The computing winner rows count their bid, the other rows carry the counts.
+    #[derive(Clone, Debug)]
+    pub struct StatisticsCols<T> {
+        /// Every statistic of `AuctionStatistics`, counted up to the current bid.
+        pub valid_bids: T,
+        pub invalid_bids: T,
+        pub bid_sum: T,
+    }
+    width = NUM_STATISTICS_COLS;
+    indices = statistics_col;
+}
+
+trace_columns! {
+    /// Columns after the `StatisticsCols`, and after the others before them if any, in time-lock decryption.
+    #[derive(Clone, Debug)]
+    pub struct TimeLockCols<T> {
+        /// The solved puzzle of the current bid, which masks its other 4 bytes.
+        pub mask: T,
+    }
+    width = NUM_TIME_LOCK_COLS;
+    indices = time_lock_col;
+}
+
+/// Name of column `index` of the `BidCols`. The columns after them depend on the gadgets of the AIR, see
+/// `ProverAir::column_name`.
+pub fn column_name(index: usize) -> String {
+    BidCols::<u8>::column_name(index)
+}
+
+/// The kind of step a trace row records, given by its flag columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    NewBidder,
+    Reading,
+    Exponent,
+    /// Multiplies in one operator's partial decryption, in threshold mode. It raises `is_exponent`, every decryption
+    /// step of a threshold decryption is a share row.
+    Share,
+    /// Writes the decrypted value of the last 4 read bytes, no flag is raised.
+    Write,
+    ComputingWinner,
+    /// Padding up to a power of two, repeats the last row with `is_dummy` raised.
+    Dummy,
+}
+
+impl<T: AbstractField> BidCols<T> {
+    /// Sets the flag columns for `phase`. `Phase::Dummy` only raises `is_dummy`.
+    pub fn set_phase(&mut self, phase: Phase) -> &mut Self {
+        if phase == Phase::Dummy {
+            return self.set_is_dummy(T::one());
+        }
+        self.set_is_dummy(T::zero())
+            .set_new_bidder(T::from_bool(phase == Phase::NewBidder))
+            .set_is_reading(T::from_bool(phase == Phase::Reading))
+            .set_is_exponent(T::from_bool(phase == Phase::Exponent || phase == Phase::Share))
+            .set_computing_winner(T::from_bool(phase == Phase::ComputingWinner))
+    }
+}
+
+impl Phase {
+    /// The phase of `row`, whose decryption steps are share rows in a `threshold` decryption.
+    pub fn of<F: Field>(row: &BidCols<F>, threshold: bool) -> Self {
+        if row.is_dummy.is_one() {
+            Phase::Dummy
+        } else if row.new_bidder.is_one() {
+            Phase::NewBidder
+        } else if row.is_reading.is_one() {
+            Phase::Reading
+        } else if row.is_exponent.is_one() && threshold {
+            Phase::Share
+        } else if row.is_exponent.is_one() {
+            Phase::Exponent
+        } else if row.computing_winner.is_one() {
+            Phase::ComputingWinner
+        } else {
+            Phase::Write
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::NewBidder => "new_bidder",
+            Phase::Reading => "reading",
+            Phase::Exponent => "exponent",
+            Phase::Share => "share",
+            Phase::Write => "write",
+            Phase::ComputingWinner => "computing_winner",
+            Phase::Dummy => "dummy",
+        }
+    }
+}
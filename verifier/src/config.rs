@@ -0,0 +1,70 @@
+use alloc::vec;
+use p3_challenger::{HashChallenger, SerializingChallenger64};
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_goldilocks::Goldilocks;
+use p3_keccak::Keccak256Hash;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher64};
+use p3_uni_stark::StarkConfig;
+use crate::transcript::EvmChallenger;
+
+pub type Val = Goldilocks;
+pub type Challenge = BinomialExtensionField<Val, 2>;
+
+pub type ByteHash = Keccak256Hash;
+pub type FieldHash = SerializingHasher64<ByteHash>;
+pub type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Dft = Radix2DitParallel<Val>;
+pub type Challenger = SerializingChallenger64<Val, HashChallenger<u8, ByteHash, 32>>;
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+/// `MyConfig` with the transcript of `EvmChallenger`, for proofs verified on chain.
+pub type EvmConfig = StarkConfig<Pcs, Challenge, EvmChallenger<Val>>;
+
+/// Identifies `MyConfig` and its FRI parameters in proof files, change it with any of them.
+pub const CONFIG_ID: &str = "goldilocks-keccak256-fri-blowup3-queries80-pow16";
+/// Identifies `EvmConfig`, and labels its transcript.
+pub const EVM_CONFIG_ID: &str = "goldilocks-keccak256-evm-fri-blowup3-queries80-pow16";
+
+pub const LOG_BLOWUP: usize = 3;
+pub const NUM_QUERIES: usize = 80;
+pub const PROOF_OF_WORK_BITS: usize = 16;
+
+/// The commitment scheme shared by `MyConfig` and `EvmConfig`.
+pub fn make_pcs() -> Pcs {
+    let byte_hash = ByteHash {};
+    let field_hash = FieldHash::new(byte_hash);
+    let compress = MyCompress::new(byte_hash);
+    let val_mmcs = ValMmcs::new(field_hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let dft = Dft::default();
+
+    let fri_config = FriConfig {
+        log_blowup: LOG_BLOWUP,
+        num_queries: NUM_QUERIES,
+        proof_of_work_bits: PROOF_OF_WORK_BITS,
+        mmcs: challenge_mmcs,
+    };
+    Pcs::new(dft, val_mmcs, fri_config)
+}
+
+pub fn make_config() -> MyConfig {
+    MyConfig::new(make_pcs())
+}
+
+pub fn make_evm_config() -> EvmConfig {
+    EvmConfig::new(make_pcs())
+}
+
+pub fn make_challenger() -> Challenger {
+    Challenger::from_hasher(vec![], ByteHash {})
+}
+
+pub fn make_evm_challenger() -> EvmChallenger<Val> {
+    EvmChallenger::new(EVM_CONFIG_ID.as_bytes())
+}
@@ -0,0 +1,300 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues};
+use p3_field::Field;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::stack::VerticalPair;
+use p3_matrix::Matrix;
+use p3_uni_stark::{DebugConstraintBuilder, ProverConstraintFolder, StarkGenericConfig, SymbolicAirBuilder, VerifierConstraintFolder};
+use crate::air::ProverAir;
+use crate::columns::FLAG_COLS;
+use crate::gadgets::{AuctionRule, CommitmentGadget, DecryptionGadget, Gadget};
+
+/// Failures kept with their column values, the rest are only counted.
+const MAX_REPORTED: usize = 16;
+
+/// A builder the gadgets can tell which section the constraints they assert next belong to. Only `DebugBuilder`
+/// keeps the names, the builders that prove and verify ignore them.
+pub trait SectionBuilder: AirBuilderWithPublicValues {
+    fn section(&mut self, _name: &'static str) {}
+}
+
+impl<F: Field> SectionBuilder for SymbolicAirBuilder<F> {}
+
+impl<F: Field> SectionBuilder for DebugConstraintBuilder<'_, F> {}
+
+impl<SC: StarkGenericConfig> SectionBuilder for ProverConstraintFolder<'_, SC> {}
+
+impl<SC: StarkGenericConfig> SectionBuilder for VerifierConstraintFolder<'_, SC> {}
+
+/// Evaluates the constraints on one pair of concrete rows and records the ones that do not hold.
+pub struct DebugBuilder<'a, F: Field> {
+    main: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+    public_values: &'a [F],
+    is_first_row: F,
+    is_last_row: F,
+    is_transition: F,
+    section: &'static str,
+    index: usize,
+    failed: Vec<(&'static str, usize, F)>,
+}
+
+impl<'a, F: Field> AirBuilder for DebugBuilder<'a, F> {
+    type F = F;
+    type Expr = F;
+    type Var = F;
+    type M = VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>;
+
+    fn main(&self) -> Self::M {
+        VerticalPair::new(self.main.top.clone(), self.main.bottom.clone())
+    }
+
+    fn is_first_row(&self) -> F {
+        self.is_first_row
+    }
+
+    fn is_last_row(&self) -> F {
+        self.is_last_row
+    }
+
+    fn is_transition_window(&self, size: usize) -> F {
+        assert_eq!(size, 2, "only two row windows are supported");
+        self.is_transition
+    }
+
+    fn assert_zero<I: Into<F>>(&mut self, x: I) {
+        let value = x.into();
+        if !value.is_zero() {
+            self.failed.push((self.section, self.index, value));
+        }
+        self.index += 1;
+    }
+}
+
+impl<F: Field> AirBuilderWithPublicValues for DebugBuilder<'_, F> {
+    type PublicVar = F;
+
+    fn public_values(&self) -> &[F] {
+        self.public_values
+    }
+}
+
+impl<F: Field> SectionBuilder for DebugBuilder<'_, F> {
+    fn section(&mut self, name: &'static str) {
+        self.section = name;
+        self.index = 0;
+    }
+}
+
+pub struct ConstraintFailure<F> {
+    pub row: usize,
+    pub gadget: &'static str,
+    pub section: &'static str,
+    /// Position of the constraint inside its section, starting from 0.
+    pub index: usize,
+    pub value: F,
+    /// Flag columns and the gadget's own columns of the row and the next one, by name.
+    pub local: Vec<(String, F)>,
+    pub next: Vec<(String, F)>,
+}
+
+pub struct ConstraintReport<F> {
+    pub failures: Vec<ConstraintFailure<F>>,
+    pub total: usize,
+}
+
+impl<F: Field> fmt::Display for ConstraintReport<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} constraint(s) failed", self.total)?;
+        for failure in &self.failures {
+            writeln!(f, "row {}: {}/{} #{} evaluates to {}",
+                     failure.row, failure.gadget, failure.section, failure.index, failure.value)?;
+            writeln!(f, "  local: {}", format_columns(&failure.local))?;
+            writeln!(f, "  next:  {}", format_columns(&failure.next))?;
+        }
+        if self.total > self.failures.len() {
+            writeln!(f, "... and {} more", self.total - self.failures.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field> fmt::Debug for ConstraintReport<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+fn format_columns<F: Field>(columns: &[(String, F)]) -> String {
+    columns.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join(", ")
+}
+
+/// Evaluates every constraint of `air` on every row of `trace`, the last row wraps around to the first.
+pub fn check_constraints<F, D, C, R>(
+    air: &ProverAir<D, C, R>,
+    trace: &RowMajorMatrix<F>,
+    public_values: &[F],
+) -> Result<(), ConstraintReport<F>>
+where
+    F: Field,
+    D: DecryptionGadget,
+    C: CommitmentGadget,
+    R: AuctionRule,
+{
+    check_rows(air, trace, public_values, 0..trace.height())
+}
+
+/// Same as `check_constraints`, only for the windows starting at `rows`.
+pub fn check_rows<F, D, C, R>(
+    air: &ProverAir<D, C, R>,
+    trace: &RowMajorMatrix<F>,
+    public_values: &[F],
+    rows: impl IntoIterator<Item = usize>,
+) -> Result<(), ConstraintReport<F>>
+where
+    F: Field,
+    D: DecryptionGadget,
+    C: CommitmentGadget,
+    R: AuctionRule,
+{
+    air.assert_public_values(public_values.len());
+    let mut report = ConstraintReport { failures: vec![], total: 0 };
+    for row in rows {
+        check_gadget(&air.decryption, trace, public_values, row, &mut report);
+        check_gadget(&air.commitment, trace, public_values, row, &mut report);
+        check_gadget(&air.rule, trace, public_values, row, &mut report);
+        if let Some(whitelist) = &air.whitelist {
+            check_gadget(whitelist, trace, public_values, row, &mut report);
+        }
+        if let Some(reveal) = &air.reveal {
+            check_gadget(reveal, trace, public_values, row, &mut report);
+        }
+        if let Some(receipts) = &air.receipts {
+            check_gadget(receipts, trace, public_values, row, &mut report);
+        }
+        if let Some(statistics) = &air.statistics {
+            check_gadget(statistics, trace, public_values, row, &mut report);
+        }
+        if let Some(time_lock) = &air.time_lock {
+            check_gadget(time_lock, trace, public_values, row, &mut report);
+        }
+        if let Some(shares) = &air.shares {
+            check_gadget(shares, trace, public_values, row, &mut report);
+        }
+    }
+    if report.total == 0 {
+        Ok(())
+    } else {
+        Err(report)
+    }
+}
+
+/// Whether every constraint of `air` holds on the windows starting at `rows`. Same as `check_rows` without building
+/// a report, for callers that only need the answer many times over.
+pub fn rows_satisfy<F, D, C, R>(
+    air: &ProverAir<D, C, R>,
+    trace: &RowMajorMatrix<F>,
+    public_values: &[F],
+    rows: impl IntoIterator<Item = usize>,
+) -> bool
+where
+    F: Field,
+    D: DecryptionGadget,
+    C: CommitmentGadget,
+    R: AuctionRule,
+{
+    air.assert_public_values(public_values.len());
+    rows.into_iter().all(|row| {
+        failed_constraints(&air.decryption, trace, public_values, row).is_empty()
+            && failed_constraints(&air.commitment, trace, public_values, row).is_empty()
+            && failed_constraints(&air.rule, trace, public_values, row).is_empty()
+            && air.whitelist.iter().all(|whitelist| failed_constraints(whitelist, trace, public_values, row).is_empty())
+            && air.reveal.iter().all(|reveal| failed_constraints(reveal, trace, public_values, row).is_empty())
+            && air.receipts.iter().all(|receipts| failed_constraints(receipts, trace, public_values, row).is_empty())
+            && air.statistics.iter().all(|statistics| failed_constraints(statistics, trace, public_values, row).is_empty())
+            && air.time_lock.iter().all(|time_lock| failed_constraints(time_lock, trace, public_values, row).is_empty())
+            && air.shares.iter().all(|shares| failed_constraints(shares, trace, public_values, row).is_empty())
+    })
+}
+
+fn check_gadget<F: Field, G: Gadget>(
+    gadget: &G,
+    trace: &RowMajorMatrix<F>,
+    public_values: &[F],
+    row: usize,
+    report: &mut ConstraintReport<F>,
+) {
+    let height = trace.height();
+    let next_row = (row + 1) % height;
+    let local = &trace.values[row * trace.width..(row + 1) * trace.width];
+    let next = &trace.values[next_row * trace.width..(next_row + 1) * trace.width];
+    for (section, index, value) in failed_constraints(gadget, trace, public_values, row) {
+        report.total += 1;
+        if report.failures.len() < MAX_REPORTED {
+            report.failures.push(ConstraintFailure {
+                row,
+                gadget: gadget.name(),
+                section,
+                index,
+                value,
+                local: named_columns(gadget, local),
+                next: named_columns(gadget, next),
+            });
+        }
+    }
+}
+
+/// The section, position and value of every constraint of `gadget` that fails on the window starting at `row`.
+fn failed_constraints<F: Field, G: Gadget>(
+    gadget: &G,
+    trace: &RowMajorMatrix<F>,
+    public_values: &[F],
+    row: usize,
+) -> Vec<(&'static str, usize, F)> {
+    eval_window(trace, public_values, row, |builder| gadget.eval(builder))
+}
+
+/// The row and section of every constraint of `air` that fails on `trace`, for the AIRs that are not split into
+/// gadgets, like `BidProofAir`.
+pub fn failed_sections<F: Field, A>(air: &A, trace: &RowMajorMatrix<F>, public_values: &[F]) -> Vec<(usize, &'static str)>
+where
+    A: for<'a> Air<DebugBuilder<'a, F>>,
+{
+    let mut sections: Vec<(usize, &'static str)> = (0..trace.height())
+        .flat_map(|row| eval_window(trace, public_values, row, |builder| air.eval(builder)).into_iter().map(move |(section, ..)| (row, section)))
+        .collect();
+    sections.dedup();
+    sections
+}
+
+fn eval_window<'a, F: Field>(
+    trace: &'a RowMajorMatrix<F>,
+    public_values: &'a [F],
+    row: usize,
+    eval: impl FnOnce(&mut DebugBuilder<'a, F>),
+) -> Vec<(&'static str, usize, F)> {
+    let height = trace.height();
+    let next_row = (row + 1) % height;
+    let local = &trace.values[row * trace.width..(row + 1) * trace.width];
+    let next = &trace.values[next_row * trace.width..(next_row + 1) * trace.width];
+
+    let mut builder = DebugBuilder {
+        main: VerticalPair::new(RowMajorMatrixView::new_row(local), RowMajorMatrixView::new_row(next)),
+        public_values,
+        is_first_row: F::from_bool(row == 0),
+        is_last_row: F::from_bool(row == height - 1),
+        is_transition: F::from_bool(row != height - 1),
+        section: "",
+        index: 0,
+        failed: vec![],
+    };
+    eval(&mut builder);
+    builder.failed
+}
+
+fn named_columns<F: Field, G: Gadget>(gadget: &G, row: &[F]) -> Vec<(String, F)> {
+    FLAG_COLS.chain(gadget.columns()).map(|i| (gadget.column_name(i), row[i])).collect()
+}
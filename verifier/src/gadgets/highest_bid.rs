@@ -0,0 +1,119 @@
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::columns::{BidCols, ADDRESS_BYTES, RULE_COLS};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::{AuctionRule, Gadget};
+
+/// First-price rule: the highest valid bid wins, ties go to the earliest bidder.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HighestBidRule;
+
+impl Gadget for HighestBidRule {
+    fn name(&self) -> &'static str {
+        "highest_bid"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        RULE_COLS
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_logic(builder);
+    }
+}
+
+impl AuctionRule for HighestBidRule {}
+
+pub fn eval_logic<AB: SectionBuilder> (builder: &mut AB) {
+    // columns involves: flags, final_value, bid_amount, nonce, winner_amount,
+    // change_winner, winner_address
+    let main = builder.main();
+
+    let local = main.row_slice(0);
+    let next = main.row_slice(1);
+    let local: &BidCols<AB::Var> = (*local).borrow();
+    let next: &BidCols< AB::Var> = (*next).borrow();
+
+    // first row
+    builder.section("first_row");
+    builder.when_first_row().assert_zero(local.winner_amount);
+    builder.when_first_row().assert_zero(local.nonce);
+    builder.when_first_row().assert_zero(local.bid_amount);
+    for i in 0..ADDRESS_BYTES {
+        builder.when_first_row().assert_zero(local.winner_address[i]);
+    }
+
+    // new bidder
+    builder.section("new_bidder");
+    let new_bidder = local.new_bidder;
+    builder.when(new_bidder).assert_zero(local.bid_amount);
+    builder.when(new_bidder).assert_zero(local.nonce);
+    builder.when(new_bidder).assert_zero(local.change_winner);
+
+    let next_computing_winner = next.computing_winner;
+    // check nonce, an invalid bid counts for nothing
+    builder.section("nonce");
+    builder.when(next_computing_winner).when(AB::Expr::one() - next.is_error)
+        .assert_eq(next.final_value, next.bid_amount * AB::Expr::from_canonical_u64(1000) + next.nonce);
+
+
+    // check winner
+    let next_not_change = AB::Expr::one() - next.change_winner;
+    let next_change = next.change_winner;
+    let next_is_error = next.is_error;
+
+    // is reading
+    builder.section("reading");
+    let is_reading = local.is_reading;
+    let next_reading = next.is_reading;
+    builder.when(is_reading).assert_zero(local.bid_amount);
+    builder.when(is_reading).assert_zero(local.nonce);
+    builder.when(is_reading).assert_zero(local.change_winner);
+    builder.when(next_reading).assert_eq(local.winner_amount, next.winner_amount);
+    for i in 0..ADDRESS_BYTES{
+        builder.when(next_reading).assert_eq(local.winner_address[i], next.winner_address[i]);
+    }
+
+
+    // when exponent
+    builder.section("exponent");
+    let next_exponent = next.is_exponent;
+    builder.when(next_exponent).assert_eq(local.bid_amount, next.bid_amount);
+    builder.when(next_exponent).assert_eq(local.nonce, next.nonce);
+    builder.when(next_exponent).assert_eq(local.change_winner, next.change_winner);
+    builder.when(next_exponent).assert_eq(local.winner_amount, next.winner_amount);
+    for i in 0..ADDRESS_BYTES{
+        builder.when(next_exponent).assert_eq(local.winner_address[i], next.winner_address[i]);
+    }
+
+    // error
+    builder.section("error");
+    builder.when(next_computing_winner).when(next_is_error).assert_zero(next.change_winner);
+
+    // if change
+    // new bid amount equals to the previous winner
+    builder.section("change");
+    builder.when(next_computing_winner).when(next_change).assert_eq(next.bid_amount, next.winner_amount);
+    for i in 0..ADDRESS_BYTES {
+        builder.when(next_computing_winner).when(next_change).assert_eq(next.winner_address[i], next.read_address[i]);
+    }
+
+    // if not change
+    builder.section("keep");
+    builder.when(next_computing_winner).when(next_not_change.clone()).assert_eq(local.winner_amount, next.winner_amount);
+    for i in 0..ADDRESS_BYTES {
+        builder.when(next_computing_winner).when(next_not_change.clone()).assert_eq(next.winner_address[i], local.winner_address[i]);
+    }
+
+    // check answer with public input
+    builder.section("public_winner");
+    let winner_amount = builder.public_values()[3];
+    builder.when_last_row().assert_eq(local.winner_amount, winner_amount);
+    for i in 0..ADDRESS_BYTES {
+        let add = builder.public_values()[i + 4];
+        builder.when_last_row().assert_eq(local.winner_address[i], add);
+    }
+}
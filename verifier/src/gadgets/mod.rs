@@ -0,0 +1,61 @@
+//! Building blocks of the auction AIR.
+//!
+//! Every gadget owns a contiguous range of `BidCols` and the constraints over it. The flag
+//! columns (`is_dummy`, `new_bidder`, `is_reading`, `is_exponent`, `computing_winner`) are shared
+//! and may be read by every gadget. `ProverAir` is generic over one gadget of each kind, so an
+//! encryption scheme, a bid commitment and an auction rule can be swapped independently. An
+//! auction with a whitelist also has the `MerkleWhitelist`, which owns the `MembershipCols` after
+//! the `BidCols`, one revealing every bid the `BidReveal`, which owns the `RevealCols` after
+//! them, one issuing receipts the `ReceiptTree`, which owns the `ReceiptCols` after them, one
+//! publishing statistics the `BidStatistics`, which owns the `StatisticsCols` after them, a
+//! time-lock decryption the `TimeLockPuzzle`, which owns the `TimeLockCols` after them, and a
+//! threshold decryption the `OperatorShares`, which owns the `ShareCols` after all the others.
+
+mod highest_bid;
+mod receipt;
+mod reveal;
+mod rolling_hash;
+mod rsa;
+mod statistics;
+mod threshold;
+mod time_lock;
+mod whitelist;
+
+use alloc::string::String;
+use core::ops::Range;
+use crate::debug_builder::SectionBuilder;
+use crate::columns::column_name;
+
+pub use highest_bid::HighestBidRule;
+pub use receipt::ReceiptTree;
+pub use reveal::BidReveal;
+pub use rolling_hash::RollingHashCommitment;
+pub use rsa::{RsaDecryption, Scheme};
+pub use statistics::BidStatistics;
+pub use threshold::OperatorShares;
+pub use time_lock::TimeLockPuzzle;
+pub use whitelist::MerkleWhitelist;
+
+pub trait Gadget: Sync {
+    /// Short name used when reporting on the gadget's constraints.
+    fn name(&self) -> &'static str;
+
+    /// Columns of the trace written by this gadget.
+    fn columns(&self) -> Range<usize>;
+
+    /// Name of column `index` when reporting on the gadget's constraints.
+    fn column_name(&self, index: usize) -> String {
+        column_name(index)
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB);
+}
+
+/// Decrypts the encrypted bid bytes into `final_value`.
+pub trait DecryptionGadget: Gadget {}
+
+/// Commits to every bidder address and encrypted bid, checked against the public hash.
+pub trait CommitmentGadget: Gadget {}
+
+/// Picks the winner out of the decrypted bids, checked against the public winner.
+pub trait AuctionRule: Gadget {}
@@ -0,0 +1,108 @@
+use alloc::format;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::columns::{column_name, ReceiptCols, NUM_RECEIPT_COLS, PERMUTATION_COLS, RECEIPT_DEPTH};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::reveal::eval_bid_leaf;
+use crate::gadgets::whitelist::eval_permutation;
+use crate::gadgets::Gadget;
+use crate::poseidon2::{DIGEST, WIDTH};
+
+/// Commits to every bid, hashed with a random `salt` in place of its nonce, with the Merkle tree of
+/// `receipt::commitment`, whose root is the public values from `index` on. The `ReceiptCols` start at `offset`.
+///
+/// The tree is built as the bids come: every computing winner row hashes its bid as the next leaf, and up through
+/// every level with the `frontier` node on its left when the leaf index has the level's bit set, with an empty (zero)
+/// node on its right otherwise. That gives the root of the bids so far, and the node stored as the level's frontier
+/// when it is a left child.
+#[derive(Clone, Copy, Debug)]
+pub struct ReceiptTree {
+    pub offset: usize,
+    pub index: usize,
+}
+
+impl Gadget for ReceiptTree {
+    fn name(&self) -> &'static str {
+        "receipts"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        self.offset..self.offset + NUM_RECEIPT_COLS
+    }
+
+    fn column_name(&self, index: usize) -> String {
+        match index.checked_sub(self.offset) {
+            Some(index) => format!("{}.{}", self.name(), ReceiptCols::<u8>::column_name(index)),
+            None => column_name(index),
+        }
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_receipts(builder, self.offset, self.index);
+    }
+}
+
+pub fn eval_receipts<AB: SectionBuilder>(builder: &mut AB, offset: usize, index: usize) {
+    let main = builder.main();
+    let public_values: [AB::Expr; DIGEST] = core::array::from_fn(|i| builder.public_values()[index + i].into());
+    let local_row = main.row_slice(0);
+    let next_row = main.row_slice(1);
+    let local: &ReceiptCols<AB::Var> = (*local_row)[offset..].borrow();
+    let next: &ReceiptCols<AB::Var> = (*next_row)[offset..].borrow();
+
+    // the tree starts empty
+    builder.section("start");
+    builder.when_first_row().assert_zero(local.count);
+    for x in local.root.into_iter().chain(local.frontier) {
+        builder.when_first_row().assert_zero(x);
+    }
+
+    let (counted, mut node) = eval_bid_leaf(builder, next.salt, &next.leaf_rounds);
+
+    // the leaf index is the number of bids before
+    builder.section("index");
+    for bit in next.index_bits {
+        builder.assert_bool(bit);
+    }
+    let leaf_index = next.index_bits.iter().rev().fold(AB::Expr::zero(), |acc, bit| acc * AB::Expr::two() + *bit);
+    builder.when(counted.clone()).assert_eq(leaf_index, local.count);
+
+    // every level hashes the node with the frontier on its left or an empty node on its right, and keeps the left one
+    builder.section("path");
+    for level in 0..RECEIPT_DEPTH {
+        let bit = next.index_bits[level];
+        let inputs = &next.level_inputs[level * WIDTH..(level + 1) * WIDTH];
+        for i in 0..DIGEST {
+            let frontier = local.frontier[level * DIGEST + i];
+            builder.when(counted.clone()).assert_eq(inputs[i], node[i].clone() + bit * (frontier - node[i].clone()));
+            builder.when(counted.clone()).assert_eq(inputs[DIGEST + i], node[i].clone() * bit);
+            builder.when(counted.clone()).assert_eq(next.frontier[level * DIGEST + i], inputs[i]);
+        }
+        let rounds = &next.level_rounds[level * PERMUTATION_COLS..(level + 1) * PERMUTATION_COLS];
+        node = eval_permutation(builder, counted.clone(), core::array::from_fn(|i| inputs[i].into()), rounds);
+    }
+
+    // the last level is the root, the rows without a bid carry the tree
+    builder.section("root");
+    builder.when(counted.clone()).assert_eq(next.count, local.count + AB::Expr::one());
+    for (root, node) in next.root.into_iter().zip(node) {
+        builder.when(counted.clone()).assert_eq(root, node);
+    }
+    let not_counted = AB::Expr::one() - counted;
+    let mut carry = builder.when_transition();
+    let mut carry = carry.when(not_counted);
+    carry.assert_eq(next.count, local.count);
+    for (next, local) in next.root.into_iter().chain(next.frontier).zip(local.root.into_iter().chain(local.frontier)) {
+        carry.assert_eq(next, local);
+    }
+
+    // the root of all bids is public
+    builder.section("public_receipts");
+    for (root, public_value) in local.root.into_iter().zip(public_values) {
+        builder.when_last_row().assert_eq(root, public_value);
+    }
+}
@@ -0,0 +1,110 @@
+use alloc::format;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::columns::{column_name, BidCols, RevealCols, ADDRESS_BYTES, NUM_BID_COLS, NUM_REVEAL_COLS};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::whitelist::eval_permutation;
+use crate::gadgets::Gadget;
+use crate::poseidon2::{DIGEST, WIDTH};
+
+/// Commits to the address, amount, nonce and validity of every bid, in submission order, with the hash chain of
+/// `reveal::commitment`, whose digest is the public values from `index` on. The `RevealCols` start at `offset`.
+#[derive(Clone, Copy, Debug)]
+pub struct BidReveal {
+    pub offset: usize,
+    pub index: usize,
+}
+
+impl Gadget for BidReveal {
+    fn name(&self) -> &'static str {
+        "reveal"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        self.offset..self.offset + NUM_REVEAL_COLS
+    }
+
+    fn column_name(&self, index: usize) -> String {
+        match index.checked_sub(self.offset) {
+            Some(index) => format!("{}.{}", self.name(), RevealCols::<u8>::column_name(index)),
+            None => column_name(index),
+        }
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_reveal(builder, self.offset, self.index);
+    }
+}
+
+pub fn eval_reveal<AB: SectionBuilder>(builder: &mut AB, offset: usize, index: usize) {
+    let main = builder.main();
+    let public_values: [AB::Expr; DIGEST] = core::array::from_fn(|i| builder.public_values()[index + i].into());
+    let local_row = main.row_slice(0);
+    let next_row = main.row_slice(1);
+    let next: &BidCols<AB::Var> = (*next_row)[..NUM_BID_COLS].borrow();
+    let local_reveal: &RevealCols<AB::Var> = (*local_row)[offset..].borrow();
+    let next_reveal: &RevealCols<AB::Var> = (*next_row)[offset..].borrow();
+
+    let (counted, leaf) = eval_bid_leaf(builder, next.nonce, &next_reveal.leaf_rounds);
+
+    // every revealed bid chains its hash to the previous ones, the other rows carry the chain
+    builder.section("chain");
+    for hash in local_reveal.reveal_hash {
+        builder.when_first_row().assert_zero(hash);
+    }
+    let input = core::array::from_fn(|i| match i.checked_sub(DIGEST) {
+        None => local_reveal.reveal_hash[i].into(),
+        Some(i) => leaf[i].clone(),
+    });
+    let chain = eval_permutation(builder, counted.clone(), input, &next_reveal.chain_rounds);
+    let not_counted = AB::Expr::one() - counted.clone();
+    for (i, node) in chain.into_iter().enumerate() {
+        builder.when(counted.clone()).assert_eq(next_reveal.reveal_hash[i], node);
+        builder.when_transition().when(not_counted.clone()).assert_eq(next_reveal.reveal_hash[i], local_reveal.reveal_hash[i]);
+    }
+
+    // the chain of all bids is public
+    builder.section("public_reveal");
+    for (hash, public_value) in local_reveal.reveal_hash.into_iter().zip(public_values) {
+        builder.when_last_row().assert_eq(hash, public_value);
+    }
+}
+
+/// Checks that `rounds` hash the bid of the next row when it is a computing winner row, with `hidden` in place of its
+/// nonce, see `reveal::RevealedBid::leaf_input`. Returns whether the next row holds a bid, and the hash.
+pub(crate) fn eval_bid_leaf<AB: SectionBuilder>(
+    builder: &mut AB,
+    hidden: AB::Var,
+    rounds: &[AB::Var],
+) -> (AB::Expr, [AB::Expr; DIGEST]) {
+    let main = builder.main();
+    let local_row = main.row_slice(0);
+    let next_row = main.row_slice(1);
+    let local: &BidCols<AB::Var> = (*local_row)[..NUM_BID_COLS].borrow();
+    let next: &BidCols<AB::Var> = (*next_row)[..NUM_BID_COLS].borrow();
+
+    // the computing winner row of a bidder still holds its address
+    builder.section("bidder");
+    let same_bidder = AB::Expr::one() - next.new_bidder - next.is_dummy;
+    for i in 0..ADDRESS_BYTES {
+        builder.when_transition().when(same_bidder.clone()).assert_eq(next.read_address[i], local.read_address[i]);
+    }
+
+    // dummy rows keep the flags of the last row, `computing_winner` included
+    builder.section("leaf");
+    let counted = next.computing_winner * (AB::Expr::one() - next.is_dummy);
+    builder.when(counted.clone()).assert_bool(next.is_error);
+    builder.when(counted.clone()).when(next.is_error).assert_zero(next.bid_amount);
+    builder.when(counted.clone()).when(next.is_error).assert_zero(next.nonce);
+    let words = next.read_address.chunks(4).map(|bytes| {
+        bytes.iter().rev().fold(AB::Expr::zero(), |word, byte| word * AB::Expr::from_canonical_u32(256) + *byte)
+    });
+    let mut input = words.chain([next.bid_amount, hidden, next.is_error].map(Into::into));
+    let input: [AB::Expr; WIDTH] = core::array::from_fn(|_| input.next().unwrap());
+    let leaf = eval_permutation(builder, counted.clone(), input, rounds);
+    (counted, leaf)
+}
@@ -0,0 +1,110 @@
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::columns::{BidCols, ADDRESS_BYTES, BASE, COMMITMENT_COLS};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::{CommitmentGadget, Gadget};
+
+/// Rolling hash over every bidder address and encrypted bid, in submission order.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RollingHashCommitment;
+
+impl Gadget for RollingHashCommitment {
+    fn name(&self) -> &'static str {
+        "rolling_hash"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        COMMITMENT_COLS
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_hashing(builder);
+    }
+}
+
+impl CommitmentGadget for RollingHashCommitment {}
+
+pub fn eval_hashing<AB: SectionBuilder> (builder: &mut AB) {
+
+    // columns involves: flags, read_bytes, read_address, hash_lim, hash_value
+
+    let main = builder.main();
+
+    let local = main.row_slice(0);
+    let next = main.row_slice(1);
+    let local: &BidCols<AB::Var> = (*local).borrow();
+    let next: &BidCols< AB::Var> = (*next).borrow();
+    let base = AB::Expr::from_canonical_u64(BASE as u64);
+
+    // new bidder
+    builder.section("new_bidder");
+    let new_bidder = local.new_bidder;
+    let next_new_bidder = next.new_bidder;
+    let next_not_dummy = AB::Expr::one() - next.is_dummy;
+
+    builder.when_transition().when(next_new_bidder).when(next_not_dummy.clone()).assert_eq(local.hash_lim, next.hash_lim);
+
+    // check lim constraints
+    builder.section("hash_lim");
+    builder.when(new_bidder).assert_eq(local.hash_lim * base.exp_u64(5), next.hash_lim);
+    let read_encrypted_byte = local.is_reading;
+    let local_not_dummy = AB::Expr::one() - local.is_dummy;
+    builder.when(next_not_dummy.clone()).when(local_not_dummy.clone()).when(read_encrypted_byte).assert_eq(local.hash_lim * base.clone(), next.hash_lim);
+    
+    // check hash values
+
+    // first hash value
+    builder.section("first_hash");
+    let mut first_lim = AB::Expr::one();
+    let mut first_hash = AB::Expr::zero();
+    for i in 0..5 {
+        let hash_num = next.read_address[i*4] +  next.read_address[i*4 + 1] * AB::Expr::from_canonical_u64(256)
+            + next.read_address[i*4 + 2] * AB::Expr::from_canonical_u64(65536) + next.read_address[i*4 + 3] * AB::Expr::from_canonical_u64(16777216);
+        first_hash += hash_num * first_lim.clone();
+        first_lim = base.clone() * first_lim;
+    }
+    builder.when_first_row().assert_eq(local.hash_value, first_hash);
+
+    // check hash during the exponent
+    builder.section("exponent");
+    let next_exponent = next.is_exponent;
+    let local_exponent = local.is_exponent;
+    builder.when(next_exponent).assert_eq(local.hash_value, next.hash_value);
+    builder.when(local_exponent).assert_eq(local.hash_lim, next.hash_lim);
+
+    // hash new address value
+    builder.section("address");
+    let next_new_bidder = next.new_bidder;
+    let mut start_lim = next.hash_lim.into();
+    let mut new_hash = local.hash_value.into();
+    for i in 0..5 {
+        let hash_num = next.read_address[i*4] +  next.read_address[i*4 + 1] * AB::Expr::from_canonical_u64(256)
+            + next.read_address[i*4 + 2] * AB::Expr::from_canonical_u64(65536) + next.read_address[i*4 + 3] * AB::Expr::from_canonical_u64(16777216);
+        new_hash += hash_num * start_lim.clone();
+        start_lim = base.clone() * start_lim;
+    }
+    builder.when(local_not_dummy.clone()).when(next_new_bidder).assert_eq(new_hash, next.hash_value);
+
+    // when reading
+    builder.section("reading");
+    let next_new_reader = next.is_reading;
+    builder.when(next_not_dummy.clone()).when(local_not_dummy.clone()).when(next_new_reader).assert_eq(local.hash_value + next.hash_lim * next.current_value, next.hash_value);
+
+    // when error
+    builder.section("error");
+    let next_error = next.is_error;
+    for i in 0..ADDRESS_BYTES {
+        builder.when(next_error).assert_eq(local.read_address[i], next.read_address[i]);
+    }
+    // reads move `hash_lim` on and are hashed even after a failed limb
+    builder.when(next_error).when(AB::Expr::one() - local.is_reading).assert_eq(local.hash_lim, next.hash_lim);
+    builder.when(next_error).when(AB::Expr::one() - next.is_reading).assert_eq(local.hash_value, next.hash_value);
+
+    // check final hash
+    builder.section("final_hash");
+    let final_hash = builder.public_values()[1];
+    builder.when_last_row().assert_eq(local.hash_value, final_hash);
+}
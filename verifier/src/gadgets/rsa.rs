@@ -0,0 +1,199 @@
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::columns::{BidCols, DECODED_BYTES, DECRYPTION_COLS, READ_BYTES};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::{DecryptionGadget, Gadget};
+
+/// Decryption of every 4 encrypted bytes modulo the public RSA modulus, see `document/proving_service.md`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scheme {
+    /// Square-and-multiply with the owner's private exponent.
+    #[default]
+    Owner,
+    /// The private exponent is split among this many operators, and every 4 bytes are the product of their partial
+    /// decryptions, see `OperatorShares`.
+    Threshold(usize),
+    /// Each bid starts with a time-lock puzzle, squared a public number of times into the mask of its other 4 bytes,
+    /// see `TimeLockPuzzle`.
+    TimeLock,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RsaDecryption {
+    pub scheme: Scheme,
+    /// Set with a whitelist, whose gadget then decides the error of the computing winner rows, see `MerkleWhitelist`.
+    pub restricted: bool,
+}
+
+impl RsaDecryption {
+    pub fn threshold(operators: usize) -> Self {
+        Self { scheme: Scheme::Threshold(operators), ..Self::default() }
+    }
+
+    pub fn time_lock() -> Self {
+        Self { scheme: Scheme::TimeLock, ..Self::default() }
+    }
+}
+
+impl Gadget for RsaDecryption {
+    fn name(&self) -> &'static str {
+        "rsa_decryption"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        DECRYPTION_COLS
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_decryption(builder, self.scheme, self.restricted);
+    }
+}
+
+impl DecryptionGadget for RsaDecryption {}
+
+pub fn eval_decryption<AB: SectionBuilder> (builder: &mut AB, scheme: Scheme, restricted: bool) {
+    // columns involves: flags, read_bytes, current_value, quotient_value, exponent_value
+    // odd_exponent, r, q_r, decoded_bytes, gap, final_value,
+    let main = builder.main();
+
+    let modules = builder.public_values()[0].into();
+    let local = main.row_slice(0);
+    let next = main.row_slice(1);
+    let local: &BidCols<AB::Var> = (*local).borrow();
+    let next: &BidCols< AB::Var> = (*next).borrow();
+
+    // first row:
+    builder.section("first_row");
+    builder.when_first_row().assert_eq(local.new_bidder, AB::F::one());
+    builder.when_first_row().assert_eq(local.is_dummy, AB::F::zero());
+
+    // conditions
+    let new_bidder = local.new_bidder;
+    let is_reading = local.is_reading;
+
+
+    // new bidder constraints
+    builder.section("new_bidder");
+    builder.when(new_bidder).assert_one(local.r);
+    builder.when(new_bidder).assert_one(local.gap);
+    builder.when(new_bidder).assert_zero(local.is_reading);
+    builder.when(new_bidder).assert_zero(local.is_exponent);
+    builder.when(new_bidder).assert_zero(local.current_value);
+    builder.when(new_bidder).assert_zero(local.quotient_value);
+    builder.when(new_bidder).assert_zero(local.odd_exponent);
+    builder.when(new_bidder).assert_zero(local.q_r);
+    builder.when(new_bidder).assert_zero(local.is_error);
+    builder.when(new_bidder).assert_zero(local.final_value);
+    builder.when(new_bidder).assert_one(local.gap);
+    for i in 0..READ_BYTES {
+        builder.when(new_bidder).assert_zero(local.read_bytes[i]);
+    }
+    for i in 0..DECODED_BYTES {
+        builder.when(new_bidder).assert_zero(local.decoded_bytes[i]);
+    }
+
+
+    // when reading
+    builder.section("reading");
+    let lim1 = AB::F::from_canonical_u64(256);  //2^8
+    let lim2 = AB::F::from_canonical_u64(65536);  //2^16
+    let lim3 = AB::F::from_canonical_u64(16777216);  //2^8
+    let first_read = next.is_reading * local.new_bidder;
+    builder.when(is_reading).assert_eq(local.current_value,
+                                       local.read_bytes[0] + local.read_bytes[1] * lim1 + local.read_bytes[2] * lim2 + local.read_bytes[3] * lim3);
+    builder.when(is_reading).assert_one(local.r);
+    builder.when(is_reading).assert_zero(local.quotient_value);
+    builder.when(is_reading).assert_zero(local.odd_exponent);
+    builder.when(is_reading).assert_zero(local.q_r);
+    builder.when(is_reading).assert_zero(local.quotient_value);
+    builder.when(first_read).assert_one(next.gap);
+    for i in 0..DECODED_BYTES {
+        builder.when(is_reading).assert_zero(local.decoded_bytes[i]);
+    }
+    builder.when(next.is_reading).assert_eq(local.final_value, next.final_value);
+
+    // when exponent
+
+    let next_exponent = next.is_exponent;
+    let next_odd_exponent = next.odd_exponent;
+    let two = AB::F::from_canonical_u64(2);
+
+    // in threshold decryption every decryption step multiplies in a partial decryption, there is no exponent to
+    // square with
+    if !matches!(scheme, Scheme::Threshold(_)) {
+        // check current value
+        builder.section("square");
+        builder.when(next_exponent).assert_eq(local.current_value * local.current_value, next.quotient_value * modules.clone() + next.current_value);
+    }
+    if scheme == Scheme::Owner {
+        // check exponent
+        builder.section("exponent");
+        builder.when(next_exponent).assert_eq(local.exponent_value, next.exponent_value * two + next.odd_exponent);
+        // check reminder
+        builder.section("multiply");
+        builder.when(next_odd_exponent).assert_eq(local.r * local.current_value, next.q_r * modules.clone() + next.r);
+    }
+    // other cells stay the same
+    builder.section("exponent_copy");
+    builder.when(next_exponent).assert_eq(local.gap, next.gap);
+    for i in 0..DECODED_BYTES {
+        builder.when(next_exponent).assert_eq(local.decoded_bytes[i], next.decoded_bytes[i]);
+    }
+    builder.when(next_exponent).assert_eq(local.final_value, next.final_value);
+
+
+    // exponent value constraints
+    builder.section("write");
+    let not_exponent = AB::Expr::one() - local.is_exponent;
+    let not_new_bidder = AB::Expr::one() - local.new_bidder;
+    let not_reading = AB::Expr::one() - local.is_reading;
+    let not_error = AB::Expr::one() - local.is_error;
+    builder.when(not_exponent.clone()).when(not_new_bidder.clone()).when(not_reading.clone()).when(not_error.clone()).assert_eq(local.exponent_value, AB::Expr::zero());
+    builder.when(not_exponent.clone()).when(not_new_bidder.clone()).when(not_reading.clone()).when(not_error.clone())
+        .assert_eq(local.current_value, local.decoded_bytes[0] + local.decoded_bytes[1] * lim1 + local.decoded_bytes[2] * lim2 + local.decoded_bytes[3] * lim3);
+
+    // final value constraints
+    builder.section("final_value");
+    let next_not_exponent = AB::Expr::one() - next.is_exponent;
+    let next_not_new_bidder = AB::Expr::one() - next.new_bidder;
+    let next_not_reading = AB::Expr::one() - next.is_reading;
+    let next_not_error = AB::Expr::one() - next.is_error;
+
+    builder.when(next_not_exponent).when(next_not_new_bidder).when(next_not_reading).when(next_not_error)
+        .assert_eq(next.final_value, local.final_value + next.current_value * local.gap);
+
+    // check gap constraints
+    builder.section("gap");
+    let gap_diff = AB::F::from_canonical_u64(65536);
+    let mut gap_condition = next.is_reading - next.is_reading * local.new_bidder - next.is_reading * local.is_reading - next.is_reading * next.new_bidder;
+    if scheme == Scheme::TimeLock {
+        // the puzzle is not a limb of the bid
+        gap_condition -= next.is_reading * local.is_exponent;
+        builder.when(next.is_reading).when(local.is_exponent).assert_eq(next.gap, local.gap);
+    }
+    let normal = next.is_exponent;
+    builder.when(gap_condition).assert_eq(next.gap, local.gap * gap_diff);
+    builder.when(normal).assert_eq(next.gap, local.gap);
+
+    // check error constraints
+    builder.section("error");
+
+    let error_before = next.is_error * next.is_reading;
+    builder.when(error_before).assert_one(local.is_error);
+
+    builder.when_ne(next.decoded_bytes[2], AB::Expr::zero()).assert_one(next.is_error * (AB::Expr::one() - local.is_error));
+    builder.when_ne(next.decoded_bytes[3], AB::Expr::zero()).assert_one(next.is_error * (AB::Expr::one() - local.is_error));
+    // the computing winner row decides with the error of the bid's last row, and with a whitelist also with the
+    // membership of the bidder
+    if !restricted {
+        builder.when(next.computing_winner).when(AB::Expr::one() - next.is_dummy).assert_eq(next.is_error, local.is_error);
+    }
+    // cells that must stay the same when is error
+    builder.section("error_copy");
+    let next_error = next.is_error;
+    builder.when(next_error).assert_eq(local.final_value, next.final_value);
+
+}
@@ -0,0 +1,75 @@
+use alloc::format;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::columns::{column_name, BidCols, StatisticsCols, NUM_BID_COLS, NUM_STATISTICS_COLS};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::Gadget;
+use crate::public_input::{AuctionPublicValues, NUM_STATISTICS};
+
+/// Counts the statistics of `AuctionStatistics` over the computing winner rows, and checks the `switched_on` ones
+/// against the public values from `index` on. The `StatisticsCols` start at `offset`, the counts of the statistics
+/// that are not published are left free.
+#[derive(Clone, Copy, Debug)]
+pub struct BidStatistics {
+    pub offset: usize,
+    pub switched_on: [bool; NUM_STATISTICS],
+    pub index: usize,
+}
+
+impl BidStatistics {
+    /// The statistics `public_values` publishes, with the `StatisticsCols` from `offset`.
+    pub fn for_auction(offset: usize, public_values: &AuctionPublicValues) -> Self {
+        Self { offset, switched_on: public_values.statistics.switched_on(), index: public_values.statistics_index() }
+    }
+}
+
+impl Gadget for BidStatistics {
+    fn name(&self) -> &'static str {
+        "statistics"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        self.offset..self.offset + NUM_STATISTICS_COLS
+    }
+
+    fn column_name(&self, index: usize) -> String {
+        match index.checked_sub(self.offset) {
+            Some(index) => format!("{}.{}", self.name(), StatisticsCols::<u8>::column_name(index)),
+            None => column_name(index),
+        }
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_statistics(builder, self.offset, self.switched_on, self.index);
+    }
+}
+
+pub fn eval_statistics<AB: SectionBuilder>(builder: &mut AB, offset: usize, switched_on: [bool; NUM_STATISTICS], index: usize) {
+    let main = builder.main();
+    let public_values: [AB::Expr; NUM_STATISTICS] = core::array::from_fn(|i| builder.public_values()[index + i].into());
+    let local_row = main.row_slice(0);
+    let next_row = main.row_slice(1);
+    let next: &BidCols<AB::Var> = (*next_row)[..NUM_BID_COLS].borrow();
+    let local_statistics: &StatisticsCols<AB::Var> = (*local_row)[offset..].borrow();
+    let next_statistics: &StatisticsCols<AB::Var> = (*next_row)[offset..].borrow();
+    let local_values = [local_statistics.valid_bids, local_statistics.invalid_bids, local_statistics.bid_sum];
+    let next_values = [next_statistics.valid_bids, next_statistics.invalid_bids, next_statistics.bid_sum];
+
+    // dummy rows keep the flags of the last row, `computing_winner` included
+    builder.section("statistics");
+    let counted = next.computing_winner * (AB::Expr::one() - next.is_dummy);
+    builder.when(counted.clone()).assert_bool(next.is_error);
+    let next_valid = AB::Expr::one() - next.is_error;
+    let steps = [next_valid.clone(), next.is_error.into(), next_valid * next.bid_amount];
+    let not_counted = AB::Expr::one() - counted.clone();
+    for (i, step) in steps.into_iter().enumerate().filter(|(i, _)| switched_on[*i]) {
+        builder.when_first_row().assert_zero(local_values[i]);
+        builder.when(counted.clone()).assert_eq(next_values[i], local_values[i] + step);
+        builder.when_transition().when(not_counted.clone()).assert_eq(next_values[i], local_values[i]);
+        builder.when_last_row().assert_eq(local_values[i], public_values[i].clone());
+    }
+}
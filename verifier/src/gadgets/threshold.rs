@@ -0,0 +1,206 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::bid_proof::{from_bits, VALUE_BITS};
+use crate::columns::{column_name, BidCols, ShareCols, MAX_OPERATORS, NUM_BID_COLS, NUM_SHARE_COLS, PUBLIC_EXPONENT_BITS};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::whitelist::eval_permutation;
+use crate::gadgets::Gadget;
+use crate::poseidon2::{DIGEST, WIDTH};
+
+/// Constraints of the share rows of a threshold decryption among `operators`, with the `ShareCols` from `offset`:
+/// after reading 4 non-zero bytes, one share row per operator, in order, multiplies its partial decryption into `r`,
+/// and the write row decodes the product. The share rows are the decryption steps, flagged by `is_exponent`, and keep
+/// the read 4 bytes in `current_value`, which `eval_operator_shares` re-encrypts the product into.
+pub fn eval_shares<AB: SectionBuilder>(builder: &mut AB, offset: usize, operators: usize) {
+    assert!((1..=MAX_OPERATORS).contains(&operators), "threshold decryption supports 1 to {} operators", MAX_OPERATORS);
+    let main = builder.main();
+    let modules: AB::Expr = builder.public_values()[0].into();
+    let local_row = main.row_slice(0);
+    let next_row = main.row_slice(1);
+    let local: &BidCols<AB::Var> = (*local_row)[..NUM_BID_COLS].borrow();
+    let next: &BidCols<AB::Var> = (*next_row)[..NUM_BID_COLS].borrow();
+    let local_shares: &ShareCols<AB::Var> = (*local_row)[offset..].borrow();
+    let next_shares: &ShareCols<AB::Var> = (*next_row)[offset..].borrow();
+
+    // one operator per share row, each in turn
+    builder.section("operator");
+    let not_share = AB::Expr::one() - local.is_exponent;
+    builder.assert_bool(local.is_exponent);
+    for j in 0..MAX_OPERATORS {
+        builder.assert_bool(local_shares.operator[j]);
+        builder.when(not_share.clone()).assert_zero(local_shares.operator[j]);
+    }
+    for j in operators..MAX_OPERATORS {
+        builder.assert_zero(local_shares.operator[j]);
+    }
+    builder.when_transition().when(next.is_exponent).assert_one(local.is_reading + local.is_exponent);
+    builder.when(local.is_reading).when(next.is_exponent).assert_one(next_shares.operator[0]);
+    builder.when(local.is_exponent).when(next.is_exponent).assert_zero(next_shares.operator[0]);
+    for j in 1..MAX_OPERATORS {
+        builder.when(local.is_exponent).when(next.is_exponent)
+            .assert_eq(next_shares.operator[j], local_shares.operator[j - 1]);
+    }
+    let last_share = local.is_exponent * (AB::Expr::one() - next.is_exponent);
+    builder.when_transition().when(last_share.clone()).assert_one(local_shares.operator[operators - 1]);
+    // 4 read bytes are either skipped (zero or after an error) or decrypted, never written without their shares
+    builder.when_transition().assert_zero(
+        local.is_reading * (AB::Expr::one() - next.is_exponent) * (AB::Expr::one() - next.new_bidder)
+            * (AB::Expr::one() - next.is_reading) * (AB::Expr::one() - next.computing_winner),
+    );
+
+    // an error of the bid holds through its share rows, whose 4 bytes are then never written
+    builder.section("share_error");
+    builder.when(local.is_exponent).assert_bool(local.is_error);
+    builder.when(next.is_exponent).assert_eq(next.is_error, local.is_error);
+    builder.when_transition().when(last_share.clone()).when(local.is_error).assert_one(next.is_reading + next.computing_winner);
+
+    // combine the partial decryptions
+    builder.section("combine");
+    builder.when(next.is_exponent).assert_eq(local.r * next_shares.partial, next.q_r * modules + next.r);
+    builder.when(next.is_exponent).assert_eq(next.current_value, local.current_value);
+    let not_error = AB::Expr::one() - local.is_error;
+    builder.when_transition().when(last_share.clone()).when(not_error.clone()).assert_eq(next.current_value, local.r);
+    builder.when_transition().when(last_share.clone()).when(not_error).assert_zero(
+        next.is_reading + next.new_bidder + next.computing_winner + next.is_dummy,
+    );
+}
+
+/// Checks the share rows of a threshold decryption among `operators`, see `eval_shares`, and binds them to the
+/// commitments of the operators and to the public key, with the `ShareCols` from `offset`. Every share row hashes its partial decryption into its operator's chain, whose last digest must be
+/// the commitment that operator published: the public values from `index`, one digest per operator, see
+/// `Decryption::Threshold`. Every write row after share rows raises the product to the public exponent,
+/// the public value after the commitments, and checks that it gives back the read 4 bytes modulo `n`: partials that
+/// do not multiply into the decryption are rejected, whatever their operators committed to. `n`, the decryption and
+/// every value and quotient of the steps are decomposed into `VALUE_BITS` bits, so that no product wraps around the
+/// field, and the decryption is below `n`.
+#[derive(Clone, Copy, Debug)]
+pub struct OperatorShares {
+    pub offset: usize,
+    pub operators: usize,
+    pub index: usize,
+}
+
+impl Gadget for OperatorShares {
+    fn name(&self) -> &'static str {
+        "shares"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        self.offset..self.offset + NUM_SHARE_COLS
+    }
+
+    fn column_name(&self, index: usize) -> String {
+        match index.checked_sub(self.offset) {
+            Some(index) => format!("{}.{}", self.name(), ShareCols::<u8>::column_name(index)),
+            None => column_name(index),
+        }
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_shares(builder, self.offset, self.operators);
+        eval_operator_shares(builder, self.offset, self.operators, self.index);
+    }
+}
+
+pub fn eval_operator_shares<AB: SectionBuilder>(builder: &mut AB, offset: usize, operators: usize, index: usize) {
+    let main = builder.main();
+    let modules: AB::Expr = builder.public_values()[0].into();
+    let commitments: Vec<[AB::Expr; DIGEST]> = (0..operators)
+        .map(|j| core::array::from_fn(|i| builder.public_values()[index + j * DIGEST + i].into()))
+        .collect();
+    let public_exponent: AB::Expr = builder.public_values()[index + operators * DIGEST].into();
+    let local_row = main.row_slice(0);
+    let next_row = main.row_slice(1);
+    let local: &BidCols<AB::Var> = (*local_row)[..NUM_BID_COLS].borrow();
+    let next: &BidCols<AB::Var> = (*next_row)[..NUM_BID_COLS].borrow();
+    let local_shares: &ShareCols<AB::Var> = (*local_row)[offset..].borrow();
+    let next_shares: &ShareCols<AB::Var> = (*next_row)[offset..].borrow();
+
+    // every share row hashes its partial after the chain of its operator, the other rows carry the chains
+    builder.section("share_hash");
+    for hash in local_shares.share_hash {
+        builder.when_first_row().assert_zero(hash);
+    }
+    for i in 0..DIGEST {
+        let chain = (0..operators)
+            .fold(AB::Expr::zero(), |acc, j| acc + next_shares.operator[j] * local_shares.share_hash[j * DIGEST + i]);
+        builder.when(next.is_exponent).assert_eq(next_shares.share_input[i], chain);
+    }
+    let input: [AB::Expr; WIDTH] = core::array::from_fn(|i| match i {
+        _ if i < DIGEST => next_shares.share_input[i].into(),
+        _ if i == DIGEST => next_shares.partial.into(),
+        _ => AB::Expr::zero(),
+    });
+    let digest = eval_permutation(builder, next.is_exponent.into(), input, &next_shares.share_rounds);
+    let not_share = AB::Expr::one() - next.is_exponent;
+    for j in 0..MAX_OPERATORS {
+        for (i, hash) in digest.iter().enumerate() {
+            let (local_hash, next_hash) = (local_shares.share_hash[j * DIGEST + i], next_shares.share_hash[j * DIGEST + i]);
+            builder.when(next.is_exponent)
+                .assert_eq(next_hash, local_hash + next_shares.operator[j] * (hash.clone() - local_hash));
+            builder.when_transition().when(not_share.clone()).assert_eq(next_hash, local_hash);
+        }
+    }
+
+    builder.section("share_commitment");
+    for (j, commitment) in commitments.into_iter().enumerate() {
+        for (i, value) in commitment.into_iter().enumerate() {
+            builder.when_last_row().assert_eq(local_shares.share_hash[j * DIGEST + i], value);
+        }
+    }
+
+    // the public exponent, from its most significant bit
+    builder.section("exponent");
+    for bit in local_shares.exponent_bits {
+        builder.assert_bool(bit);
+    }
+    let exponent = local_shares.exponent_bits.iter().fold(AB::Expr::zero(), |acc, bit| acc * AB::Expr::two() + *bit);
+    builder.assert_eq(exponent, public_exponent);
+
+    // every value of the re-encryption fits in `VALUE_BITS`, and the decryption of a write row is below `n`
+    builder.section("share_bits");
+    let bits = [&local_shares.squares_bits[..], &local_shares.square_quotients_bits, &local_shares.powers_bits,
+        &local_shares.power_quotients_bits, &local_shares.encrypted_quotient_bits, &local_shares.modulus_bits,
+        &local_shares.decryption_bits, &local_shares.decryption_slack_bits];
+    for bit in bits.concat() {
+        builder.assert_bool(bit);
+    }
+    let value_bits = |bits: &[AB::Var], i: usize| from_bits::<AB>(&bits[i * VALUE_BITS..(i + 1) * VALUE_BITS]);
+    for i in 0..PUBLIC_EXPONENT_BITS {
+        builder.assert_eq(local_shares.squares[i], value_bits(&local_shares.squares_bits, i));
+        builder.assert_eq(local_shares.square_quotients[i], value_bits(&local_shares.square_quotients_bits, i));
+        builder.assert_eq(local_shares.powers[i], value_bits(&local_shares.powers_bits, i));
+        builder.assert_eq(local_shares.power_quotients[i], value_bits(&local_shares.power_quotients_bits, i));
+    }
+    builder.assert_eq(local_shares.encrypted_quotient, from_bits::<AB>(&local_shares.encrypted_quotient_bits));
+    builder.assert_eq(modules.clone(), from_bits::<AB>(&local_shares.modulus_bits));
+    let written = local.is_exponent * (AB::Expr::one() - next.is_exponent) * (AB::Expr::one() - local.is_error);
+    builder.when_first_row().assert_zero(from_bits::<AB>(&local_shares.decryption_bits));
+    builder.when_first_row().assert_zero(from_bits::<AB>(&local_shares.decryption_slack_bits));
+    builder.when_transition()
+        .assert_eq(from_bits::<AB>(&next_shares.decryption_bits), written.clone() * next.current_value);
+    builder.when_transition().assert_eq(
+        from_bits::<AB>(&next_shares.decryption_slack_bits),
+        written.clone() * (modules.clone() - AB::Expr::one() - next.current_value),
+    );
+
+    // square-and-multiply the decryption of the write row after the last share row back into the read 4 bytes
+    builder.section("reencrypt");
+    let mut power = AB::Expr::one();
+    for i in 0..PUBLIC_EXPONENT_BITS {
+        let factor = AB::Expr::one() + next_shares.exponent_bits[i] * (next.current_value - AB::Expr::one());
+        let mut reencrypt = builder.when_transition();
+        let mut reencrypt = reencrypt.when(written.clone());
+        reencrypt.assert_eq(power.clone() * power, next_shares.square_quotients[i] * modules.clone() + next_shares.squares[i]);
+        reencrypt.assert_eq(next_shares.squares[i] * factor, next_shares.power_quotients[i] * modules.clone() + next_shares.powers[i]);
+        power = next_shares.powers[i].into();
+    }
+    builder.when_transition().when(written)
+        .assert_eq(power + next_shares.encrypted_quotient * modules, local.current_value);
+}
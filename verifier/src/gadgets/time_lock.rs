@@ -0,0 +1,75 @@
+use alloc::format;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::columns::{column_name, BidCols, TimeLockCols, NUM_BID_COLS, NUM_TIME_LOCK_COLS};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::Gadget;
+
+/// Constraints of a time-lock decryption, with the `TimeLockCols` from `offset`: the first 4 bytes of a bid are its
+/// puzzle, squared as many times as the public value at `index` into `mask`, and every other 4 bytes are a limb times
+/// the mask. The square constraint itself is shared with the owner scheme, see `Scheme::TimeLock`.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeLockPuzzle {
+    pub offset: usize,
+    pub index: usize,
+}
+
+impl Gadget for TimeLockPuzzle {
+    fn name(&self) -> &'static str {
+        "time_lock"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        self.offset..self.offset + NUM_TIME_LOCK_COLS
+    }
+
+    fn column_name(&self, index: usize) -> String {
+        match index.checked_sub(self.offset) {
+            Some(index) => format!("{}.{}", self.name(), TimeLockCols::<u8>::column_name(index)),
+            None => column_name(index),
+        }
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_time_lock(builder, self.offset, self.index);
+    }
+}
+
+pub fn eval_time_lock<AB: SectionBuilder>(builder: &mut AB, offset: usize, index: usize) {
+    let main = builder.main();
+    let modules: AB::Expr = builder.public_values()[0].into();
+    let squarings: AB::Expr = builder.public_values()[index].into();
+    let local_row = main.row_slice(0);
+    let next_row = main.row_slice(1);
+    let local: &BidCols<AB::Var> = (*local_row)[..NUM_BID_COLS].borrow();
+    let next: &BidCols<AB::Var> = (*next_row)[..NUM_BID_COLS].borrow();
+    let local_time_lock: &TimeLockCols<AB::Var> = (*local_row)[offset..].borrow();
+    let next_time_lock: &TimeLockCols<AB::Var> = (*next_row)[offset..].borrow();
+
+    // only the first read of a bid is squared, exactly `squarings` times
+    builder.section("puzzle");
+    builder.when(local.new_bidder).when(next.is_reading).assert_eq(next.exponent_value, squarings);
+    builder.when(next.is_reading).when(AB::Expr::one() - local.new_bidder).assert_zero(next.exponent_value);
+    builder.when(local.is_reading).when(AB::Expr::one() - next.is_exponent).assert_zero(local.exponent_value);
+    builder.when(next.is_exponent).assert_eq(local.exponent_value, next.exponent_value + AB::Expr::one());
+    let last_squaring = local.is_exponent * (AB::Expr::one() - next.is_exponent);
+    builder.when(last_squaring.clone()).assert_zero(local.exponent_value);
+    builder.when(last_squaring).assert_one(next.is_reading + next.computing_winner);
+
+    // the solved puzzle masks the rest of the bid
+    builder.section("mask");
+    let solved = local.is_exponent * next.is_reading;
+    builder.when(solved.clone()).assert_eq(next_time_lock.mask, local.current_value);
+    builder.when_transition().when(AB::Expr::one() - solved).assert_eq(next_time_lock.mask, local_time_lock.mask);
+
+    // a written limb times the mask is the 4 bytes read before it, unless the bid is invalid: a mask that is not
+    // invertible unmasks nothing
+    builder.section("unmask");
+    let next_write = AB::Expr::one() - next.is_reading - next.is_exponent - next.new_bidder - next.computing_winner;
+    builder.when(local.is_reading).when(next_write).when(AB::Expr::one() - next.is_error)
+        .assert_eq(next_time_lock.mask * next.current_value, next.quotient_value * modules + local.current_value);
+}
@@ -0,0 +1,175 @@
+use alloc::format;
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::ops::Range;
+use p3_air::AirBuilder;
+use p3_field::AbstractField;
+use p3_matrix::Matrix;
+use crate::columns::{
+    column_name, BidCols, MembershipCols, ADDRESS_BYTES, ADDRESS_LIMBS, ADDRESS_LIMB_BITS, ADDRESS_LIMB_BYTES,
+    NUM_BID_COLS, NUM_MEMBERSHIP_COLS, PERMUTATION_COLS, WHITELIST_DEPTH,
+};
+use crate::debug_builder::SectionBuilder;
+use crate::gadgets::Gadget;
+use crate::poseidon2::{
+    external_layer, internal_layer, sbox, DIGEST, EXTERNAL_CONSTANTS, FULL_ROUNDS, INTERNAL_CONSTANTS, WIDTH,
+};
+
+/// Restricts the auction to the addresses of a whitelist, whose Merkle root is the last `DIGEST` public values, see
+/// `whitelist::Whitelist`. On every new bidder row the `MembershipCols` hash the range holding `read_address` and a
+/// Merkle path from it to the root: a member starts its range, any other address lies strictly inside it. The bid of
+/// a bidder that is not a member is invalid, it never wins and counts as an error in the statistics and the reveal.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MerkleWhitelist;
+
+impl Gadget for MerkleWhitelist {
+    fn name(&self) -> &'static str {
+        "whitelist"
+    }
+
+    fn columns(&self) -> Range<usize> {
+        NUM_BID_COLS..NUM_BID_COLS + NUM_MEMBERSHIP_COLS
+    }
+
+    fn column_name(&self, index: usize) -> String {
+        match index.checked_sub(NUM_BID_COLS) {
+            Some(index) => format!("membership.{}", MembershipCols::<u8>::column_name(index)),
+            None => column_name(index),
+        }
+    }
+
+    fn eval<AB: SectionBuilder>(&self, builder: &mut AB) {
+        eval_membership(builder);
+    }
+}
+
+pub fn eval_membership<AB: SectionBuilder>(builder: &mut AB) {
+    let main = builder.main();
+    let public_values = builder.public_values();
+    let root: [AB::Expr; DIGEST] = core::array::from_fn(|i| public_values[public_values.len() - DIGEST + i].into());
+    let local_row = main.row_slice(0);
+    let next_row = main.row_slice(1);
+    let local: &BidCols<AB::Var> = (*local_row).borrow();
+    let next: &BidCols<AB::Var> = (*next_row).borrow();
+    let membership: &MembershipCols<AB::Var> = (*local_row)[NUM_BID_COLS..].borrow();
+    let next_membership: &MembershipCols<AB::Var> = (*next_row)[NUM_BID_COLS..].borrow();
+    let new_bidder: AB::Expr = local.new_bidder.into();
+
+    // the address and its membership hold for every row of the bidder
+    builder.section("bidder");
+    builder.assert_bool(membership.is_member);
+    let same_bidder = AB::Expr::one() - next.new_bidder - next.is_dummy;
+    builder.when_transition().when(same_bidder.clone()).assert_eq(next_membership.is_member, membership.is_member);
+    for i in 0..ADDRESS_BYTES {
+        builder.when_transition().when(same_bidder.clone()).assert_eq(next.read_address[i], local.read_address[i]);
+    }
+
+    // the bid of an outsider is an error, whatever it decrypts to
+    builder.section("error");
+    let valid = (AB::Expr::one() - local.is_error) * next_membership.is_member;
+    builder.when(next.computing_winner).when(AB::Expr::one() - next.is_dummy).assert_eq(next.is_error, AB::Expr::one() - valid);
+
+    // a member starts its range, an outsider lies strictly inside it
+    builder.section("range");
+    let address: [AB::Expr; ADDRESS_LIMBS] = core::array::from_fn(|i| {
+        let bytes = &local.read_address[i * ADDRESS_LIMB_BYTES..(i + 1) * ADDRESS_LIMB_BYTES];
+        bytes.iter().fold(AB::Expr::zero(), |limb, byte| limb * AB::Expr::from_canonical_u32(256) + *byte)
+    });
+    let low = membership.low.map(Into::into);
+    let next_listed = membership.next.map(Into::into);
+    for (low, address) in low.iter().zip(&address) {
+        builder.when(new_bidder.clone()).when(membership.is_member).assert_eq(low.clone(), address.clone());
+    }
+    let outsider = new_bidder.clone() * (AB::Expr::one() - membership.is_member);
+    eval_less_than(builder, outsider.clone(), &low, &address, &membership.above_bits, &membership.above_borrows);
+    eval_less_than(builder, outsider, &address, &next_listed, &membership.below_bits, &membership.below_borrows);
+
+    // the leaf is the hash of the limbs of both ends of the range
+    builder.section("leaf");
+    let input: [AB::Expr; WIDTH] = core::array::from_fn(|i| match i.checked_sub(ADDRESS_LIMBS) {
+        None => low[i].clone(),
+        Some(i) => next_listed[i].clone(),
+    });
+    let mut node = eval_permutation(builder, new_bidder.clone(), input, &membership.leaf_rounds);
+
+    // every level hashes the node with its sibling, in the order of the path bit
+    builder.section("path");
+    for level in 0..WHITELIST_DEPTH {
+        let bit = membership.path_bits[level];
+        builder.assert_bool(bit);
+        let inputs = &membership.level_inputs[level * WIDTH..(level + 1) * WIDTH];
+        for i in 0..DIGEST {
+            let sibling = membership.siblings[level * DIGEST + i];
+            builder.when(new_bidder.clone()).assert_eq(inputs[i], node[i].clone() + bit * (sibling - node[i].clone()));
+            builder.when(new_bidder.clone()).assert_eq(inputs[DIGEST + i], sibling + bit * (node[i].clone() - sibling));
+        }
+        let rounds = &membership.level_rounds[level * PERMUTATION_COLS..(level + 1) * PERMUTATION_COLS];
+        node = eval_permutation(builder, new_bidder.clone(), core::array::from_fn(|i| inputs[i].into()), rounds);
+    }
+
+    // every path, a member's or an outsider's, leads to the public root
+    builder.section("root");
+    for (node, root) in node.into_iter().zip(root) {
+        builder.when(new_bidder.clone()).assert_eq(node, root);
+    }
+}
+
+/// Checks, when `condition` holds, that `lower < upper`, both in big-endian limbs of `ADDRESS_LIMB_BITS` bits: that
+/// `bits` hold every limb of `upper - lower - 1` from its least significant bit, and `borrows[i]` whether limb `i + 1`
+/// borrows from limb `i`, the most significant limb borrowing nothing.
+fn eval_less_than<AB: AirBuilder>(
+    builder: &mut AB,
+    condition: AB::Expr,
+    lower: &[AB::Expr; ADDRESS_LIMBS],
+    upper: &[AB::Expr; ADDRESS_LIMBS],
+    bits: &[AB::Var],
+    borrows: &[AB::Var],
+) {
+    for x in bits.iter().chain(borrows) {
+        builder.assert_bool(*x);
+    }
+    let limb_base = AB::Expr::from_canonical_u64(1 << ADDRESS_LIMB_BITS);
+    for i in 0..ADDRESS_LIMBS {
+        let limb = bits[i * ADDRESS_LIMB_BITS..(i + 1) * ADDRESS_LIMB_BITS]
+            .iter()
+            .rev()
+            .fold(AB::Expr::zero(), |limb, bit| limb.double() + *bit);
+        // the least significant limb takes the 1 off, the others what the limb below borrows
+        let lent = if i == ADDRESS_LIMBS - 1 { AB::Expr::one() } else { borrows[i].into() };
+        let borrowed = if i == 0 { AB::Expr::zero() } else { limb_base.clone() * borrows[i - 1] };
+        builder.when(condition.clone()).assert_eq(limb, upper[i].clone() - lower[i].clone() - lent + borrowed);
+    }
+}
+
+/// Checks, when `condition` holds, that `rounds` are the rounds of the permutation of `input` (see
+/// `poseidon2::Rounds`) and returns the digest they end with.
+pub(crate) fn eval_permutation<AB: AirBuilder>(
+    builder: &mut AB,
+    condition: AB::Expr,
+    input: [AB::Expr; WIDTH],
+    rounds: &[AB::Var],
+) -> [AB::Expr; DIGEST] {
+    let (full, partial) = rounds.split_at(FULL_ROUNDS * WIDTH);
+    let mut state = input;
+    external_layer(&mut state);
+    for round in 0..FULL_ROUNDS {
+        if round == FULL_ROUNDS / 2 {
+            for (k, constant) in INTERNAL_CONSTANTS.into_iter().enumerate() {
+                let output = sbox(state[0].clone() + AB::Expr::from_canonical_u64(constant));
+                builder.when(condition.clone()).assert_eq(partial[k], output);
+                state[0] = partial[k].into();
+                internal_layer(&mut state);
+            }
+        }
+        for (x, constant) in state.iter_mut().zip(EXTERNAL_CONSTANTS[round]) {
+            *x = sbox(x.clone() + AB::Expr::from_canonical_u64(constant));
+        }
+        external_layer(&mut state);
+        for (i, x) in state.iter_mut().enumerate() {
+            let column = full[round * WIDTH + i];
+            builder.when(condition.clone()).assert_eq(column, x.clone());
+            *x = column.into();
+        }
+    }
+    core::array::from_fn(|i| state[i].clone())
+}
@@ -0,0 +1,40 @@
+//! Verifier of silent-bid auction proofs.
+//!
+//! Holds the auction AIR, the STARK config and the proof file format, without the trace generation, so that
+//! bidders and auditors can check a published proof with `verify_auction`, and the contract a bidder's proof that
+//! its bid is well formed with `verify_bid`.
+//!
+//! The crate is `no_std` with `alloc`. The default `std` feature adds reading and writing proof files and names
+//! the sections of the constraint debugger; the `wasm` feature exports `wasm::verify_auction_proof` and
+//! `verify_auction_proof_with_public_values` to JavaScript.
+
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[macro_use]
+mod macros;
+pub mod abi;
+pub mod air;
+pub mod bid_proof;
+pub mod columns;
+pub mod config;
+pub mod debug_builder;
+pub mod gadgets;
+pub mod poseidon2;
+pub mod proof_file;
+pub mod public_input;
+pub mod receipt;
+pub mod reveal;
+pub mod transcript;
+pub mod utils;
+mod verify;
+pub mod whitelist;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use public_input::{AuctionOutcome, AuctionPublicValues, AuctionResolution, AuctionStatistics};
+pub use bid_proof::verify_bid;
+pub use verify::{verify_auction, VerifyError};
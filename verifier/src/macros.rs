@@ -0,0 +1,127 @@
+/// Declares a `#[repr(C)]` trace column struct together with the boilerplate that has to follow
+/// its field list:
+///
+/// - `$num_cols`, the number of columns in a row,
+/// - a module `$indices` with the index of the first column of every field, e.g. `IS_READING`,
+/// - `Default`, flattening into a row with `to_vec` and viewing a row as the struct with `borrow`,
+/// - `column_name`/`column_names` to show columns by name, array fields are suffixed with `[i]`,
+/// - one chainable setter per field, e.g. `set_is_reading`.
+///
+/// Fields must be `T` or `[T; N]`.
+macro_rules! trace_columns {
+    (
+        $(#[$attr:meta])*
+        pub struct $name:ident<T> {
+            $(
+                $(#[$field_attr:meta])*
+                pub $field:ident: $ty:ty,
+            )*
+        }
+        width = $num_cols:ident;
+        indices = $indices:ident;
+    ) => {
+        $(#[$attr])*
+        #[repr(C)]
+        pub struct $name<T> {
+            $(
+                $(#[$field_attr])*
+                pub $field: $ty,
+            )*
+        }
+
+        pub const $num_cols: usize = core::mem::size_of::<$name<u8>>();
+
+        #[allow(dead_code)]
+        pub mod $indices {
+            use super::$name;
+            paste::paste! {
+                $(pub const [<$field:upper>]: usize = core::mem::offset_of!($name<u8>, $field);)*
+            }
+        }
+
+        impl<T: Default> Default for $name<T> {
+            fn default() -> Self {
+                // built as a row, since arrays longer than 32 have no `Default`, see `as_slice` for the layout
+                let row: [T; $num_cols] = core::array::from_fn(|_| T::default());
+                unsafe { core::mem::transmute_copy(&core::mem::ManuallyDrop::new(row)) }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl<T> $name<T> {
+            /// Every field name with the index of its first column, in row order.
+            pub const FIELDS: &'static [(&'static str, usize)] = &[
+                $((stringify!($field), core::mem::offset_of!($name<u8>, $field)),)*
+            ];
+
+            /// Name of the field column `index` belongs to.
+            pub fn field_name(index: usize) -> &'static str {
+                Self::FIELDS[Self::field_position(index)].0
+            }
+
+            pub fn column_name(index: usize) -> alloc::string::String {
+                let pos = Self::field_position(index);
+                let (name, start) = Self::FIELDS[pos];
+                let end = Self::FIELDS.get(pos + 1).map_or($num_cols, |(_, next)| *next);
+                if end - start == 1 {
+                    alloc::string::ToString::to_string(name)
+                } else {
+                    alloc::format!("{}[{}]", name, index - start)
+                }
+            }
+
+            fn field_position(index: usize) -> usize {
+                assert!(index < $num_cols, "column {} out of range", index);
+                Self::FIELDS.iter().rposition(|(_, start)| *start <= index).unwrap()
+            }
+
+            pub fn column_names() -> alloc::vec::Vec<alloc::string::String> {
+                (0..$num_cols).map(Self::column_name).collect()
+            }
+
+            pub fn as_slice(&self) -> &[T] {
+                // `#[repr(C)]` with only `T` and `[T; N]` fields is laid out like `[T; $num_cols]`
+                unsafe { core::slice::from_raw_parts(self as *const Self as *const T, $num_cols) }
+            }
+
+            pub fn to_vec(&self) -> alloc::vec::Vec<T>
+            where
+                T: Clone,
+            {
+                self.as_slice().to_vec()
+            }
+
+            paste::paste! {
+                $(
+                    pub fn [<set_ $field>](&mut self, value: $ty) -> &mut Self {
+                        self.$field = value;
+                        self
+                    }
+                )*
+            }
+        }
+
+        /// Borrows the first `$num_cols` columns of a row, other columns may follow them.
+        impl<T> core::borrow::Borrow<$name<T>> for [T] {
+            fn borrow(&self) -> &$name<T> {
+                debug_assert!(self.len() >= $num_cols);
+                let (prefix, shorts, suffix) = unsafe { self[..$num_cols].align_to::<$name<T>>() };
+                debug_assert!(prefix.is_empty(), "Alignment should match");
+                debug_assert!(suffix.is_empty(), "Alignment should match");
+                debug_assert_eq!(shorts.len(), 1);
+                &shorts[0]
+            }
+        }
+
+        impl<T> core::borrow::BorrowMut<$name<T>> for [T] {
+            fn borrow_mut(&mut self) -> &mut $name<T> {
+                debug_assert!(self.len() >= $num_cols);
+                let (prefix, shorts, suffix) = unsafe { self[..$num_cols].align_to_mut::<$name<T>>() };
+                debug_assert!(prefix.is_empty(), "Alignment should match");
+                debug_assert!(suffix.is_empty(), "Alignment should match");
+                debug_assert_eq!(shorts.len(), 1);
+                &mut shorts[0]
+            }
+        }
+    };
+}
@@ -0,0 +1,182 @@
+//! The Poseidon2 permutation of `p3-poseidon2` over Goldilocks with width 8, the `x^7` S-box, 8 full and 22 partial
+//! rounds, with the Horizen Labs round constants and internal diagonal of `p3-goldilocks` and their external matrix.
+//!
+//! The hashes outside the AIR go through `p3-poseidon2` itself. The AIR and the trace keep the state of every round,
+//! which that permutation does not expose, so they apply the same layers written over `AbstractField`: to column
+//! expressions in the AIR and to field elements in `permute_with_rounds`, which the tests check against `permute`.
+
+use p3_field::{AbstractField, Field};
+use p3_goldilocks::{
+    DiffusionMatrixGoldilocks, HL_GOLDILOCKS_8_EXTERNAL_ROUND_CONSTANTS, HL_GOLDILOCKS_8_INTERNAL_ROUND_CONSTANTS,
+    MATRIX_DIAG_8_GOLDILOCKS_U64,
+};
+use p3_poseidon2::{Poseidon2, Poseidon2ExternalMatrixHL};
+use p3_symmetric::Permutation;
+use crate::columns::{ADDRESS_BYTES, ADDRESS_LIMBS, ADDRESS_LIMB_BYTES, PERMUTATION_COLS};
+use crate::config::Val;
+
+pub const WIDTH: usize = 8;
+/// Field elements of a hash, taken from the start of the permuted state.
+pub const DIGEST: usize = 4;
+pub const FULL_ROUNDS: usize = 8;
+pub const PARTIAL_ROUNDS: usize = 22;
+const SBOX_DEGREE: u64 = 7;
+
+pub const EXTERNAL_CONSTANTS: [[u64; WIDTH]; FULL_ROUNDS] = HL_GOLDILOCKS_8_EXTERNAL_ROUND_CONSTANTS;
+pub const INTERNAL_CONSTANTS: [u64; PARTIAL_ROUNDS] = HL_GOLDILOCKS_8_INTERNAL_ROUND_CONSTANTS;
+pub const INTERNAL_DIAGONAL: [u64; WIDTH] = MATRIX_DIAG_8_GOLDILOCKS_U64;
+
+pub type Perm = Poseidon2<Val, Poseidon2ExternalMatrixHL, DiffusionMatrixGoldilocks, WIDTH, SBOX_DEGREE>;
+
+pub fn poseidon2() -> Perm {
+    Poseidon2::new(
+        FULL_ROUNDS,
+        EXTERNAL_CONSTANTS.map(|round| round.map(Val::from_canonical_u64)).to_vec(),
+        Poseidon2ExternalMatrixHL,
+        PARTIAL_ROUNDS,
+        INTERNAL_CONSTANTS.map(Val::from_canonical_u64).to_vec(),
+        DiffusionMatrixGoldilocks,
+    )
+}
+
+pub fn permute(input: [Val; WIDTH]) -> [Val; WIDTH] {
+    poseidon2().permute(input)
+}
+
+/// Hash of `input`, the first `DIGEST` elements of its permutation.
+pub fn hash(input: [Val; WIDTH]) -> [Val; DIGEST] {
+    let state = permute(input);
+    core::array::from_fn(|i| state[i])
+}
+
+pub fn sbox<E: AbstractField>(x: E) -> E {
+    let x2 = x.clone() * x.clone();
+    let x3 = x2.clone() * x.clone();
+    x3.clone() * x3 * x
+}
+
+/// The external linear layer of `Poseidon2ExternalMatrixHL`: the 4x4 matrix of Horizen Labs on each half, then the
+/// sum of both halves added to each.
+pub fn external_layer<E: AbstractField>(state: &mut [E; WIDTH]) {
+    let k = |n: u8| E::from_canonical_u8(n);
+    for half in state.chunks_mut(4) {
+        let [a, b, c, d] = [half[0].clone(), half[1].clone(), half[2].clone(), half[3].clone()];
+        half[0] = a.clone() * k(5) + b.clone() * k(7) + c.clone() + d.clone() * k(3);
+        half[1] = a.clone() * k(4) + b.clone() * k(6) + c.clone() + d.clone();
+        half[2] = a.clone() + b.clone() * k(3) + c.clone() * k(5) + d.clone() * k(7);
+        half[3] = a + b + c * k(4) + d * k(6);
+    }
+    for i in 0..4 {
+        let sum = state[i].clone() + state[i + 4].clone();
+        state[i] += sum.clone();
+        state[i + 4] += sum;
+    }
+}
+
+/// The internal linear layer of `DiffusionMatrixGoldilocks`: every element times its diagonal entry, plus the sum
+/// of the state.
+pub fn internal_layer<E: AbstractField>(state: &mut [E; WIDTH]) {
+    let sum = state.iter().cloned().fold(E::zero(), |acc, x| acc + x);
+    for (x, diagonal) in state.iter_mut().zip(INTERNAL_DIAGONAL) {
+        *x = x.clone() * E::from_canonical_u64(diagonal) + sum.clone();
+    }
+}
+
+/// The state after every full round and the S-box output of every partial round, which the AIR takes as columns.
+pub struct Rounds<F> {
+    pub full: [[F; WIDTH]; FULL_ROUNDS],
+    pub partial: [F; PARTIAL_ROUNDS],
+}
+
+impl<F: Copy> Rounds<F> {
+    /// The rounds in the order of their columns, full rounds first.
+    pub fn columns(&self) -> [F; PERMUTATION_COLS] {
+        core::array::from_fn(|i| match i.checked_sub(FULL_ROUNDS * WIDTH) {
+            None => self.full[i / WIDTH][i % WIDTH],
+            Some(round) => self.partial[round],
+        })
+    }
+
+    pub fn digest(&self) -> [F; DIGEST] {
+        core::array::from_fn(|i| self.full[FULL_ROUNDS - 1][i])
+    }
+}
+
+pub fn permute_with_rounds<F: Field>(input: [F; WIDTH]) -> Rounds<F> {
+    let mut state = input;
+    let mut rounds = Rounds { full: [[F::zero(); WIDTH]; FULL_ROUNDS], partial: [F::zero(); PARTIAL_ROUNDS] };
+    external_layer(&mut state);
+    for (round, (full, constants)) in rounds.full.iter_mut().zip(EXTERNAL_CONSTANTS).enumerate() {
+        if round == FULL_ROUNDS / 2 {
+            for (partial, constant) in rounds.partial.iter_mut().zip(INTERNAL_CONSTANTS) {
+                state[0] = sbox(state[0] + F::from_canonical_u64(constant));
+                *partial = state[0];
+                internal_layer(&mut state);
+            }
+        }
+        for (x, constant) in state.iter_mut().zip(constants) {
+            *x = sbox(*x + F::from_canonical_u64(constant));
+        }
+        external_layer(&mut state);
+        *full = state;
+    }
+    rounds
+}
+
+/// The input of the hash of two digests, their concatenation.
+pub fn compress_input<F: Field>(left: [F; DIGEST], right: [F; DIGEST]) -> [F; WIDTH] {
+    core::array::from_fn(|i| if i < DIGEST { left[i] } else { right[i - DIGEST] })
+}
+
+/// Hash of two digests, the first `DIGEST` elements of their permuted concatenation.
+pub fn compress(left: [Val; DIGEST], right: [Val; DIGEST]) -> [Val; DIGEST] {
+    hash(compress_input(left, right))
+}
+
+/// The 5 little-endian `u32` words of an address followed by zeros.
+pub fn address_input<F: Field>(address: &[u8; ADDRESS_BYTES]) -> [F; WIDTH] {
+    let mut input = [F::zero(); WIDTH];
+    for (x, word) in input.iter_mut().zip(address.chunks(4)) {
+        *x = F::from_canonical_u32(u32::from_le_bytes(word.try_into().unwrap()));
+    }
+    input
+}
+
+/// The address as a 160-bit big-endian number, in `ADDRESS_LIMBS` big-endian limbs of `ADDRESS_LIMB_BYTES` bytes.
+pub fn address_limbs(address: &[u8; ADDRESS_BYTES]) -> [u64; ADDRESS_LIMBS] {
+    core::array::from_fn(|i| {
+        let bytes = &address[i * ADDRESS_LIMB_BYTES..(i + 1) * ADDRESS_LIMB_BYTES];
+        bytes.iter().fold(0, |limb, byte| limb << 8 | *byte as u64)
+    })
+}
+
+/// The limbs of `low` then those of `next`, the input of the hash of a whitelist range, see `whitelist::Whitelist`.
+pub fn range_input<F: Field>(low: &[u8; ADDRESS_BYTES], next: &[u8; ADDRESS_BYTES]) -> [F; WIDTH] {
+    let (low, next) = (address_limbs(low), address_limbs(next));
+    core::array::from_fn(|i| F::from_canonical_u64(if i < ADDRESS_LIMBS { low[i] } else { next[i - ADDRESS_LIMBS] }))
+}
+
+/// The input of the hash of a partial decryption into its operator's chain: the chain, the partial, then zeros, see
+/// `gadgets::OperatorShares`.
+pub fn share_input<F: Field>(chain: [F; DIGEST], partial: F) -> [F; WIDTH] {
+    core::array::from_fn(|i| match i {
+        _ if i < DIGEST => chain[i],
+        _ if i == DIGEST => partial,
+        _ => F::zero(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::AbstractField;
+    use crate::config::Val;
+    use super::{permute, permute_with_rounds, FULL_ROUNDS, WIDTH};
+
+    #[test]
+    fn rounds_end_with_the_p3_permutation() {
+        for seed in 0..4u64 {
+            let input: [Val; WIDTH] = core::array::from_fn(|i| Val::from_canonical_u64(seed * 1_000_003 + i as u64));
+            assert_eq!(permute_with_rounds(input).full[FULL_ROUNDS - 1], permute(input));
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::path::Path;
+use p3_field::PrimeField64;
+use p3_uni_stark::{Proof, StarkGenericConfig};
+use serde::{Deserialize, Serialize};
+use crate::abi::{from_abi_bytes, to_abi_bytes};
+use crate::config::EVM_CONFIG_ID;
+
+/// First bytes of the binary form, followed by the format version as a little-endian `u32` and the postcard
+/// encoding of `ProofFile`.
+pub const MAGIC: [u8; 4] = *b"SBID";
+
+/// First bytes of the form of `EVM_CONFIG_ID` proofs, followed by the format version as a big-endian `u32` and
+/// the `abi` encoding of `ProofFile`.
+pub const EVM_MAGIC: [u8; 4] = *b"SBEV";
+
+/// Bump on any change to `ProofFile`, to the serialization of its fields or to the layout of its public values.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// A proof together with what is needed to verify it in another process.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ProofFile<SC: StarkGenericConfig> {
+    pub version: u32,
+    /// Which `StarkConfig` produced the proof, see `config::CONFIG_ID`.
+    pub config_id: String,
+    /// Canonical values of the public inputs, in the order of `AuctionPublicValues::to_field_elements`.
+    pub public_values: Vec<u64>,
+    pub proof: Proof<SC>,
+}
+
+impl<SC: StarkGenericConfig> ProofFile<SC> {
+    pub fn new<F: PrimeField64>(config_id: &str, public_values: &[F], proof: Proof<SC>) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            config_id: config_id.to_string(),
+            public_values: public_values.iter().map(|v| v.as_canonical_u64()).collect(),
+            proof,
+        }
+    }
+
+    pub fn public_values<F: PrimeField64>(&self) -> Result<Vec<F>, String> {
+        self.public_values.iter().map(|v| {
+            if *v >= F::ORDER_U64 {
+                return Err(format!("public value {} is not a canonical field element", v));
+            }
+            Ok(F::from_canonical_u64(*v))
+        }).collect()
+    }
+
+    /// The binary form: the `abi` layout for proofs of `EVM_CONFIG_ID`, which are verified on chain, and postcard
+    /// otherwise.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        if self.config_id == EVM_CONFIG_ID {
+            let mut bytes = EVM_MAGIC.to_vec();
+            bytes.extend(self.version.to_be_bytes());
+            bytes.extend(to_abi_bytes(self).expect("proof file is serializable"));
+            return bytes;
+        }
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(self.version.to_le_bytes());
+        bytes.extend(postcard::to_allocvec(self).expect("proof file is serializable"));
+        bytes
+    }
+
+    /// Reads the binary form, rejecting other format versions and proofs of another config than `config_id`.
+    pub fn from_bytes(bytes: &[u8], config_id: &str) -> Result<Self, String> {
+        if let Some(body) = bytes.strip_prefix(&EVM_MAGIC[..]) {
+            if body.len() < 4 {
+                return Err("truncated proof file".to_string());
+            }
+            let (version, body) = body.split_at(4);
+            check_version(u32::from_be_bytes(version.try_into().unwrap()))?;
+            let file: Self = from_abi_bytes(body).map_err(|e| format!("invalid proof file: {}", e))?;
+            return file.check(config_id);
+        }
+        let body = bytes.strip_prefix(&MAGIC[..]).ok_or("not a silent-bid proof file")?;
+        if body.len() < 4 {
+            return Err("truncated proof file".to_string());
+        }
+        let (version, body) = body.split_at(4);
+        check_version(u32::from_le_bytes(version.try_into().unwrap()))?;
+        let file: Self = postcard::from_bytes(body).map_err(|e| format!("invalid proof file: {}", e))?;
+        file.check(config_id)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("proof file is serializable")
+    }
+
+    pub fn from_json(json: &str, config_id: &str) -> Result<Self, String> {
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| format!("invalid proof file: {}", e))?;
+        let version = value.get("version").and_then(|v| v.as_u64()).ok_or("proof file has no version")?;
+        check_version(version as u32)?;
+        let file: Self = serde_json::from_value(value).map_err(|e| format!("invalid proof file: {}", e))?;
+        file.check(config_id)
+    }
+
+    /// Writes the JSON form when `path` ends with `.json`, the binary form otherwise.
+    #[cfg(feature = "std")]
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let content = if is_json(path) { self.to_json().into_bytes() } else { self.to_bytes() };
+        fs::write(path, content).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn read(path: impl AsRef<Path>, config_id: &str) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        if is_json(path) {
+            let json = String::from_utf8(content).map_err(|_| format!("{} is not valid utf-8", path.display()))?;
+            Self::from_json(&json, config_id)
+        } else {
+            Self::from_bytes(&content, config_id)
+        }
+    }
+
+    fn check(self, config_id: &str) -> Result<Self, String> {
+        check_version(self.version)?;
+        if self.config_id != config_id {
+            return Err(format!("proof was made with config {}, expected {}", self.config_id, config_id));
+        }
+        Ok(self)
+    }
+}
+
+fn check_version(version: u32) -> Result<(), String> {
+    if version != FORMAT_VERSION {
+        return Err(format!("unsupported proof format version {}, expected {}", version, FORMAT_VERSION));
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
@@ -0,0 +1,505 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use p3_field::PrimeField64;
+use crate::columns::{ADDRESS_BYTES, BASE, MAX_OPERATORS, PUBLIC_EXPONENT_BITS};
+use crate::poseidon2::DIGEST;
+use crate::utils::{bytes_to_address, hex_to_bytes};
+
+pub type Address = [u8; ADDRESS_BYTES];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicBid {
+    pub bidder: Address,
+    /// 4 little-endian bytes per encrypted 16-bit limb, least significant limb first.
+    pub encrypted_amount: Vec<u8>,
+}
+
+impl PublicBid {
+    /// Parses a `0x`-prefixed bidder address and a hex ciphertext, as bids are published.
+    pub fn from_hex(bidder: &str, encrypted_amount: &str) -> Result<Self, String> {
+        Ok(Self {
+            bidder: parse_address(bidder)?,
+            encrypted_amount: hex_to_bytes(encrypted_amount.trim_start_matches("0x"))?,
+        })
+    }
+
+    pub fn bidder_hex(&self) -> String {
+        bytes_to_address(&self.bidder)
+    }
+}
+
+/// The rolling hash of the bids that the contract accumulates as they arrive and the AIR recomputes: every 4
+/// little-endian bytes of the bidder address and then of the encrypted amount, padded with zeros to a multiple of
+/// 4 bytes, is one term.
+#[derive(Clone, Copy, Debug)]
+pub struct BidHash<F> {
+    hash: F,
+    lim: F,
+}
+
+impl<F: PrimeField64> Default for BidHash<F> {
+    fn default() -> Self {
+        Self { hash: F::zero(), lim: F::one() }
+    }
+}
+
+impl<F: PrimeField64> BidHash<F> {
+    pub fn of(bids: &[PublicBid]) -> u64 {
+        let mut hash = Self::default();
+        bids.iter().for_each(|bid| hash.absorb(bid));
+        hash.value()
+    }
+
+    pub fn absorb(&mut self, bid: &PublicBid) {
+        let mut encrypted = bid.encrypted_amount.clone();
+        encrypted.resize(encrypted.len().next_multiple_of(4), 0);
+        for chunk in bid.bidder.chunks(4).chain(encrypted.chunks(4)) {
+            self.hash += F::from_canonical_u32(u32::from_le_bytes(chunk.try_into().unwrap())) * self.lim;
+            self.lim *= F::from_canonical_usize(BASE);
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.hash.as_canonical_u64()
+    }
+}
+
+/// Parses a hex address with or without the `0x` prefix.
+pub fn parse_address(address: &str) -> Result<Address, String> {
+    let bytes = hex_to_bytes(address.trim_start_matches("0x"))?;
+    bytes.as_slice().try_into().map_err(|_| format!("address {} is not {} bytes long", address, ADDRESS_BYTES))
+}
+
+/// Number of public values of the auction AIR with a single owner.
+pub const NUM_PUBLIC_VALUES: usize = 4 + ADDRESS_BYTES;
+
+/// Tags of the first public value of the decryption's section, telling how the bids are decrypted.
+pub const THRESHOLD_TAG: u64 = 1;
+pub const TIME_LOCK_TAG: u64 = 2;
+/// Tag of the section of the whitelist root, the last one.
+pub const WHITELIST_TAG: u64 = 3;
+/// Tag of the section of the statistics, after the decryption's.
+pub const STATISTICS_TAG: u64 = 4;
+/// Number of statistics an auction can publish, see `AuctionStatistics`.
+pub const NUM_STATISTICS: usize = 3;
+/// Tag of the section of the commitment to every bid in full reveal mode, after the statistics.
+pub const REVEAL_TAG: u64 = 5;
+/// Tag of the section of the commitment the bid receipts lead to, after the full reveal one.
+pub const RECEIPTS_TAG: u64 = 6;
+/// Most sections after the auction's public values: the decryption, the statistics, the reveal, the receipts and the
+/// whitelist, in this order.
+pub const MAX_SECTIONS: usize = 5;
+
+/// How the bids are decrypted. Other than with an owner, the scheme's own public values make the first section of the
+/// auction's, after a tag.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Decryption {
+    /// With the owner's private exponent.
+    #[default]
+    Owner,
+    /// By combining operators' partial decryptions, given the commitment each operator published to its partials and
+    /// the public exponent the decryptions are re-encrypted with, see `OperatorShares`.
+    Threshold { commitments: Vec<[u64; DIGEST]>, public_exponent: u64 },
+    /// By solving each bid's time-lock puzzle with this many squarings.
+    TimeLock(u64),
+}
+
+impl Decryption {
+    pub(crate) fn to_canonical(&self) -> Vec<u64> {
+        match self {
+            Decryption::Owner => vec![],
+            Decryption::Threshold { commitments, public_exponent } => {
+                [&[THRESHOLD_TAG], commitments.as_flattened(), &[*public_exponent]].concat()
+            }
+            Decryption::TimeLock(squarings) => vec![TIME_LOCK_TAG, *squarings],
+        }
+    }
+
+    fn from_canonical(values: &[u64]) -> Result<Self, String> {
+        match values {
+            [] => Ok(Decryption::Owner),
+            [THRESHOLD_TAG, commitments @ .., public_exponent]
+                if commitments.len() % DIGEST == 0
+                    && (1..=MAX_OPERATORS).contains(&(commitments.len() / DIGEST))
+                    && (2..1 << PUBLIC_EXPONENT_BITS).contains(public_exponent) =>
+            {
+                let commitments = commitments.chunks(DIGEST).map(|digest| digest.try_into().unwrap()).collect();
+                Ok(Decryption::Threshold { commitments, public_exponent: *public_exponent })
+            }
+            [TIME_LOCK_TAG, squarings] if *squarings > 0 => Ok(Decryption::TimeLock(*squarings)),
+            _ => Err(format!("invalid decryption public values {:?}", values)),
+        }
+    }
+}
+
+/// Aggregates over all the bids that an auction publishes beside its winner, each only when the auction switches it
+/// on (`Some`). A bid is valid when it decrypts without error, and the sum is over the amounts of the valid bids.
+///
+/// Their section follows the decryption's: `STATISTICS_TAG`, a bit mask of the switched on statistics and one value
+/// per statistic, 0 when it is off.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AuctionStatistics {
+    pub valid_bids: Option<u64>,
+    pub invalid_bids: Option<u64>,
+    pub bid_sum: Option<u64>,
+}
+
+impl AuctionStatistics {
+    /// The statistics among `values` (valid bids, invalid bids, bid sum) that are `switched_on`.
+    pub fn select(values: [u64; NUM_STATISTICS], switched_on: [bool; NUM_STATISTICS]) -> Self {
+        let [valid_bids, invalid_bids, bid_sum] = core::array::from_fn(|i| switched_on[i].then_some(values[i]));
+        Self { valid_bids, invalid_bids, bid_sum }
+    }
+
+    pub fn values(&self) -> [Option<u64>; NUM_STATISTICS] {
+        [self.valid_bids, self.invalid_bids, self.bid_sum]
+    }
+
+    pub fn switched_on(&self) -> [bool; NUM_STATISTICS] {
+        self.values().map(|value| value.is_some())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values().iter().all(Option::is_none)
+    }
+
+    /// The mean amount of the valid bids, rounded down, when both their number and their sum are published.
+    pub fn average_bid(&self) -> Option<u64> {
+        match (self.valid_bids, self.bid_sum) {
+            (Some(count), Some(sum)) if count > 0 => Some(sum / count),
+            _ => None,
+        }
+    }
+
+    fn to_canonical(self) -> Vec<u64> {
+        if self.is_empty() {
+            return vec![];
+        }
+        let mask = self.switched_on().iter().enumerate().map(|(i, on)| (*on as u64) << i).sum();
+        [STATISTICS_TAG, mask].into_iter().chain(self.values().map(|value| value.unwrap_or(0))).collect()
+    }
+
+    fn from_canonical(mask: u64, values: [u64; NUM_STATISTICS]) -> Result<Self, String> {
+        let switched_on = core::array::from_fn(|i| mask >> i & 1 == 1);
+        let unused = values.iter().zip(switched_on).any(|(value, on)| !on && *value != 0);
+        if mask == 0 || mask >> NUM_STATISTICS != 0 || unused {
+            return Err(format!("invalid statistics public values {} {:?}", mask, values));
+        }
+        Ok(Self::select(values, switched_on))
+    }
+}
+
+/// Public values of the auction AIR: the RSA modulus, the bid hash, the rolling hash base, the winning amount,
+/// then one element per byte of the winner address. An auction with any other than an owner decryption, statistics,
+/// a full reveal, receipts or a whitelist follows them with a header, the number of its sections and the length of
+/// each, then the sections in this order, each starting with its tag: those of `decryption`, those of `statistics`,
+/// the commitment to every bid in full reveal mode, the one of the receipts when the auction issues them and the
+/// whitelist root when only listed addresses may bid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuctionPublicValues {
+    pub modulus: u64,
+    pub hash_value: u64,
+    pub base: u64,
+    pub winner_amount: u64,
+    pub winner_address: Address,
+    pub decryption: Decryption,
+    pub statistics: AuctionStatistics,
+    /// The hash chain of every decrypted bid, in full reveal mode, see `reveal::commitment`.
+    pub reveal: Option<[u64; DIGEST]>,
+    /// The hash chain of every bid with a random salt, when bidders get receipts, see `receipt::BidReceipt`.
+    pub receipts: Option<[u64; DIGEST]>,
+    /// The Merkle root of the allowed addresses, see `whitelist::Whitelist`.
+    pub whitelist: Option<[u64; DIGEST]>,
+}
+
+/// What the auction publishes: the winner, the winning amount and the hash of all bids.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuctionOutcome {
+    /// All zero when no bid is valid.
+    pub winner: Address,
+    pub winner_amount: u64,
+    pub hash_value: u64,
+}
+
+impl AuctionOutcome {
+    /// Whether the owner proved that no bid is valid, so that nobody wins.
+    pub fn is_no_contest(&self) -> bool {
+        self.winner == [0; ADDRESS_BYTES]
+    }
+}
+
+/// How an auction ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuctionResolution {
+    /// The owner proved the winner in time.
+    Won(AuctionOutcome),
+    /// The owner proved in time that no bid is valid.
+    NoContest { hash_value: u64 },
+    /// No result was accepted before the proof deadline: bidders reclaim their deposits and the owner's collateral
+    /// is slashed.
+    TimedOut,
+}
+
+impl From<AuctionOutcome> for AuctionResolution {
+    fn from(outcome: AuctionOutcome) -> Self {
+        if outcome.is_no_contest() {
+            AuctionResolution::NoContest { hash_value: outcome.hash_value }
+        } else {
+            AuctionResolution::Won(outcome)
+        }
+    }
+}
+
+impl AuctionPublicValues {
+    /// Public values of a no-contest proof: no winner and a zero amount.
+    pub fn no_contest(modulus: u64, hash_value: u64) -> Self {
+        Self::new(modulus, hash_value, 0, [0; ADDRESS_BYTES])
+    }
+
+    pub fn new(modulus: u64, hash_value: u64, winner_amount: u64, winner_address: Address) -> Self {
+        Self {
+            modulus,
+            hash_value,
+            base: BASE as u64,
+            winner_amount,
+            winner_address,
+            decryption: Decryption::Owner,
+            statistics: AuctionStatistics::default(),
+            reveal: None,
+            receipts: None,
+            whitelist: None,
+        }
+    }
+
+    pub fn with_decryption(mut self, decryption: Decryption) -> Self {
+        self.decryption = decryption;
+        self
+    }
+
+    pub fn with_statistics(mut self, statistics: AuctionStatistics) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
+    pub fn with_reveal(mut self, commitment: [u64; DIGEST]) -> Self {
+        self.reveal = Some(commitment);
+        self
+    }
+
+    pub fn with_receipts(mut self, commitment: [u64; DIGEST]) -> Self {
+        self.receipts = Some(commitment);
+        self
+    }
+
+    pub fn with_whitelist(mut self, root: [u64; DIGEST]) -> Self {
+        self.whitelist = Some(root);
+        self
+    }
+
+    /// Index of the first public value of the decryption, after its tag.
+    pub fn decryption_index(&self) -> usize {
+        self.section_index(&[THRESHOLD_TAG, TIME_LOCK_TAG]) + 1
+    }
+
+    /// Index of the public value of the first statistic, after the tag and the mask.
+    pub fn statistics_index(&self) -> usize {
+        self.section_index(&[STATISTICS_TAG]) + 2
+    }
+
+    /// Index of the public value of the first element of the reveal commitment, after its tag.
+    pub fn reveal_index(&self) -> usize {
+        self.section_index(&[REVEAL_TAG]) + 1
+    }
+
+    /// Index of the public value of the first element of the receipts commitment, after its tag.
+    pub fn receipts_index(&self) -> usize {
+        self.section_index(&[RECEIPTS_TAG]) + 1
+    }
+
+    /// Index of the tag of the section starting with one of `tags`, or of where it would be.
+    fn section_index(&self, tags: &[u64]) -> usize {
+        let sections = self.sections();
+        let before = sections.iter().take_while(|section| !tags.contains(&section[0])).map(Vec::len).sum::<usize>();
+        NUM_PUBLIC_VALUES + 1 + sections.len() + before
+    }
+
+    /// The sections after the header, in order.
+    fn sections(&self) -> Vec<Vec<u64>> {
+        let mut sections = vec![self.decryption.to_canonical(), self.statistics.to_canonical()];
+        sections.extend([(REVEAL_TAG, self.reveal), (RECEIPTS_TAG, self.receipts), (WHITELIST_TAG, self.whitelist)]
+            .into_iter()
+            .filter_map(|(tag, digest)| Some([&[tag][..], &digest?].concat())));
+        sections.retain(|section| !section.is_empty());
+        sections
+    }
+
+    pub fn to_field_elements<F: PrimeField64>(&self) -> Vec<F> {
+        self.to_canonical().into_iter().map(F::from_canonical_u64).collect()
+    }
+
+    /// The canonical `u64` values stored in proof files, see `from_canonical`.
+    pub fn to_canonical(&self) -> Vec<u64> {
+        let mut public_values = vec![self.modulus, self.hash_value, self.base, self.winner_amount];
+        public_values.extend(self.winner_address.iter().map(|b| *b as u64));
+        let sections = self.sections();
+        if !sections.is_empty() {
+            public_values.push(sections.len() as u64);
+            public_values.extend(sections.iter().map(|section| section.len() as u64));
+            public_values.extend(sections.concat());
+        }
+        public_values
+    }
+
+    pub fn from_field_elements<F: PrimeField64>(values: &[F]) -> Result<Self, String> {
+        Self::from_canonical(&values.iter().map(|v| v.as_canonical_u64()).collect::<Vec<_>>())
+    }
+
+    /// Reads the canonical `u64` values stored in proof files.
+    pub fn from_canonical(values: &[u64]) -> Result<Self, String> {
+        if values.len() < NUM_PUBLIC_VALUES {
+            return Err(format!("expected at least {} public values, got {}", NUM_PUBLIC_VALUES, values.len()));
+        }
+        let mut winner_address: Address = [0; ADDRESS_BYTES];
+        for (byte, value) in winner_address.iter_mut().zip(&values[4..NUM_PUBLIC_VALUES]) {
+            *byte = u8::try_from(*value).map_err(|_| format!("winner address byte {} is out of range", value))?;
+        }
+        let mut public_values = Self {
+            modulus: values[0],
+            hash_value: values[1],
+            base: values[2],
+            winner_amount: values[3],
+            winner_address,
+            decryption: Decryption::Owner,
+            statistics: AuctionStatistics::default(),
+            reveal: None,
+            receipts: None,
+            whitelist: None,
+        };
+        // each section is where the header puts it, and its tag only tells which one it is
+        let mut last = None;
+        for section in split_sections(&values[NUM_PUBLIC_VALUES..])? {
+            let position = section_position(section[0]).ok_or_else(|| format!("unknown public values section {:?}", section))?;
+            if last.is_some_and(|last| last >= position) {
+                return Err(format!("public values section {:?} is out of order", section));
+            }
+            last = Some(position);
+            match section {
+                [STATISTICS_TAG, mask, statistics @ ..] if statistics.len() == NUM_STATISTICS => {
+                    public_values.statistics = AuctionStatistics::from_canonical(*mask, statistics.try_into().unwrap())?;
+                }
+                [REVEAL_TAG, ..] => public_values.reveal = Some(section_digest(section)?),
+                [RECEIPTS_TAG, ..] => public_values.receipts = Some(section_digest(section)?),
+                [WHITELIST_TAG, ..] => public_values.whitelist = Some(section_digest(section)?),
+                [STATISTICS_TAG, ..] => return Err(format!("invalid statistics public values {:?}", section)),
+                _ => public_values.decryption = Decryption::from_canonical(section)?,
+            }
+        }
+        Ok(public_values)
+    }
+
+    pub fn outcome(&self) -> AuctionOutcome {
+        AuctionOutcome {
+            winner: self.winner_address,
+            winner_amount: self.winner_amount,
+            hash_value: self.hash_value,
+        }
+    }
+}
+
+/// Splits the public values after the auction's ones into the sections their header lists, none without a header.
+fn split_sections(values: &[u64]) -> Result<Vec<&[u64]>, String> {
+    let Some((count, mut rest)) = values.split_first() else {
+        return Ok(vec![]);
+    };
+    let count = *count as usize;
+    if !(1..=MAX_SECTIONS).contains(&count) || rest.len() < count {
+        return Err(format!("invalid public values header {:?}", values));
+    }
+    let lengths;
+    (lengths, rest) = rest.split_at(count);
+    let mut sections = Vec::with_capacity(count);
+    for length in lengths {
+        if *length == 0 || *length > rest.len() as u64 {
+            return Err(format!("public values section of length {} does not fit in {} values", length, rest.len()));
+        }
+        let section;
+        (section, rest) = rest.split_at(*length as usize);
+        sections.push(section);
+    }
+    if !rest.is_empty() {
+        return Err(format!("{} public values after the last section", rest.len()));
+    }
+    Ok(sections)
+}
+
+/// Position of the section starting with `tag` in the order of the sections.
+fn section_position(tag: u64) -> Option<usize> {
+    match tag {
+        THRESHOLD_TAG | TIME_LOCK_TAG => Some(0),
+        STATISTICS_TAG => Some(1),
+        REVEAL_TAG => Some(2),
+        RECEIPTS_TAG => Some(3),
+        WHITELIST_TAG => Some(4),
+        _ => None,
+    }
+}
+
+/// The digest of a section, after its tag.
+fn section_digest(section: &[u64]) -> Result<[u64; DIGEST], String> {
+    section[1..].try_into().map_err(|_| format!("public values section {:?} is not a tag and a digest", section))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use super::{
+        AuctionPublicValues, AuctionStatistics, Decryption, NUM_PUBLIC_VALUES, RECEIPTS_TAG, REVEAL_TAG, STATISTICS_TAG,
+        WHITELIST_TAG,
+    };
+
+    #[test]
+    fn sections_are_read_where_the_header_puts_them() {
+        let owner = AuctionPublicValues::new(1_000_003, 42, 3035, [0x11; 20]);
+        assert_eq!(owner.to_canonical().len(), NUM_PUBLIC_VALUES);
+
+        // digests that look like the tags of the sections after them
+        let threshold = Decryption::Threshold { commitments: vec![[WHITELIST_TAG, 8, 9, 10], [11, 12, 13, 14]], public_exponent: 5153 };
+        let statistics = AuctionStatistics::select([2, 0, 6048], [true, false, true]);
+        let extended = owner.clone()
+            .with_decryption(threshold)
+            .with_statistics(statistics)
+            .with_reveal([RECEIPTS_TAG, 1, 2, 3])
+            .with_receipts([STATISTICS_TAG, 2, 0, 6048])
+            .with_whitelist([REVEAL_TAG, 4, 5, 6]);
+        let partial = owner.clone().with_decryption(Decryption::TimeLock(100)).with_receipts([WHITELIST_TAG, 1, 2, 3]);
+        for public_values in [owner.clone(), extended.clone(), partial] {
+            assert_eq!(AuctionPublicValues::from_canonical(&public_values.to_canonical()), Ok(public_values));
+        }
+        let values = extended.to_canonical();
+        assert_eq!(values[NUM_PUBLIC_VALUES..NUM_PUBLIC_VALUES + 6], [5, 10, 5, 5, 5, 5]);
+        assert_eq!(values[extended.decryption_index() - 1..extended.decryption_index() + 1], [1, WHITELIST_TAG]);
+        assert_eq!(values[extended.statistics_index()], 2);
+        assert_eq!(values[extended.reveal_index()..extended.reveal_index() + 4], [RECEIPTS_TAG, 1, 2, 3]);
+        assert_eq!(values[extended.receipts_index()..extended.receipts_index() + 4], [STATISTICS_TAG, 2, 0, 6048]);
+
+        // the header must cover exactly the sections, in order, and an owner without any has none
+        let with_header = |header: &[u64], sections: &[u64]| [&owner.to_canonical()[..], header, sections].concat();
+        let reveal = [REVEAL_TAG, 1, 2, 3, 4];
+        let whitelist = [WHITELIST_TAG, 1, 2, 3, 4];
+        assert!(AuctionPublicValues::from_canonical(&with_header(&[1, 5], &reveal)).is_ok());
+        for values in [
+            with_header(&[0], &[]),
+            with_header(&[1, 5], &[&reveal[..], &[7]].concat()),
+            with_header(&[1, 6], &reveal),
+            with_header(&[2, 5, 0], &reveal),
+            with_header(&[2, 5, 5], &[whitelist, reveal].concat()),
+            with_header(&[2, 5, 5], &[reveal, reveal].concat()),
+            with_header(&[1, 5], &[7, 1, 2, 3, 4]),
+            with_header(&[1, 4], &reveal[..4]),
+        ] {
+            assert!(AuctionPublicValues::from_canonical(&values).is_err(), "{:?}", values);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Private receipts of the bids of an auction that issues them. The proof commits to every bid with a Merkle tree of
+//! `RECEIPT_DEPTH` levels: its leaves are the hashes of `reveal::RevealedBid` in submission order, each bid hashing a
+//! salt of the prover in place of its nonce so that the hashes hide the amounts, and an empty node is the zero
+//! digest. The path of a bid is the sibling of its node on every level. A bidder checks its receipt against
+//! `AuctionPublicValues::receipts` with `check`.
+//!
+//! The amounts stay hidden from the other bidders only if the owner draws every salt uniformly at random and keeps
+//! them secret: a path holds the hash of the neighbouring bid, whose address is public and whose amount can be
+//! guessed, so a predictable salt lets its receipt holder find that amount by trying them all. Nothing in the proof
+//! shows how the salts were chosen, and the owner, who decrypts every bid, can always disclose the amounts anyway.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use p3_field::{AbstractField, PrimeField64};
+use crate::columns::RECEIPT_DEPTH;
+use crate::config::Val;
+use crate::poseidon2::{compress, DIGEST};
+use crate::public_input::{parse_address, Address, AuctionPublicValues};
+use crate::reveal::RevealedBid;
+use crate::utils::bytes_to_address;
+
+/// What the proof holds about one bid: its amount as decrypted and whether it is valid, with the salt, its place in
+/// the tree and the path that lead to the public commitment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BidReceipt {
+    pub bidder: Address,
+    pub bid_amount: u64,
+    pub is_valid: bool,
+    pub salt: u64,
+    pub index: usize,
+    /// The sibling of the bid's node on every level, from the leaves up.
+    pub path: Vec<[u64; DIGEST]>,
+}
+
+/// The receipts of `bids`, in order, each salted with the salt of the same index.
+pub fn issue(bids: &[RevealedBid], salts: &[u64]) -> Vec<BidReceipt> {
+    let salted = salted(bids, salts);
+    let levels = levels(&salted);
+    salted.iter().enumerate().map(|(index, bid)| BidReceipt {
+        bidder: bid.bidder,
+        bid_amount: bid.bid_amount,
+        is_valid: bid.is_valid,
+        salt: bid.nonce,
+        index,
+        path: levels[..RECEIPT_DEPTH].iter().enumerate().map(|(level, nodes)| {
+            let sibling = nodes.get((index >> level) ^ 1).copied().unwrap_or([Val::zero(); DIGEST]);
+            sibling.map(|x| x.as_canonical_u64())
+        }).collect(),
+    }).collect()
+}
+
+/// The public commitment the receipts of `bids` lead to, the root of their tree.
+pub fn commitment(bids: &[RevealedBid], salts: &[u64]) -> [u64; DIGEST] {
+    let levels = levels(&salted(bids, salts));
+    levels[RECEIPT_DEPTH].first().copied().unwrap_or([Val::zero(); DIGEST]).map(|x| x.as_canonical_u64())
+}
+
+/// The bids as the tree hashes them, the salt in place of the nonce.
+pub fn salted(bids: &[RevealedBid], salts: &[u64]) -> Vec<RevealedBid> {
+    bids.iter().zip(salts).map(|(bid, salt)| RevealedBid { nonce: *salt, ..*bid }).collect()
+}
+
+/// The nodes of every level of the tree of `bids`, from the leaves up to the root. A node without a right sibling
+/// is hashed with the zero digest, and the empty nodes after the last bid are left out.
+fn levels(bids: &[RevealedBid]) -> Vec<Vec<[Val; DIGEST]>> {
+    assert!(bids.len() <= 1 << RECEIPT_DEPTH, "at most 2^{} bids get receipts", RECEIPT_DEPTH);
+    let mut levels = vec![bids.iter().map(RevealedBid::hash).collect::<Vec<_>>()];
+    for level in 0..RECEIPT_DEPTH {
+        let nodes = levels[level]
+            .chunks(2)
+            .map(|pair| compress(pair[0], pair.get(1).copied().unwrap_or([Val::zero(); DIGEST])))
+            .collect();
+        levels.push(nodes);
+    }
+    levels
+}
+
+impl BidReceipt {
+    /// The commitment the path leads to from the hash of the bid.
+    pub fn root(&self) -> [u64; DIGEST] {
+        let bid = RevealedBid { bidder: self.bidder, bid_amount: self.bid_amount, nonce: self.salt, is_valid: self.is_valid };
+        let root = self.path.iter().enumerate().fold(bid.hash(), |node, (level, sibling)| {
+            let sibling = sibling.map(Val::from_canonical_u64);
+            if self.index >> level & 1 == 1 {
+                compress(sibling, node)
+            } else {
+                compress(node, sibling)
+            }
+        });
+        root.map(|x| x.as_canonical_u64())
+    }
+
+    /// Checks that the proof of `public_values` holds this receipt.
+    pub fn check(&self, public_values: &AuctionPublicValues) -> Result<(), String> {
+        match public_values.receipts {
+            None => Err("the auction does not issue receipts".into()),
+            Some(expected) if expected != self.root() => Err("the receipt does not lead to the proof's commitment".into()),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Reads the lines written by `Display`: the `0x`-prefixed address, the amount, `valid` or `invalid`, the salt
+    /// and the index, then one line of 4 elements per level of the path, from the leaves up.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let first = lines.next().ok_or("empty receipt")?;
+        let [bidder, bid_amount, status, salt, index] = first.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(format!("expected `<address> <amount> <valid|invalid> <salt> <index>`, got {:?}", first));
+        };
+        let is_valid = match status {
+            "valid" => true,
+            "invalid" => false,
+            _ => return Err(format!("invalid status {}, expected valid or invalid", status)),
+        };
+        let index = match index.parse() {
+            Ok(index) if index < 1 << RECEIPT_DEPTH => index,
+            _ => return Err(format!("invalid index {}", index)),
+        };
+        let path: Vec<_> = lines.map(parse_digest).collect::<Result<_, _>>()?;
+        if path.len() != RECEIPT_DEPTH {
+            return Err(format!("the path has {} levels, expected {}", path.len(), RECEIPT_DEPTH));
+        }
+        Ok(Self {
+            bidder: parse_address(bidder)?,
+            bid_amount: parse_element(bid_amount)?,
+            is_valid,
+            salt: parse_element(salt)?,
+            index,
+            path,
+        })
+    }
+}
+
+impl fmt::Display for BidReceipt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.is_valid { "valid" } else { "invalid" };
+        writeln!(f, "{} {} {} {} {}", bytes_to_address(&self.bidder), self.bid_amount, status, self.salt, self.index)?;
+        for digest in &self.path {
+            writeln!(f, "{} {} {} {}", digest[0], digest[1], digest[2], digest[3])?;
+        }
+        Ok(())
+    }
+}
+
+/// A canonical field element, so that the receipt hashes as the AIR did.
+fn parse_element(value: &str) -> Result<u64, String> {
+    match value.parse() {
+        Ok(element) if element < Val::ORDER_U64 => Ok(element),
+        _ => Err(format!("invalid field element {}", value)),
+    }
+}
+
+fn parse_digest(line: &str) -> Result<[u64; DIGEST], String> {
+    let elements = line.split_whitespace().map(parse_element).collect::<Result<Vec<_>, _>>()?;
+    elements.try_into().map_err(|_| format!("expected {} elements, got {:?}", DIGEST, line))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use crate::columns::RECEIPT_DEPTH;
+    use crate::reveal::RevealedBid;
+    use super::{commitment, issue, BidReceipt};
+
+    #[test]
+    fn every_receipt_leads_to_the_commitment() {
+        let bids: Vec<_> = (0..5u8)
+            .map(|i| RevealedBid { bidder: [i; 20], bid_amount: 100 + i as u64, nonce: 7, is_valid: i != 2 })
+            .collect();
+        let salts = [11, 12, 13, 14, 15];
+        let receipts = issue(&bids, &salts);
+        for receipt in &receipts {
+            assert_eq!(receipt.path.len(), RECEIPT_DEPTH);
+            assert_eq!(receipt.root(), commitment(&bids, &salts));
+            assert_eq!(BidReceipt::parse(&receipt.to_string()).unwrap(), *receipt);
+        }
+        assert_ne!(commitment(&bids, &salts), commitment(&bids, &[11, 12, 13, 14, 16]));
+        assert_ne!(commitment(&bids[..4], &salts), commitment(&bids, &salts));
+
+        // the path only leads to the root from the bid's own place
+        let higher = BidReceipt { bid_amount: 101, ..receipts[0].clone() };
+        assert_ne!(higher.root(), commitment(&bids, &salts));
+        let moved = BidReceipt { index: 1, ..receipts[0].clone() };
+        assert_ne!(moved.root(), commitment(&bids, &salts));
+        assert!(BidReceipt::parse("0x01 1 valid 2 0\n0 0 0 0").is_err());
+    }
+}
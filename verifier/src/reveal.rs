@@ -0,0 +1,98 @@
+//! The bids an auction in full reveal mode discloses once it closes. The proof commits to them with a hash chain:
+//! starting from the zero digest, every bid in submission order is hashed with `poseidon2` and then compressed with
+//! the chain so far. Anyone holding the list the prover published recomputes the chain with `commitment` and checks
+//! it against `AuctionPublicValues::reveal`.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use p3_field::{AbstractField, Field, PrimeField64};
+use crate::config::Val;
+use crate::poseidon2::{address_input, compress, permute_with_rounds, DIGEST, WIDTH};
+use crate::public_input::{parse_address, Address, AuctionPublicValues};
+use crate::utils::bytes_to_address;
+
+/// A decrypted bid. An invalid bid, that decrypts to an error, is revealed with a zero amount and nonce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RevealedBid {
+    pub bidder: Address,
+    pub bid_amount: u64,
+    pub nonce: u64,
+    pub is_valid: bool,
+}
+
+impl RevealedBid {
+    /// The input of the bid's hash: the words of the address as in `poseidon2::address_input`, the amount, the nonce
+    /// and 1 for an invalid bid.
+    pub fn leaf_input<F: Field>(&self) -> [F; WIDTH] {
+        let mut input = address_input(&self.bidder);
+        input[5] = F::from_canonical_u64(self.bid_amount);
+        input[6] = F::from_canonical_u64(self.nonce);
+        input[7] = F::from_bool(!self.is_valid);
+        input
+    }
+
+    /// The hash of the bid, chained by `commitment`.
+    pub fn hash(&self) -> [Val; DIGEST] {
+        permute_with_rounds(self.leaf_input()).digest()
+    }
+
+    /// Reads a line written by `Display`: the `0x`-prefixed address, the amount, the nonce and `valid` or `invalid`.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let [bidder, bid_amount, nonce, status] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(format!("expected `<address> <amount> <nonce> <valid|invalid>`, got {:?}", line));
+        };
+        let number = |value: &str| value.parse().map_err(|_| format!("invalid number {}", value));
+        let is_valid = match status {
+            "valid" => true,
+            "invalid" => false,
+            _ => return Err(format!("invalid status {}, expected valid or invalid", status)),
+        };
+        Ok(Self { bidder: parse_address(bidder)?, bid_amount: number(bid_amount)?, nonce: number(nonce)?, is_valid })
+    }
+}
+
+impl fmt::Display for RevealedBid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.is_valid { "valid" } else { "invalid" };
+        write!(f, "{} {} {} {}", bytes_to_address(&self.bidder), self.bid_amount, self.nonce, status)
+    }
+}
+
+/// The hash chain of `bids`, in order, that the AIR publishes in full reveal mode.
+pub fn commitment(bids: &[RevealedBid]) -> [u64; DIGEST] {
+    let chain = bids.iter().fold([Val::zero(); DIGEST], |chain, bid| compress(chain, bid.hash()));
+    chain.map(|x| x.as_canonical_u64())
+}
+
+/// Checks that `bids` are the ones revealed by the proof of `public_values`.
+pub fn check_revealed(public_values: &AuctionPublicValues, bids: &[RevealedBid]) -> Result<(), String> {
+    match public_values.reveal {
+        None => Err("the auction does not reveal its bids".into()),
+        Some(expected) if expected != commitment(bids) => Err("the bids are not the ones the proof revealed".into()),
+        Some(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use super::{commitment, RevealedBid};
+
+    #[test]
+    fn the_commitment_binds_every_bid() {
+        let bids = [
+            RevealedBid { bidder: [1; 20], bid_amount: 2023, nonce: 17, is_valid: true },
+            RevealedBid { bidder: [2; 20], bid_amount: 0, nonce: 0, is_valid: false },
+        ];
+        for bid in &bids {
+            assert_eq!(RevealedBid::parse(&bid.to_string()).unwrap(), *bid);
+        }
+        let lower = RevealedBid { bid_amount: 2022, ..bids[0] };
+        assert_ne!(commitment(&[lower, bids[1]]), commitment(&bids));
+        assert_ne!(commitment(&[bids[1], bids[0]]), commitment(&bids));
+        assert_ne!(commitment(&bids[..1]), commitment(&bids));
+        assert!(RevealedBid::parse("0x01 1 2 valid").is_err());
+    }
+}
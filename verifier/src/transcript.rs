@@ -0,0 +1,162 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use p3_challenger::{CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger};
+use p3_field::{ExtensionField, PrimeField64};
+use p3_keccak::Keccak256Hash;
+use p3_symmetric::{CryptographicHasher, Hash};
+
+/// Prefix of the initial state, followed by the label of the config.
+pub const DOMAIN_SEPARATOR: &[u8] = b"silent-bid/evm-transcript/v1";
+/// First byte hashed when absorbing observed words into the state.
+pub const ABSORB_TAG: u8 = 0x01;
+/// First byte hashed when squeezing a word out of the state.
+pub const SQUEEZE_TAG: u8 = 0x02;
+
+/// A Fiat-Shamir transcript that a Solidity verifier replays with one `keccak256(abi.encodePacked(..))` per step.
+///
+/// - `state = keccak256(DOMAIN_SEPARATOR ‖ label)` initially.
+/// - Observing a field element appends its canonical value as a big-endian `uint256` word to the pending input,
+///   observing a commitment appends its 32 digest bytes.
+/// - Before sampling, pending input is absorbed with `state = keccak256(ABSORB_TAG ‖ state ‖ input)` and the
+///   squeeze counter restarts at 0.
+/// - Each sample is the word `keccak256(SQUEEZE_TAG ‖ state ‖ uint256(counter++))`. A field element is the word
+///   `mod p`, `sample_bits(bits)` its low `bits` bits, and an extension element `D` consecutive field elements.
+#[derive(Clone, Debug)]
+pub struct EvmChallenger<F> {
+    state: [u8; 32],
+    input: Vec<u8>,
+    counter: u64,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField64> EvmChallenger<F> {
+    pub fn new(label: &[u8]) -> Self {
+        let state = Keccak256Hash.hash_iter(DOMAIN_SEPARATOR.iter().chain(label).copied());
+        Self { state, input: Vec::new(), counter: 0, _field: PhantomData }
+    }
+
+    fn absorb(&mut self) {
+        if self.input.is_empty() {
+            return;
+        }
+        let input = [ABSORB_TAG].into_iter().chain(self.state).chain(self.input.drain(..));
+        self.state = Keccak256Hash.hash_iter(input);
+        self.counter = 0;
+    }
+
+    fn squeeze(&mut self) -> [u8; 32] {
+        self.absorb();
+        let mut counter = [0; 32];
+        counter[24..].copy_from_slice(&self.counter.to_be_bytes());
+        self.counter += 1;
+        Keccak256Hash.hash_iter([SQUEEZE_TAG].into_iter().chain(self.state).chain(counter))
+    }
+}
+
+/// `word mod p` for a big-endian 256-bit word.
+fn reduce<F: PrimeField64>(word: &[u8; 32]) -> F {
+    let p = F::ORDER_U64 as u128;
+    let value = word.chunks(8).fold(0u128, |acc, limb| {
+        let limb = u64::from_be_bytes(limb.try_into().unwrap()) as u128;
+        ((acc << 64) + limb) % p
+    });
+    F::from_canonical_u64(value as u64)
+}
+
+impl<F: PrimeField64> CanObserve<F> for EvmChallenger<F> {
+    fn observe(&mut self, value: F) {
+        self.input.extend([0; 24]);
+        self.input.extend(value.as_canonical_u64().to_be_bytes());
+    }
+}
+
+impl<F: PrimeField64, const N: usize> CanObserve<Hash<F, u8, N>> for EvmChallenger<F> {
+    fn observe(&mut self, digest: Hash<F, u8, N>) {
+        self.input.extend(digest);
+    }
+}
+
+impl<F: PrimeField64, EF: ExtensionField<F>> CanSample<EF> for EvmChallenger<F> {
+    fn sample(&mut self) -> EF {
+        EF::from_base_fn(|_| reduce(&self.squeeze()))
+    }
+}
+
+impl<F: PrimeField64> CanSampleBits<usize> for EvmChallenger<F> {
+    fn sample_bits(&mut self, bits: usize) -> usize {
+        assert!(bits < usize::BITS as usize && bits < 64);
+        let word = self.squeeze();
+        let low = u64::from_be_bytes(word[24..].try_into().unwrap());
+        (low & ((1 << bits) - 1)) as usize
+    }
+}
+
+impl<F: PrimeField64> FieldChallenger<F> for EvmChallenger<F> {}
+
+impl<F: PrimeField64> GrindingChallenger for EvmChallenger<F> {
+    type Witness = F;
+
+    fn grind(&mut self, bits: usize) -> F {
+        let witness = (0..F::ORDER_U64)
+            .map(F::from_canonical_u64)
+            .find(|witness| self.clone().check_witness(bits, *witness))
+            .expect("failed to find witness");
+        assert!(self.check_witness(bits, witness));
+        witness
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_challenger::{CanObserve, CanSample, CanSampleBits, GrindingChallenger};
+    use p3_field::{AbstractField, PrimeField64};
+    use p3_goldilocks::Goldilocks;
+    use p3_keccak::Keccak256Hash;
+    use p3_symmetric::{CryptographicHasher, Hash};
+    use super::{EvmChallenger, ABSORB_TAG, DOMAIN_SEPARATOR, SQUEEZE_TAG};
+
+    fn word(value: u64) -> [u8; 32] {
+        let mut word = [0; 32];
+        word[24..].copy_from_slice(&value.to_be_bytes());
+        word
+    }
+
+    fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+        Keccak256Hash.hash_iter(parts.iter().flat_map(|part| part.iter().copied()))
+    }
+
+    #[test]
+    fn follows_the_documented_layout() {
+        let mut challenger = EvmChallenger::<Goldilocks>::new(b"label");
+        challenger.observe(Goldilocks::from_canonical_u64(5));
+        challenger.observe(Hash::<Goldilocks, u8, 32>::from([7; 32]));
+        let sampled: Goldilocks = challenger.sample();
+        let bits = challenger.sample_bits(10);
+
+        let state = keccak(&[DOMAIN_SEPARATOR, b"label"]);
+        let state = keccak(&[&[ABSORB_TAG], &state, &word(5), &[7; 32]]);
+        let first = keccak(&[&[SQUEEZE_TAG], &state, &word(0)]);
+        let second = keccak(&[&[SQUEEZE_TAG], &state, &word(1)]);
+        let expected = first.iter().fold(0u128, |acc, b| ((acc << 8) + *b as u128) % Goldilocks::ORDER_U64 as u128);
+        assert_eq!(sampled.as_canonical_u64(), expected as u64);
+        assert_eq!(bits, u16::from_be_bytes([second[30], second[31]]) as usize & 0x3ff);
+    }
+
+    #[test]
+    fn labels_separate_transcripts() {
+        let sample = |label: &[u8]| -> Goldilocks { EvmChallenger::<Goldilocks>::new(label).sample() };
+        assert_eq!(sample(b"a"), sample(b"a"));
+        assert_ne!(sample(b"a"), sample(b"b"));
+    }
+
+    #[test]
+    fn grinding_finds_a_checkable_witness() {
+        let mut prover = EvmChallenger::<Goldilocks>::new(b"pow");
+        prover.observe(Goldilocks::from_canonical_u64(1));
+        let mut verifier = prover.clone();
+        let witness = prover.grind(8);
+        assert!(verifier.check_witness(8, witness));
+        let after: Goldilocks = prover.sample();
+        assert_eq!(after, verifier.sample());
+    }
+}
@@ -0,0 +1,44 @@
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+pub fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>()
+}
+pub fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, String> {
+    if hex.len() % 2 != 0 {
+        return Err("Hex string must have an even number of characters.".to_string());
+    }
+
+    // over bytes, since the string may hold multi-byte characters
+    let digit = |i: usize| {
+        char::from(hex.as_bytes()[i]).to_digit(16).ok_or_else(|| format!("Invalid hex character at index {}", i))
+    };
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| Ok((digit(i)? * 16 + digit(i + 1)?) as u8))
+        .collect()
+}
+
+pub fn bytes_to_address(address_bytes: &[u8]) -> String {
+    // Convert bytes to a hex string
+    let address_hex = hex::encode(address_bytes);
+
+    // Add "0x" prefix to make it look like a typical Ethereum address
+    let ethereum_address = format!("0x{}", address_hex);
+    ethereum_address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hex_to_bytes;
+
+    #[test]
+    fn hex_to_bytes_rejects_non_hex_input() {
+        assert_eq!(hex_to_bytes("00fFa1").unwrap(), [0x00, 0xff, 0xa1]);
+        assert!(hex_to_bytes("+1").is_err());
+        assert!(hex_to_bytes("é").is_err());
+        assert!(hex_to_bytes("0é0").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use core::fmt;
+use p3_uni_stark::verify;
+use crate::air::ProverAir;
+use crate::columns::BASE;
+use crate::config::{make_challenger, make_config, make_evm_challenger, make_evm_config, EvmConfig, MyConfig, Val, CONFIG_ID, EVM_CONFIG_ID};
+use crate::proof_file::{ProofFile, EVM_MAGIC};
+use crate::public_input::{AuctionOutcome, AuctionPublicValues};
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The bytes are not a proof file of this format version and config.
+    InvalidProofFile(String),
+    /// The public values are not the ones the proof was made for, or not ones the AIR supports.
+    InvalidPublicValues(String),
+    /// The STARK proof does not verify.
+    InvalidProof(String),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InvalidProofFile(e) => write!(f, "invalid proof file: {}", e),
+            VerifyError::InvalidPublicValues(e) => write!(f, "invalid public values: {}", e),
+            VerifyError::InvalidProof(e) => write!(f, "invalid proof: {}", e),
+        }
+    }
+}
+
+impl core::error::Error for VerifyError {}
+
+/// Verifies a proof file (see `ProofFile::to_bytes`) against the public values the caller expects, and returns
+/// the outcome they commit to.
+/// Proofs of both `CONFIG_ID` and `EVM_CONFIG_ID` are accepted, told apart by the magic of their binary form.
+pub fn verify_auction(proof_bytes: &[u8], public_values: &AuctionPublicValues) -> Result<AuctionOutcome, VerifyError> {
+    let air = ProverAir::for_auction(vec![], public_values);
+    let public_values_elements = public_values.to_field_elements::<Val>();
+    let result = if proof_bytes.starts_with(&EVM_MAGIC) {
+        let proof_file = ProofFile::<EvmConfig>::from_bytes(proof_bytes, EVM_CONFIG_ID).map_err(VerifyError::InvalidProofFile)?;
+        check_public_values(&proof_file.public_values, public_values)?;
+        verify(&make_evm_config(), &air, &mut make_evm_challenger(), &proof_file.proof, &public_values_elements)
+    } else {
+        let proof_file = ProofFile::<MyConfig>::from_bytes(proof_bytes, CONFIG_ID).map_err(VerifyError::InvalidProofFile)?;
+        check_public_values(&proof_file.public_values, public_values)?;
+        verify(&make_config(), &air, &mut make_challenger(), &proof_file.proof, &public_values_elements)
+    };
+    result.map_err(|e| VerifyError::InvalidProof(format!("{:?}", e)))?;
+    Ok(public_values.outcome())
+}
+
+fn check_public_values(proven: &[u64], expected: &AuctionPublicValues) -> Result<(), VerifyError> {
+    let proven = AuctionPublicValues::from_canonical(proven).map_err(VerifyError::InvalidProofFile)?;
+    if &proven != expected {
+        return Err(VerifyError::InvalidPublicValues("the proof was made for other public values".to_string()));
+    }
+    // the AIR hashes with `BASE` and does not read the public base
+    if expected.base != BASE as u64 {
+        return Err(VerifyError::InvalidPublicValues(format!("unsupported hash base {}", expected.base)));
+    }
+    Ok(())
+}
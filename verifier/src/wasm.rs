@@ -0,0 +1,65 @@
+use alloc::string::{String, ToString};
+use wasm_bindgen::prelude::*;
+use crate::public_input::{parse_address, AuctionPublicValues};
+use crate::utils::bytes_to_address;
+use crate::verify_auction;
+
+/// Outcome of a verified auction, as seen from JavaScript.
+#[wasm_bindgen]
+pub struct VerifiedAuction {
+    winner: String,
+    winner_amount: u64,
+    hash_value: u64,
+}
+
+#[wasm_bindgen]
+impl VerifiedAuction {
+    /// `0x`-prefixed lowercase hex address.
+    #[wasm_bindgen(getter)]
+    pub fn winner(&self) -> String {
+        self.winner.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = winnerAmount)]
+    pub fn winner_amount(&self) -> u64 {
+        self.winner_amount
+    }
+
+    #[wasm_bindgen(getter, js_name = hashValue)]
+    pub fn hash_value(&self) -> u64 {
+        self.hash_value
+    }
+}
+
+/// Verifies a binary proof file against the published modulus, bid hash, winning amount and winner address of an
+/// auction with owner decryption and no extension, and throws on any mismatch or invalid proof.
+#[wasm_bindgen(js_name = verifyAuctionProof)]
+pub fn verify_auction_proof(
+    proof_bytes: &[u8],
+    modulus: u64,
+    hash_value: u64,
+    winner_amount: u64,
+    winner_address: &str,
+) -> Result<VerifiedAuction, JsError> {
+    let winner_address = parse_address(winner_address).map_err(|e| JsError::new(&e))?;
+    let public_values = AuctionPublicValues::new(modulus, hash_value, winner_amount, winner_address);
+    verify(proof_bytes, &public_values)
+}
+
+/// Verifies a binary proof file against the canonical public values vector (`AuctionPublicValues::from_canonical`),
+/// which also carries the decryption, statistics, reveal, receipts and whitelist of the auction, and throws on any
+/// mismatch or invalid proof.
+#[wasm_bindgen(js_name = verifyAuctionProofWithPublicValues)]
+pub fn verify_auction_proof_with_public_values(proof_bytes: &[u8], public_values: &[u64]) -> Result<VerifiedAuction, JsError> {
+    let public_values = AuctionPublicValues::from_canonical(public_values).map_err(|e| JsError::new(&e))?;
+    verify(proof_bytes, &public_values)
+}
+
+fn verify(proof_bytes: &[u8], public_values: &AuctionPublicValues) -> Result<VerifiedAuction, JsError> {
+    let outcome = verify_auction(proof_bytes, public_values).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(VerifiedAuction {
+        winner: bytes_to_address(&outcome.winner),
+        winner_amount: outcome.winner_amount,
+        hash_value: outcome.hash_value,
+    })
+}
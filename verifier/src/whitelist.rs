@@ -0,0 +1,145 @@
+//! The Merkle tree of the addresses allowed to bid in a restricted auction, which proves both that an address is
+//! listed and that it is not. The addresses are sorted as big-endian numbers between the zero and the all-ones
+//! address, and every leaf is the Poseidon2 hash of an address and the next one (the last one twice), see
+//! `poseidon2::range_input`. A listed address starts a range and any other lies strictly inside one, so the AIR
+//! shows either with the path of a single leaf. The leaves are padded with zero digests to `2^WHITELIST_DEPTH`, every
+//! node hashes its two children with `poseidon2::compress`, and the root is a public value of the auction, see
+//! `AuctionPublicValues`.
+//!
+//! The zero and all-ones addresses bound the ranges and so count as listed; nobody holds their keys to bid with them.
+//! Whoever checks a root recomputes it from the published addresses: a root over unsorted ranges could leave a
+//! listed address inside another range.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use p3_field::{AbstractField, PrimeField64};
+use crate::columns::{ADDRESS_BYTES, WHITELIST_DEPTH};
+use crate::config::Val;
+use crate::poseidon2::{compress, hash, range_input, DIGEST};
+use crate::public_input::Address;
+
+const LAST: Address = [0xff; ADDRESS_BYTES];
+
+#[derive(Clone, Debug)]
+pub struct Whitelist {
+    /// The sorted addresses, the zero address first and the all-ones one last.
+    addresses: Vec<Address>,
+    /// The non-padding nodes of every level, the leaves first.
+    levels: Vec<Vec<[Val; DIGEST]>>,
+    /// The node of every level above only padding leaves.
+    padding: [[Val; DIGEST]; WHITELIST_DEPTH + 1],
+}
+
+/// The range of the whitelist holding an address: its first address, which is the address itself when it is
+/// listed, the next listed address and the path of their leaf.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WhitelistRange {
+    pub low: Address,
+    pub next: Address,
+    pub path: MerklePath,
+}
+
+/// The sibling of every node from a leaf up to the root, and whether that node is a right child.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerklePath {
+    pub siblings: [[u64; DIGEST]; WHITELIST_DEPTH],
+    pub is_right: [bool; WHITELIST_DEPTH],
+}
+
+impl Whitelist {
+    pub fn new(mut addresses: Vec<Address>) -> Result<Self, String> {
+        addresses.extend([[0; ADDRESS_BYTES], LAST]);
+        addresses.sort_unstable();
+        addresses.dedup();
+        if addresses.len() > 1 << WHITELIST_DEPTH {
+            let most = (1 << WHITELIST_DEPTH) - 2;
+            return Err(format!("a whitelist holds at most {} addresses, got {}", most, addresses.len() - 2));
+        }
+        let mut padding = [[Val::zero(); DIGEST]; WHITELIST_DEPTH + 1];
+        for level in 0..WHITELIST_DEPTH {
+            padding[level + 1] = compress(padding[level], padding[level]);
+        }
+        let ranges = addresses.iter().zip(addresses.iter().skip(1).chain([&LAST]));
+        let mut levels = vec![ranges.map(|(low, next)| hash(range_input(low, next))).collect::<Vec<_>>()];
+        for level in 0..WHITELIST_DEPTH {
+            let parents = levels[level]
+                .chunks(2)
+                .map(|pair| compress(pair[0], pair.get(1).copied().unwrap_or(padding[level])))
+                .collect();
+            levels.push(parents);
+        }
+        Ok(Self { addresses, levels, padding })
+    }
+
+    pub fn root(&self) -> [u64; DIGEST] {
+        let root = self.levels[WHITELIST_DEPTH].first().unwrap_or(&self.padding[WHITELIST_DEPTH]);
+        root.map(|x| x.as_canonical_u64())
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        self.addresses.binary_search(address).is_ok()
+    }
+
+    /// The range holding `address`, the one it starts when it is listed.
+    pub fn range(&self, address: &Address) -> WhitelistRange {
+        // the zero address starts the first range, so one starts at or below any address
+        let mut index = self.addresses.partition_point(|listed| listed <= address) - 1;
+        let low = self.addresses[index];
+        let next = self.addresses.get(index + 1).copied().unwrap_or(LAST);
+        let mut path = MerklePath::default();
+        for level in 0..WHITELIST_DEPTH {
+            let sibling = self.levels[level].get(index ^ 1).unwrap_or(&self.padding[level]);
+            path.siblings[level] = sibling.map(|x| x.as_canonical_u64());
+            path.is_right[level] = index & 1 == 1;
+            index >>= 1;
+        }
+        WhitelistRange { low, next, path }
+    }
+}
+
+impl WhitelistRange {
+    /// The root the path leads to from the leaf of the range.
+    pub fn root(&self) -> [u64; DIGEST] {
+        let mut node = hash(range_input(&self.low, &self.next));
+        for (sibling, is_right) in self.path.siblings.iter().zip(self.path.is_right) {
+            let sibling = sibling.map(Val::from_canonical_u64);
+            node = if is_right { compress(sibling, node) } else { compress(node, sibling) };
+        }
+        node.map(|x| x.as_canonical_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use super::Whitelist;
+
+    #[test]
+    fn ranges_lead_to_the_root() {
+        let addresses: Vec<_> = (1..6u8).map(|i| [i * 2; 20]).collect();
+        let whitelist = Whitelist::new(addresses.clone()).unwrap();
+        for address in &addresses {
+            let range = whitelist.range(address);
+            assert_eq!((range.low, range.root()), (*address, whitelist.root()));
+            assert!(whitelist.contains(address));
+        }
+
+        // any other address lies strictly inside a range
+        for outsider in [[1; 20], [3; 20], [0xee; 20]] {
+            let range = whitelist.range(&outsider);
+            assert!(range.low < outsider && outsider < range.next);
+            assert_eq!(range.root(), whitelist.root());
+            assert!(!whitelist.contains(&outsider));
+        }
+
+        // the root depends on every address but not on their order
+        let mut reordered = addresses.clone();
+        reordered.swap(0, 1);
+        assert_eq!(Whitelist::new(reordered).unwrap().root(), whitelist.root());
+        assert_ne!(Whitelist::new(addresses[1..].to_vec()).unwrap().root(), whitelist.root());
+        assert_ne!(Whitelist::new(vec![]).unwrap().root(), whitelist.root());
+    }
+}